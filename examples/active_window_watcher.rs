@@ -0,0 +1,16 @@
+use xcap::ActiveWindowWatcher;
+
+fn main() {
+    let watcher = ActiveWindowWatcher::new().unwrap();
+
+    for active_window in watcher {
+        match active_window.unwrap() {
+            Some(window) => println!(
+                "Active window: {} ({})",
+                window.title().unwrap(),
+                window.app_name().unwrap()
+            ),
+            None => println!("No active window"),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use std::{thread, time::Duration};
+use xcap::{MjpegServer, MjpegServerOptions, Monitor};
+
+fn main() {
+    let monitor = Monitor::from_point(100, 100).unwrap();
+
+    let server = MjpegServer::new(&monitor, MjpegServerOptions::default()).unwrap();
+
+    println!("stream: http://{}/stream", server.local_addr());
+    println!("snapshot: http://{}/snapshot.jpg", server.local_addr());
+
+    thread::sleep(Duration::from_secs(60));
+
+    server.stop().unwrap();
+}
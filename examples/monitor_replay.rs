@@ -0,0 +1,32 @@
+use std::{thread, time::Duration};
+use xcap::{ImageFormat, Monitor, ReplayFormat, ReplayRecorder, ReplayRecorderOptions};
+
+fn main() {
+    let monitor = Monitor::from_point(100, 100).unwrap();
+
+    let options = ReplayRecorderOptions {
+        duration: Duration::from_secs(5),
+        ..ReplayRecorderOptions::default()
+    };
+    let replay_recorder = ReplayRecorder::new(&monitor, options).unwrap();
+
+    println!("start");
+    replay_recorder.start().unwrap();
+    thread::sleep(Duration::from_secs(8));
+
+    let count = replay_recorder
+        .dump("target/replay.y4m", ReplayFormat::Y4m)
+        .unwrap();
+    println!("saved {count} frames: target/replay.y4m");
+
+    let count = replay_recorder
+        .dump(
+            "target/replay",
+            ReplayFormat::ImageSequence(ImageFormat::Png),
+        )
+        .unwrap();
+    println!("saved {count} frames: target/replay");
+
+    println!("stop");
+    replay_recorder.stop().unwrap();
+}
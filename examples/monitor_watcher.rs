@@ -0,0 +1,13 @@
+use xcap::{MonitorEvent, MonitorWatcher};
+
+fn main() {
+    for event in MonitorWatcher::new().unwrap() {
+        match event.unwrap() {
+            MonitorEvent::Added(monitor) => println!("Added {:?}", monitor.name()),
+            MonitorEvent::Removed(monitor) => println!("Removed {:?}", monitor.id()),
+            MonitorEvent::Changed(monitor, fields) => {
+                println!("Changed {:?}: {fields:?}", monitor.name())
+            }
+        }
+    }
+}
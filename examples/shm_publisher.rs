@@ -0,0 +1,44 @@
+// Publish:   cargo run --example shm_publisher --features shm
+// Subscribe: cargo run --example shm_publisher --features shm -- subscribe
+#[cfg(target_os = "linux")]
+fn main() {
+    use std::{thread, time::Duration};
+    use xcap::{Monitor, ShmPublisher, ShmPublisherOptions, ShmSubscriber};
+
+    if std::env::args().nth(1).as_deref() == Some("subscribe") {
+        let mut subscriber = ShmSubscriber::attach("xcap").unwrap();
+        loop {
+            if let Some(frame) = subscriber.recv_frame(None).unwrap() {
+                println!(
+                    "frame: {}, {}x{}, {} bytes",
+                    frame.sequence,
+                    frame.width,
+                    frame.height,
+                    frame.data.len()
+                );
+            }
+        }
+    }
+
+    let monitor = Monitor::from_point(100, 100).unwrap();
+    let (video_recorder, rx) = monitor.video_recorder().unwrap();
+    let publisher = ShmPublisher::new(ShmPublisherOptions::default()).unwrap();
+
+    thread::spawn(move || {
+        for frame in rx {
+            if let Err(err) = publisher.publish(&frame) {
+                println!("publish failed: {err:?}");
+            }
+        }
+    });
+
+    println!("publishing to /dev/shm/xcap");
+    video_recorder.start().unwrap();
+    thread::sleep(Duration::from_secs(30));
+    video_recorder.stop().unwrap();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    println!("Shared memory publishing is only supported on Linux");
+}
@@ -0,0 +1,18 @@
+use std::{thread, time::Duration};
+use xcap::{Monitor, Timelapse, TimelapseOptions};
+
+fn main() {
+    let monitors = Monitor::all().unwrap();
+
+    let options = TimelapseOptions {
+        interval: Duration::from_secs(2),
+        directory: "target/timelapse".into(),
+        max_files: Some(20),
+        ..TimelapseOptions::default()
+    };
+    let mut timelapse = Timelapse::new(monitors, options).unwrap();
+
+    thread::sleep(Duration::from_secs(10));
+    timelapse.stop().unwrap();
+    println!("saved to target/timelapse");
+}
@@ -0,0 +1,18 @@
+use std::{thread, time::Duration};
+use xcap::{Monitor, VncServer, VncServerOptions};
+
+fn main() {
+    let monitor = Monitor::from_point(100, 100).unwrap();
+
+    let options = VncServerOptions {
+        password: Some("xcap".to_string()),
+        ..VncServerOptions::default()
+    };
+    let server = VncServer::new(&monitor, options).unwrap();
+
+    println!("vnc server: {}", server.local_addr());
+
+    thread::sleep(Duration::from_secs(300));
+
+    server.stop().unwrap();
+}
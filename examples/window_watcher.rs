@@ -0,0 +1,23 @@
+use xcap::{WindowEvent, WindowWatcher};
+
+fn main() {
+    let receiver = WindowWatcher::new().unwrap().into_receiver();
+
+    for event in receiver {
+        let event = event.unwrap();
+        let id = event.window().id().unwrap();
+
+        match event {
+            WindowEvent::Created(window) => println!("Created {id}: {:?}", window.title()),
+            WindowEvent::Destroyed(_) => println!("Destroyed {id}"),
+            WindowEvent::Moved(window) => println!("Moved {id}: {:?}", (window.x(), window.y())),
+            WindowEvent::Resized(window) => {
+                println!("Resized {id}: {:?}", (window.width(), window.height()))
+            }
+            WindowEvent::TitleChanged(window) => println!("Retitled {id}: {:?}", window.title()),
+            WindowEvent::StateChanged(window) => {
+                println!("State {id}: minimized {:?}", window.is_minimized())
+            }
+        }
+    }
+}
@@ -1,6 +1,13 @@
 use crate::{
+    edid::Edid,
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    pixel_format::PixelFormat,
+    process_info::ProcessInfo,
+    transform::Transform,
+    video_recorder::{Frame, FramePool, VideoRecorderOptions},
+    watcher::{MonitorEvent, WindowEvent},
+    window_type::WindowType,
+    workspace::Workspace,
 };
 use image::RgbaImage;
 use std::sync::mpsc::Receiver;
@@ -29,6 +36,10 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
+    pub fn edid(&self) -> XCapResult<Edid> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         Err(XCapError::NotSupported)
     }
@@ -45,9 +56,26 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+    pub fn window_space_bounds(&self) -> XCapResult<(i32, i32, u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         Err(XCapError::NotSupported)
     }
+    pub fn transform(&self) -> XCapResult<Transform> {
+        Err(XCapError::NotSupported)
+    }
+    pub fn icc_profile(&self) -> XCapResult<Option<Vec<u8>>> {
+        Err(XCapError::NotSupported)
+    }
 
     pub fn scale_factor(&self) -> XCapResult<f32> {
         Err(XCapError::NotSupported)
@@ -69,6 +97,18 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
+    pub fn capture_image_as(&self, _format: PixelFormat) -> XCapResult<Frame> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_into(&self, _buffer: &mut [u8], _stride: usize) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_thumbnail(&self, _max_width: u32, _max_height: u32) -> XCapResult<RgbaImage> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn capture_region(
         &self,
         _x: u32,
@@ -79,7 +119,11 @@ impl ImplMonitor {
         Err(XCapError::NotSupported)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+    pub fn video_recorder(
+        &self,
+        _options: &VideoRecorderOptions,
+        _pool: FramePool,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
         Err(XCapError::NotSupported)
     }
 }
@@ -92,6 +136,10 @@ impl ImplWindow {
         Ok(Vec::new())
     }
 
+    pub fn from_point(_x: i32, _y: i32) -> XCapResult<ImplWindow> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn id(&self) -> XCapResult<u32> {
         Err(XCapError::NotSupported)
     }
@@ -132,10 +180,22 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         Err(XCapError::NotSupported)
     }
 
+    pub fn is_viewable(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn is_maximized(&self) -> XCapResult<bool> {
         Err(XCapError::NotSupported)
     }
@@ -144,9 +204,61 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_fullscreen(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_always_on_top(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_sticky(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_skip_taskbar(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn desktop(&self) -> XCapResult<Option<u32>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn icon(&self, _preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn process(&self) -> XCapResult<ProcessInfo> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         Err(XCapError::NotSupported)
     }
+
+    pub fn capture_image_as(&self, _format: PixelFormat) -> XCapResult<Frame> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_into(&self, _buffer: &mut [u8], _stride: usize) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_thumbnail(&self, _max_width: u32, _max_height: u32) -> XCapResult<RgbaImage> {
+        Err(XCapError::NotSupported)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -162,6 +274,49 @@ impl ImplVideoRecorder {
     }
 }
 
+#[derive(Debug)]
+pub struct ImplActiveWindowWatcher;
+
+impl ImplActiveWindowWatcher {
+    pub fn new() -> XCapResult<ImplActiveWindowWatcher> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn next_change(&mut self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+}
+
+#[derive(Debug)]
+pub struct ImplWindowWatcher;
+
+impl ImplWindowWatcher {
+    pub fn new() -> XCapResult<ImplWindowWatcher> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<WindowEvent> {
+        Err(XCapError::NotSupported)
+    }
+}
+
+#[derive(Debug)]
+pub struct ImplMonitorWatcher;
+
+impl ImplMonitorWatcher {
+    pub fn new() -> XCapResult<ImplMonitorWatcher> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<MonitorEvent> {
+        Err(XCapError::NotSupported)
+    }
+}
+
+pub fn get_workspaces() -> XCapResult<Vec<Workspace>> {
+    Err(XCapError::NotSupported)
+}
+
 pub mod impl_monitor {
     pub use super::ImplMonitor;
 }
@@ -170,6 +325,14 @@ pub mod impl_video_recorder {
     pub use super::ImplVideoRecorder;
 }
 
+pub mod impl_watcher {
+    pub use super::{ImplActiveWindowWatcher, ImplMonitorWatcher, ImplWindowWatcher};
+}
+
 pub mod impl_window {
     pub use super::ImplWindow;
 }
+
+pub mod impl_workspace {
+    pub use super::get_workspaces;
+}
@@ -0,0 +1,208 @@
+use image::{RgbaImage, imageops::FilterType};
+
+use crate::{
+    color_profile::convert_to_srgb,
+    error::XCapResult,
+    redaction::{RedactionStyle, WindowFilter},
+    transform::Transform,
+};
+
+/// Pixel grid of a captured image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    /// Device pixels, `logical size * scale factor`.
+    #[default]
+    Physical,
+    /// Scaled pixels, the size reported by `Monitor::width()/height()` on scaled displays.
+    Logical,
+}
+
+/// Orientation of a captured image on rotated or mirrored monitors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// As the user sees it, the orientation returned by `capture_image`.
+    #[default]
+    Visible,
+    /// As the panel scans it out, with the monitor transform undone.
+    PanelNative,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CaptureOptions {
+    pub resolution: Resolution,
+    pub orientation: Orientation,
+    /// Windows hidden from monitor captures, only their parts that are visible on screen are
+    /// redacted. Ignored by window captures.
+    pub redact: Vec<WindowFilter>,
+    pub redaction_style: RedactionStyle,
+    /// Convert the pixels from the monitor's ICC profile to sRGB. Monitors without a profile are
+    /// assumed to be sRGB already.
+    pub convert_to_srgb: bool,
+    /// Return the monitor's ICC profile in [`CapturedImage::icc_profile`] so it can be embedded
+    /// when saving. Has no effect together with `convert_to_srgb`.
+    pub embed_icc_profile: bool,
+}
+
+/// Image returned by `capture_image_with_options`.
+#[derive(Debug, Clone)]
+pub struct CapturedImage {
+    pub image: RgbaImage,
+    /// The resolution `image` was returned in.
+    pub resolution: Resolution,
+    /// Physical pixels per logical pixel.
+    pub scale_factor: f32,
+    /// The orientation `image` was returned in.
+    pub orientation: Orientation,
+    /// Transform of the monitor, from panel-native to visible orientation.
+    pub transform: Transform,
+    /// ICC profile of the pixels in `image`, only set with `embed_icc_profile` when the monitor
+    /// has a profile. Pass it to [`SaveOptions::icc_profile`](crate::SaveOptions::icc_profile) to
+    /// keep the colors when saving.
+    pub icc_profile: Option<Vec<u8>>,
+}
+
+/// Scale a size by `factor`, rounding to whole pixels.
+pub(crate) fn scale_size((width, height): (u32, u32), factor: f32) -> (u32, u32) {
+    (
+        (width as f32 * factor).round() as u32,
+        (height as f32 * factor).round() as u32,
+    )
+}
+
+/// Convert the image to sRGB when requested, returns the ICC profile to embed.
+///
+/// `icc_profile` is only called when the options need the profile.
+pub(crate) fn apply_color(
+    image: &mut RgbaImage,
+    options: &CaptureOptions,
+    icc_profile: impl FnOnce() -> XCapResult<Option<Vec<u8>>>,
+) -> XCapResult<Option<Vec<u8>>> {
+    if !options.convert_to_srgb && !options.embed_icc_profile {
+        return Ok(None);
+    }
+
+    match icc_profile()? {
+        Some(icc_profile) if options.convert_to_srgb => {
+            convert_to_srgb(image, &icc_profile)?;
+            Ok(None)
+        }
+        icc_profile => Ok(icc_profile),
+    }
+}
+
+/// Bring the visible image returned by a backend into the requested orientation and resolution.
+///
+/// Backends disagree on what they return (X11 always physical, compositors may
+/// return logical pixels), so the image is only resampled when its size differs.
+pub(crate) fn apply_options(
+    image: RgbaImage,
+    options: &CaptureOptions,
+    physical_size: (u32, u32),
+    logical_size: (u32, u32),
+    scale_factor: f32,
+    transform: Transform,
+    icc_profile: Option<Vec<u8>>,
+) -> CapturedImage {
+    let (mut width, mut height) = match options.resolution {
+        Resolution::Physical => physical_size,
+        Resolution::Logical => logical_size,
+    };
+
+    let image = match options.orientation {
+        Orientation::Visible => image,
+        Orientation::PanelNative => {
+            if transform.swaps_axes() {
+                (width, height) = (height, width);
+            }
+            transform.revert(image)
+        }
+    };
+
+    let image = if image.dimensions() == (width, height) || width == 0 || height == 0 {
+        image
+    } else {
+        image::imageops::resize(&image, width, height, FilterType::Triangle)
+    };
+
+    CapturedImage {
+        image,
+        resolution: options.resolution,
+        scale_factor,
+        orientation: options.orientation,
+        transform,
+        icc_profile,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_options() {
+        let options = CaptureOptions {
+            resolution: Resolution::Logical,
+            ..CaptureOptions::default()
+        };
+        let captured = apply_options(
+            RgbaImage::new(300, 150),
+            &options,
+            (300, 150),
+            (200, 100),
+            1.5,
+            Transform::default(),
+            None,
+        );
+        assert_eq!(captured.image.dimensions(), (200, 100));
+        assert_eq!(captured.resolution, Resolution::Logical);
+
+        // 合成器已返回逻辑像素时放大到物理像素
+        let options = CaptureOptions::default();
+        let captured = apply_options(
+            RgbaImage::new(200, 100),
+            &options,
+            (300, 150),
+            (200, 100),
+            1.5,
+            Transform::default(),
+            None,
+        );
+        assert_eq!(captured.image.dimensions(), (300, 150));
+
+        // 旋转 90 度的显示器，面板原生方向的宽高互换
+        let options = CaptureOptions {
+            orientation: Orientation::PanelNative,
+            ..CaptureOptions::default()
+        };
+        let captured = apply_options(
+            RgbaImage::new(100, 200),
+            &options,
+            (100, 200),
+            (100, 200),
+            1.0,
+            Transform::new(90, false, false),
+            None,
+        );
+        assert_eq!(captured.image.dimensions(), (200, 100));
+
+        assert_eq!(scale_size((1707, 960), 1.5), (2561, 1440));
+    }
+
+    #[test]
+    fn test_apply_color() {
+        let mut image = RgbaImage::new(1, 1);
+
+        // 不需要时不读取 ICC profile
+        let icc_profile = apply_color(&mut image, &CaptureOptions::default(), || {
+            panic!("ICC profile should not be read")
+        });
+        assert_eq!(icc_profile.unwrap(), None);
+
+        let options = CaptureOptions {
+            embed_icc_profile: true,
+            ..CaptureOptions::default()
+        };
+        let icc_profile = apply_color(&mut image, &options, || Ok(Some(vec![1, 2, 3])));
+        assert_eq!(icc_profile.unwrap(), Some(vec![1, 2, 3]));
+    }
+}
@@ -0,0 +1,313 @@
+use image::RgbaImage;
+
+use crate::error::{XCapError, XCapResult};
+
+/// XYZ (D50) to linear sRGB, Bradford adapted.
+const XYZ_D50_TO_SRGB: [[f32; 3]; 3] = [
+    [3.133_856, -1.616_867, -0.490_615],
+    [-0.978_768, 1.916_142, 0.033_454],
+    [0.071_945, -0.228_991, 1.405_243],
+];
+
+const OUTPUT_TABLE_SIZE: usize = 4096;
+
+/// A tone response curve of an ICC profile.
+#[derive(Debug, Clone, PartialEq)]
+enum Curve {
+    Gamma(f32),
+    Table(Vec<u16>),
+    /// Parametric curve, parameters `g, a, b, c, d, e, f` with unused ones set to their neutral value.
+    Parametric([f32; 7]),
+}
+
+impl Curve {
+    fn eval(&self, x: f32) -> f32 {
+        match self {
+            Curve::Gamma(gamma) => x.powf(*gamma),
+            Curve::Table(table) => {
+                let position = x * (table.len() - 1) as f32;
+                let index = (position as usize).min(table.len() - 2);
+                let fraction = position - index as f32;
+                let (a, b) = (table[index] as f32, table[index + 1] as f32);
+
+                (a + (b - a) * fraction) / 65535.0
+            }
+            Curve::Parametric([g, a, b, c, d, e, f]) => {
+                if x >= *d {
+                    (a * x + b).max(0.0).powf(*g) + e
+                } else {
+                    c * x + f
+                }
+            }
+        }
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> XCapResult<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| XCapError::new("ICC profile is truncated"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> XCapResult<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| XCapError::new("ICC profile is truncated"))
+}
+
+fn read_s15_fixed16(data: &[u8], offset: usize) -> XCapResult<f32> {
+    Ok(read_u32(data, offset)? as i32 as f32 / 65536.0)
+}
+
+fn find_tag<'a>(profile: &'a [u8], signature: &[u8; 4]) -> XCapResult<&'a [u8]> {
+    let count = read_u32(profile, 128)? as usize;
+
+    for index in 0..count {
+        let entry = 132 + index * 12;
+        // 标签数量不可信，超出数据长度时不再继续查找
+        let tag_signature = profile
+            .get(entry..entry + 4)
+            .ok_or_else(|| XCapError::new("ICC profile is truncated"))?;
+        if tag_signature != signature {
+            continue;
+        }
+
+        let offset = read_u32(profile, entry + 4)? as usize;
+        let size = read_u32(profile, entry + 8)? as usize;
+
+        return offset
+            .checked_add(size)
+            .and_then(|end| profile.get(offset..end))
+            .ok_or_else(|| XCapError::new("ICC profile is truncated"));
+    }
+
+    Err(XCapError::new(format!(
+        "ICC profile has no {} tag, only matrix/TRC profiles are supported",
+        String::from_utf8_lossy(signature)
+    )))
+}
+
+fn read_xyz(profile: &[u8], signature: &[u8; 4]) -> XCapResult<[f32; 3]> {
+    let tag = find_tag(profile, signature)?;
+    if tag.get(..4) != Some(b"XYZ ") {
+        return Err(XCapError::new("Unsupported ICC XYZ tag type"));
+    }
+
+    Ok([
+        read_s15_fixed16(tag, 8)?,
+        read_s15_fixed16(tag, 12)?,
+        read_s15_fixed16(tag, 16)?,
+    ])
+}
+
+fn read_curve(profile: &[u8], signature: &[u8; 4]) -> XCapResult<Curve> {
+    let tag = find_tag(profile, signature)?;
+
+    match tag.get(..4) {
+        Some(b"curv") => {
+            let count = read_u32(tag, 8)? as usize;
+            match count {
+                0 => Ok(Curve::Gamma(1.0)),
+                1 => Ok(Curve::Gamma(read_u16(tag, 12)? as f32 / 256.0)),
+                _ => Ok(Curve::Table(
+                    (0..count)
+                        .map(|index| read_u16(tag, 12 + index * 2))
+                        .collect::<XCapResult<_>>()?,
+                )),
+            }
+        }
+        Some(b"para") => {
+            let function_type = read_u16(tag, 8)?;
+            let count = match function_type {
+                0 => 1,
+                1 => 3,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                _ => return Err(XCapError::new("Unsupported ICC parametric curve")),
+            };
+            let mut values = [0.0; 7];
+            for (index, value) in values.iter_mut().take(count).enumerate() {
+                *value = read_s15_fixed16(tag, 12 + index * 4)?;
+            }
+
+            // 统一为 type 4 的形式：X >= d 时 (aX + b)^g + e，否则 cX + f
+            let [g, a, b, c, d, e, f] = values;
+            let parameters = match function_type {
+                0 => [g, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                1 => [g, a, b, 0.0, -b / a, 0.0, 0.0],
+                2 => [g, a, b, 0.0, -b / a, c, c],
+                3 => [g, a, b, c, d, 0.0, 0.0],
+                _ => [g, a, b, c, d, e, f],
+            };
+
+            Ok(Curve::Parametric(parameters))
+        }
+        _ => Err(XCapError::new("Unsupported ICC curve tag type")),
+    }
+}
+
+/// Converts pixels from an RGB matrix/TRC ICC profile to sRGB.
+#[derive(Debug, Clone)]
+pub(crate) struct SrgbConverter {
+    input: [[f32; 256]; 3],
+    matrix: [[f32; 3]; 3],
+    output: Vec<u8>,
+}
+
+impl SrgbConverter {
+    pub fn new(profile: &[u8]) -> XCapResult<SrgbConverter> {
+        if profile.get(16..20) != Some(b"RGB ") || profile.get(20..24) != Some(b"XYZ ") {
+            return Err(XCapError::new(
+                "Only RGB ICC profiles with an XYZ connection space are supported",
+            ));
+        }
+
+        let mut input = [[0.0; 256]; 3];
+        for (table, signature) in input.iter_mut().zip([b"rTRC", b"gTRC", b"bTRC"]) {
+            let curve = read_curve(profile, signature)?;
+            for (value, output) in table.iter_mut().enumerate() {
+                *output = curve.eval(value as f32 / 255.0);
+            }
+        }
+
+        // 设备 RGB -> XYZ 的矩阵由三个原色的 XYZ 值作为列组成
+        let colorants = [
+            read_xyz(profile, b"rXYZ")?,
+            read_xyz(profile, b"gXYZ")?,
+            read_xyz(profile, b"bXYZ")?,
+        ];
+        let mut matrix = [[0.0; 3]; 3];
+        for (row, srgb_row) in matrix.iter_mut().zip(XYZ_D50_TO_SRGB) {
+            for (column, colorant) in colorants.iter().enumerate() {
+                row[column] = (0..3).map(|index| srgb_row[index] * colorant[index]).sum();
+            }
+        }
+
+        let output = (0..OUTPUT_TABLE_SIZE)
+            .map(|index| {
+                let linear = index as f32 / (OUTPUT_TABLE_SIZE - 1) as f32;
+                let encoded = if linear <= 0.003_130_8 {
+                    linear * 12.92
+                } else {
+                    1.055 * linear.powf(1.0 / 2.4) - 0.055
+                };
+
+                (encoded * 255.0).round() as u8
+            })
+            .collect();
+
+        Ok(SrgbConverter {
+            input,
+            matrix,
+            output,
+        })
+    }
+
+    pub fn convert(&self, image: &mut RgbaImage) {
+        for pixel in image.pixels_mut() {
+            let linear = [
+                self.input[0][pixel[0] as usize],
+                self.input[1][pixel[1] as usize],
+                self.input[2][pixel[2] as usize],
+            ];
+
+            for (channel, row) in self.matrix.iter().enumerate() {
+                let value = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+                let index = (value.clamp(0.0, 1.0) * (OUTPUT_TABLE_SIZE - 1) as f32).round();
+                pixel[channel] = self.output[index as usize];
+            }
+        }
+    }
+}
+
+/// Convert pixels from the color space described by an RGB ICC profile to sRGB.
+///
+/// Only matrix/TRC profiles are supported, which is what monitor profiles usually are.
+pub fn convert_to_srgb(image: &mut RgbaImage, icc_profile: &[u8]) -> XCapResult<()> {
+    SrgbConverter::new(icc_profile)?.convert(image);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    fn xyz_tag([x, y, z]: [f32; 3]) -> Vec<u8> {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for value in [x, y, z] {
+            tag.extend(((value * 65536.0).round() as i32).to_be_bytes());
+        }
+        tag
+    }
+
+    /// A matrix/TRC profile with the sRGB primaries and the given curve tag for all channels.
+    fn build_profile(curve: Vec<u8>) -> Vec<u8> {
+        let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+            (b"rXYZ", xyz_tag([0.436_07, 0.222_49, 0.013_92])),
+            (b"gXYZ", xyz_tag([0.385_15, 0.716_87, 0.097_08])),
+            (b"bXYZ", xyz_tag([0.143_07, 0.060_61, 0.714_09])),
+            (b"rTRC", curve.clone()),
+            (b"gTRC", curve.clone()),
+            (b"bTRC", curve),
+        ];
+
+        let mut profile = vec![0u8; 128];
+        profile[16..20].copy_from_slice(b"RGB ");
+        profile[20..24].copy_from_slice(b"XYZ ");
+        profile.extend((tags.len() as u32).to_be_bytes());
+
+        let mut offset = 132 + tags.len() * 12;
+        let mut data = Vec::new();
+        for (signature, tag) in &tags {
+            profile.extend(*signature);
+            profile.extend((offset as u32).to_be_bytes());
+            profile.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+            data.extend_from_slice(tag);
+        }
+        profile.extend(data);
+
+        profile
+    }
+
+    #[test]
+    fn test_convert_to_srgb() {
+        // sRGB 的参数曲线，转换后颜色应基本不变
+        let mut curve = b"para\0\0\0\0\0\x03\0\0".to_vec();
+        for value in [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045] {
+            curve.extend(((value * 65536.0_f64).round() as i32).to_be_bytes());
+        }
+        let profile = build_profile(curve);
+
+        let original = RgbaImage::from_fn(16, 16, |x, y| {
+            Rgba([(x * 16) as u8, (y * 16) as u8, ((x + y) * 8) as u8, 200])
+        });
+        let mut image = original.clone();
+        convert_to_srgb(&mut image, &profile).unwrap();
+
+        for (a, b) in original.pixels().zip(image.pixels()) {
+            for channel in 0..4 {
+                assert!(a[channel].abs_diff(b[channel]) <= 2, "{a:?} {b:?}");
+            }
+        }
+
+        // 线性曲线：中灰在 sRGB 中变亮
+        let profile = build_profile(b"curv\0\0\0\0\0\0\0\0".to_vec());
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([128, 128, 128, 255]));
+        convert_to_srgb(&mut image, &profile).unwrap();
+        assert!(image.get_pixel(0, 0)[0] > 180);
+
+        assert!(convert_to_srgb(&mut image, &profile[..100]).is_err());
+
+        // 标签的偏移和长度越界时返回错误而不是 panic
+        let mut profile = profile;
+        profile[136..144].fill(0xff);
+        assert!(convert_to_srgb(&mut image, &profile).is_err());
+        profile[128..132].fill(0xff);
+        assert!(find_tag(&profile, b"none").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::error::{XCapError, XCapResult};
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const BLOCK_SIZE: usize = 128;
+
+/// Established timings I and II, in bit order of bytes 0x23 and 0x24 plus the first bit of 0x25.
+const ESTABLISHED_TIMINGS: [(u32, u32, f32); 17] = [
+    (720, 400, 70.0),
+    (720, 400, 88.0),
+    (640, 480, 60.0),
+    (640, 480, 67.0),
+    (640, 480, 72.0),
+    (640, 480, 75.0),
+    (800, 600, 56.0),
+    (800, 600, 60.0),
+    (800, 600, 72.0),
+    (800, 600, 75.0),
+    (832, 624, 75.0),
+    (1024, 768, 87.0),
+    (1024, 768, 60.0),
+    (1024, 768, 70.0),
+    (1024, 768, 75.0),
+    (1280, 1024, 75.0),
+    (1152, 870, 75.0),
+];
+
+/// A video mode supported by the monitor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdidTiming {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: f32,
+    pub interlaced: bool,
+    /// The native mode of the monitor, the first detailed timing.
+    pub preferred: bool,
+}
+
+/// HDR static metadata from the CTA-861 extension block.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HdrMetadata {
+    pub sdr: bool,
+    pub traditional_hdr: bool,
+    /// SMPTE ST 2084, used by HDR10.
+    pub pq: bool,
+    pub hlg: bool,
+    /// Desired content max luminance in cd/m².
+    pub max_luminance: Option<f32>,
+    /// Desired content max frame-average luminance in cd/m².
+    pub max_frame_average_luminance: Option<f32>,
+    /// Desired content min luminance in cd/m².
+    pub min_luminance: Option<f32>,
+}
+
+/// Parsed Extended Display Identification Data of a monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edid {
+    /// Three letter PNP manufacturer ID, e.g. `DEL`.
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    pub serial_number: u32,
+    /// Serial number from the display descriptor, usually what is printed on the label.
+    pub serial_number_string: Option<String>,
+    /// Week of manufacture, `None` when unknown or when `year` is the model year.
+    pub week: Option<u8>,
+    /// Year of manufacture, or the model year.
+    pub year: u16,
+    /// EDID version and revision, e.g. `(1, 4)`.
+    pub version: (u8, u8),
+    /// Physical size of the image area in millimeters, 0 when unknown.
+    pub width_mm: u32,
+    pub height_mm: u32,
+    /// Display product name descriptor.
+    pub name: Option<String>,
+    pub timings: Vec<EdidTiming>,
+    pub hdr: Option<HdrMetadata>,
+    /// Raw extension blocks, e.g. CTA-861 or DisplayID.
+    pub extensions: Vec<Vec<u8>>,
+}
+
+fn descriptor_text(descriptor: &[u8]) -> String {
+    let text = &descriptor[5..18];
+    let end = text.iter().position(|c| *c == b'\n').unwrap_or(text.len());
+
+    String::from_utf8_lossy(&text[..end]).trim_end().to_string()
+}
+
+/// Detailed timing descriptor, `None` for display descriptors.
+fn detailed_timing(descriptor: &[u8]) -> Option<(EdidTiming, u32, u32)> {
+    let pixel_clock = u16::from_le_bytes([descriptor[0], descriptor[1]]) as f32 * 10_000.0;
+    if pixel_clock == 0.0 {
+        return None;
+    }
+
+    let width = descriptor[2] as u32 | ((descriptor[4] as u32 & 0xF0) << 4);
+    let h_blank = descriptor[3] as u32 | ((descriptor[4] as u32 & 0x0F) << 8);
+    let height = descriptor[5] as u32 | ((descriptor[7] as u32 & 0xF0) << 4);
+    let v_blank = descriptor[6] as u32 | ((descriptor[7] as u32 & 0x0F) << 8);
+    let width_mm = descriptor[12] as u32 | ((descriptor[14] as u32 & 0xF0) << 4);
+    let height_mm = descriptor[13] as u32 | ((descriptor[14] as u32 & 0x0F) << 8);
+    let interlaced = descriptor[17] & 0x80 != 0;
+
+    let total = ((width + h_blank) * (height + v_blank)) as f32;
+    let timing = EdidTiming {
+        width,
+        // 隔行扫描时描述符中是一场的行数
+        height: if interlaced { height * 2 } else { height },
+        refresh_rate: if total > 0.0 {
+            pixel_clock / total
+        } else {
+            0.0
+        },
+        interlaced,
+        preferred: false,
+    };
+
+    Some((timing, width_mm, height_mm))
+}
+
+fn standard_timing(bytes: &[u8], version: (u8, u8)) -> Option<EdidTiming> {
+    if bytes == [0x01, 0x01] || bytes[0] == 0x00 {
+        return None;
+    }
+
+    let width = (bytes[0] as u32 + 31) * 8;
+    let height = match bytes[1] >> 6 {
+        // EDID 1.3 之前 00 表示 1:1
+        0 if version < (1, 3) => width,
+        0 => width * 10 / 16,
+        1 => width * 3 / 4,
+        2 => width * 4 / 5,
+        _ => width * 9 / 16,
+    };
+
+    Some(EdidTiming {
+        width,
+        height,
+        refresh_rate: (bytes[1] & 0x3F) as f32 + 60.0,
+        interlaced: false,
+        preferred: false,
+    })
+}
+
+fn luminance(code_value: u8) -> f32 {
+    50.0 * 2f32.powf(code_value as f32 / 32.0)
+}
+
+fn hdr_metadata(payload: &[u8]) -> Option<HdrMetadata> {
+    let eotf = *payload.first()?;
+    let max_luminance = payload.get(2).map(|value| luminance(*value));
+
+    Some(HdrMetadata {
+        sdr: eotf & 0x01 != 0,
+        traditional_hdr: eotf & 0x02 != 0,
+        pq: eotf & 0x04 != 0,
+        hlg: eotf & 0x08 != 0,
+        max_luminance,
+        max_frame_average_luminance: payload.get(3).map(|value| luminance(*value)),
+        min_luminance: payload
+            .get(4)
+            .zip(max_luminance)
+            .map(|(value, max_luminance)| max_luminance * (*value as f32 / 255.0).powi(2) / 100.0),
+    })
+}
+
+/// Data blocks and detailed timings of a CTA-861 extension block.
+fn parse_cta_extension(block: &[u8], edid: &mut Edid) {
+    let dtd_offset = (block[2] as usize).min(BLOCK_SIZE - 1);
+
+    let mut offset = 4;
+    while offset < dtd_offset {
+        let tag = block[offset] >> 5;
+        let length = (block[offset] & 0x1F) as usize;
+        let Some(payload) = block.get(offset + 1..offset + 1 + length) else {
+            break;
+        };
+
+        // 扩展标签 7，扩展标签码 6 为 HDR 静态元数据
+        if tag == 7 && payload.first() == Some(&6) {
+            edid.hdr = hdr_metadata(&payload[1..]);
+        }
+
+        offset += 1 + length;
+    }
+
+    if dtd_offset >= 4 {
+        for descriptor in block[dtd_offset..BLOCK_SIZE - 1].chunks_exact(18) {
+            if let Some((timing, _, _)) = detailed_timing(descriptor) {
+                edid.timings.push(timing);
+            }
+        }
+    }
+}
+
+impl Edid {
+    /// Parse an EDID blob, the 128 byte base block followed by its extension blocks.
+    pub fn parse(data: &[u8]) -> XCapResult<Edid> {
+        if data.len() < BLOCK_SIZE || data[..8] != HEADER {
+            return Err(XCapError::new("Invalid EDID header"));
+        }
+
+        let id = u16::from_be_bytes([data[8], data[9]]);
+        let manufacturer_id = [10, 5, 0]
+            .iter()
+            .map(|shift| (((id >> shift) & 0x1F) as u8 + b'@') as char)
+            .collect();
+
+        let version = (data[18], data[19]);
+        let (week, year) = match data[16] {
+            0 | 0xFF => (None, data[17] as u16 + 1990),
+            week => (Some(week), data[17] as u16 + 1990),
+        };
+
+        let mut edid = Edid {
+            manufacturer_id,
+            product_code: u16::from_le_bytes([data[10], data[11]]),
+            serial_number: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+            serial_number_string: None,
+            week,
+            year,
+            version,
+            width_mm: data[21] as u32 * 10,
+            height_mm: data[22] as u32 * 10,
+            name: None,
+            timings: Vec::new(),
+            hdr: None,
+            extensions: Vec::new(),
+        };
+
+        for descriptor in data[0x36..0x7E].chunks_exact(18) {
+            if let Some((mut timing, width_mm, height_mm)) = detailed_timing(descriptor) {
+                // 第一个详细时序是首选时序，其尺寸比基本参数中的厘米值更精确
+                if edid.timings.is_empty() {
+                    timing.preferred = true;
+                    if width_mm > 0 && height_mm > 0 {
+                        edid.width_mm = width_mm;
+                        edid.height_mm = height_mm;
+                    }
+                }
+                edid.timings.push(timing);
+                continue;
+            }
+
+            match descriptor[3] {
+                0xFC => edid.name = Some(descriptor_text(descriptor)),
+                0xFF => edid.serial_number_string = Some(descriptor_text(descriptor)),
+                _ => {}
+            }
+        }
+
+        for chunk in data[0x26..0x36].chunks_exact(2) {
+            if let Some(timing) = standard_timing(chunk, version) {
+                edid.timings.push(timing);
+            }
+        }
+
+        let established = u32::from_be_bytes([data[0x23], data[0x24], data[0x25], 0]);
+        for (index, (width, height, refresh_rate)) in ESTABLISHED_TIMINGS.iter().enumerate() {
+            if established & (1 << (31 - index)) != 0 {
+                edid.timings.push(EdidTiming {
+                    width: *width,
+                    height: *height,
+                    refresh_rate: *refresh_rate,
+                    interlaced: false,
+                    preferred: false,
+                });
+            }
+        }
+
+        let extension_count = data[0x7E] as usize;
+        for block in data[BLOCK_SIZE..]
+            .chunks_exact(BLOCK_SIZE)
+            .take(extension_count)
+        {
+            if block[0] == 0x02 {
+                parse_cta_extension(block, &mut edid);
+            }
+            edid.extensions.push(block.to_vec());
+        }
+
+        Ok(edid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_edid() -> Vec<u8> {
+        let mut data = vec![0u8; BLOCK_SIZE * 2];
+        data[..8].copy_from_slice(&HEADER);
+        // DEL = 4, 5, 12
+        data[8..10].copy_from_slice(&((4u16 << 10) | (5 << 5) | 12).to_be_bytes());
+        data[10..12].copy_from_slice(&0xA0C3u16.to_le_bytes());
+        data[12..16].copy_from_slice(&12345u32.to_le_bytes());
+        data[16] = 12;
+        data[17] = 33;
+        data[18..20].copy_from_slice(&[1, 4]);
+        data[21..23].copy_from_slice(&[60, 34]);
+        data[0x23] = 0x20;
+        data[0x26..0x36].fill(0x01);
+        // 1920 = (0xD1 + 31) * 8，16:9，60Hz
+        data[0x26..0x28].copy_from_slice(&[0xD1, 0xC0]);
+
+        // 1920x1080@60 详细时序, 148.5 MHz
+        let dtd = &mut data[0x36..0x48];
+        dtd[..2].copy_from_slice(&14850u16.to_le_bytes());
+        dtd[2] = 0x80;
+        dtd[3] = 0x18;
+        dtd[4] = 0x71;
+        dtd[5] = 0x38;
+        dtd[6] = 0x2D;
+        dtd[7] = 0x40;
+        dtd[12] = 0x27;
+        dtd[13] = 0x51;
+        dtd[14] = 0x21;
+
+        let name = &mut data[0x48..0x5A];
+        name[3] = 0xFC;
+        name[5..18].copy_from_slice(b"DELL U2720Q\n ");
+        data[0x7E] = 1;
+
+        // CTA-861 扩展块，HDR 静态元数据块
+        let cta = &mut data[BLOCK_SIZE..];
+        cta[..4].copy_from_slice(&[0x02, 0x03, 10, 0]);
+        cta[4..10].copy_from_slice(&[0xE5, 0x06, 0x05, 0x01, 0x60, 0x40]);
+
+        data
+    }
+
+    #[test]
+    fn test_parse_edid() {
+        let edid = Edid::parse(&build_edid()).unwrap();
+
+        assert_eq!(edid.manufacturer_id, "DEL");
+        assert_eq!(edid.product_code, 0xA0C3);
+        assert_eq!(edid.serial_number, 12345);
+        assert_eq!((edid.week, edid.year), (Some(12), 2023));
+        assert_eq!(edid.version, (1, 4));
+        assert_eq!((edid.width_mm, edid.height_mm), (0x227, 0x151));
+        assert_eq!(edid.name.as_deref(), Some("DELL U2720Q"));
+
+        let preferred = edid.timings[0];
+        assert!(preferred.preferred);
+        assert_eq!((preferred.width, preferred.height), (1920, 1080));
+        assert!((preferred.refresh_rate - 60.0).abs() < 0.01);
+        assert_eq!(
+            edid.timings[1..]
+                .iter()
+                .map(|timing| (timing.width, timing.height))
+                .collect::<Vec<_>>(),
+            vec![(1920, 1080), (640, 480)]
+        );
+
+        let hdr = edid.hdr.unwrap();
+        assert!(hdr.sdr && hdr.pq && !hdr.hlg);
+        assert_eq!(hdr.max_luminance, Some(50.0 * 8.0));
+        assert_eq!(edid.extensions.len(), 1);
+
+        assert!(Edid::parse(&[0; 128]).is_err());
+    }
+}
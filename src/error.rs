@@ -12,6 +12,10 @@ pub enum XCapError {
     StdSyncPoisonError(String),
     #[error("Invalid capture region: {0}")]
     InvalidCaptureRegion(String),
+    #[error(transparent)]
+    ImageImageError(#[from] image::ImageError),
+    #[error(transparent)]
+    StdIOError(#[from] std::io::Error),
 
     #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
     #[error(transparent)]
@@ -21,18 +25,12 @@ pub enum XCapError {
     XcbConnError(#[from] xcb::ConnError),
     #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
     #[error(transparent)]
-    ImageImageError(#[from] image::ImageError),
-    #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
-    #[error(transparent)]
     StdStringFromUtf8Error(#[from] std::string::FromUtf8Error),
     #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
     #[error(transparent)]
     ZbusError(#[from] zbus::Error),
     #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
     #[error(transparent)]
-    StdIOError(#[from] std::io::Error),
-    #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
-    #[error(transparent)]
     StdMPSCRecvError(#[from] std::sync::mpsc::RecvError),
     #[cfg(all(target_os = "linux", not(target_env = "ohos")))]
     #[error(transparent)]
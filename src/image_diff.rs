@@ -0,0 +1,276 @@
+use std::borrow::Cow;
+
+use image::RgbaImage;
+
+use crate::{
+    error::{XCapError, XCapResult},
+    pixel_format::{PixelFormat, convert_pixels},
+    video_recorder::Frame,
+};
+
+/// A rectangle in image pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Region {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Exclusive right edge, saturating at `u32::MAX`.
+    pub fn right(&self) -> u32 {
+        self.x.saturating_add(self.width)
+    }
+
+    /// Exclusive bottom edge, saturating at `u32::MAX`.
+    pub fn bottom(&self) -> u32 {
+        self.y.saturating_add(self.height)
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    pub fn intersects(&self, other: &Region) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    /// The smallest region containing both regions.
+    pub fn union(&self, other: &Region) -> Region {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        Region::new(x, y, right - x, bottom - y)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Largest difference per RGBA channel that still counts as unchanged.
+    pub tolerance: [u8; 4],
+    /// Changed pixels are grouped into square tiles of this size before merging into regions.
+    pub tile_size: u32,
+    /// Regions that are never compared, e.g. clocks.
+    pub ignore: Vec<Region>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            tolerance: [0; 4],
+            tile_size: 16,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDiff {
+    /// Number of compared pixels that differ.
+    pub changed_pixels: u64,
+    /// Number of compared pixels, ignored pixels are not counted.
+    pub compared_pixels: u64,
+    /// Bounding rectangles of the changed tiles, overlapping and adjacent tiles merged.
+    pub regions: Vec<Region>,
+}
+
+impl ImageDiff {
+    /// Changed pixels relative to compared pixels, in the range 0.0..=1.0.
+    pub fn changed_ratio(&self) -> f64 {
+        if self.compared_pixels == 0 {
+            return 0.0;
+        }
+
+        self.changed_pixels as f64 / self.compared_pixels as f64
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.changed_pixels == 0
+    }
+}
+
+/// Merge overlapping or touching regions until none are left to merge.
+fn merge_regions(mut regions: Vec<Region>) -> Vec<Region> {
+    let mut merged = true;
+    while merged {
+        merged = false;
+        let mut result: Vec<Region> = Vec::with_capacity(regions.len());
+
+        for region in regions {
+            // 扩大一个像素，使相邻的区域也合并
+            let grown = Region::new(
+                region.x.saturating_sub(1),
+                region.y.saturating_sub(1),
+                region.width.saturating_add(2),
+                region.height.saturating_add(2),
+            );
+
+            match result.iter_mut().find(|item| item.intersects(&grown)) {
+                Some(item) => {
+                    *item = item.union(&region);
+                    merged = true;
+                }
+                None => result.push(region),
+            }
+        }
+
+        regions = result;
+    }
+
+    regions.sort_by_key(|region| (region.y, region.x));
+    regions
+}
+
+fn diff_pixels(width: u32, height: u32, a: &[u8], b: &[u8], options: &DiffOptions) -> ImageDiff {
+    let tile_size = options.tile_size.max(1);
+    let tiles_x = width.div_ceil(tile_size) as usize;
+    let tiles_y = height.div_ceil(tile_size) as usize;
+    let mut changed_tiles = vec![false; tiles_x * tiles_y];
+    let mut changed_pixels = 0;
+    let mut compared_pixels = 0;
+
+    for y in 0..height {
+        let ignore: Vec<&Region> = options
+            .ignore
+            .iter()
+            .filter(|region| y >= region.y && y - region.y < region.height)
+            .collect();
+
+        for x in 0..width {
+            if ignore.iter().any(|region| region.contains(x, y)) {
+                continue;
+            }
+            compared_pixels += 1;
+
+            let index = ((y * width + x) * 4) as usize;
+            let is_changed = a[index..index + 4]
+                .iter()
+                .zip(&b[index..index + 4])
+                .zip(options.tolerance)
+                .any(|((a, b), tolerance)| a.abs_diff(*b) > tolerance);
+
+            if is_changed {
+                changed_pixels += 1;
+                changed_tiles[(y / tile_size) as usize * tiles_x + (x / tile_size) as usize] = true;
+            }
+        }
+    }
+
+    let regions = changed_tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, is_changed)| **is_changed)
+        .map(|(index, _)| {
+            let x = (index % tiles_x) as u32 * tile_size;
+            let y = (index / tiles_x) as u32 * tile_size;
+            Region::new(x, y, tile_size.min(width - x), tile_size.min(height - y))
+        })
+        .collect();
+
+    ImageDiff {
+        changed_pixels,
+        compared_pixels,
+        regions: merge_regions(regions),
+    }
+}
+
+/// Compare two images of the same size.
+pub fn diff_images(a: &RgbaImage, b: &RgbaImage, options: &DiffOptions) -> XCapResult<ImageDiff> {
+    if a.dimensions() != b.dimensions() {
+        return Err(XCapError::new(format!(
+            "Image sizes differ: {:?} and {:?}",
+            a.dimensions(),
+            b.dimensions()
+        )));
+    }
+
+    let (width, height) = a.dimensions();
+    Ok(diff_pixels(width, height, a.as_raw(), b.as_raw(), options))
+}
+
+fn rgba_pixels(frame: &Frame) -> Cow<'_, [u8]> {
+    match frame.format {
+        PixelFormat::Rgba => Cow::Borrowed(&frame.raw),
+        format => Cow::Owned(convert_pixels(&frame.raw, format, PixelFormat::Rgba)),
+    }
+}
+
+/// Compare two recorder frames of the same size, frames that are not RGBA are converted first.
+pub fn diff_frames(a: &Frame, b: &Frame, options: &DiffOptions) -> XCapResult<ImageDiff> {
+    let (a_raw, b_raw) = (rgba_pixels(a), rgba_pixels(b));
+
+    let len = a.width as usize * a.height as usize * 4;
+    if (a.width, a.height) != (b.width, b.height) || a_raw.len() < len || b_raw.len() < len {
+        return Err(XCapError::new(format!(
+            "Frame sizes differ: {}x{} and {}x{}",
+            a.width, a.height, b.width, b.height
+        )));
+    }
+
+    Ok(diff_pixels(a.width, a.height, &a_raw, &b_raw, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_images() {
+        let a = RgbaImage::from_pixel(64, 64, Rgba([0, 0, 0, 255]));
+        let mut b = a.clone();
+        // 两个相邻 tile 中的变化合并为一个区域
+        b.put_pixel(15, 0, Rgba([255, 0, 0, 255]));
+        b.put_pixel(16, 0, Rgba([255, 0, 0, 255]));
+        b.put_pixel(63, 63, Rgba([255, 0, 0, 255]));
+        // 在容差范围内
+        b.put_pixel(40, 10, Rgba([3, 0, 0, 255]));
+
+        let options = DiffOptions {
+            tolerance: [4; 4],
+            ..DiffOptions::default()
+        };
+        let diff = diff_images(&a, &b, &options).unwrap();
+        assert_eq!(diff.changed_pixels, 3);
+        assert_eq!(
+            diff.regions,
+            vec![Region::new(0, 0, 32, 16), Region::new(48, 48, 16, 16)]
+        );
+
+        let options = DiffOptions {
+            ignore: vec![Region::new(48, 48, 16, 16)],
+            ..options
+        };
+        let diff = diff_images(&a, &b, &options).unwrap();
+        assert_eq!(diff.changed_pixels, 2);
+        assert_eq!(diff.compared_pixels, 64 * 64 - 16 * 16);
+        assert_eq!(diff.regions.len(), 1);
+
+        // 忽略区域超出 u32 范围时不会溢出
+        let options = DiffOptions {
+            ignore: vec![Region::new(u32::MAX - 1, 0, u32::MAX, u32::MAX)],
+            ..options
+        };
+        let diff = diff_images(&a, &b, &options).unwrap();
+        assert_eq!(diff.changed_pixels, 3);
+
+        let c = RgbaImage::new(32, 32);
+        assert!(diff_images(&a, &c, &options).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    fs,
+    io::{Cursor, Seek, Write},
+    path::Path,
+};
+
+use image::{
+    ExtendedColorType, ImageEncoder, RgbaImage,
+    codecs::png::{self, PngEncoder},
+};
+
+use crate::error::{XCapError, XCapResult};
+
+/// Image file formats that captures can be saved as.
+///
+/// PNG is always available, every other format is behind the cargo feature
+/// of the same name (`jpeg`, `webp`, `qoi`, `bmp`, `tiff`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Qoi,
+    Bmp,
+    Tiff,
+}
+
+impl ImageFormat {
+    /// Guess the format from the file extension of `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> XCapResult<ImageFormat> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .ok_or_else(|| XCapError::new("Image file has no extension"))?
+            .to_ascii_lowercase();
+
+        match extension.as_str() {
+            "png" => Ok(ImageFormat::Png),
+            "jpg" | "jpeg" => Ok(ImageFormat::Jpeg),
+            "webp" => Ok(ImageFormat::WebP),
+            "qoi" => Ok(ImageFormat::Qoi),
+            "bmp" => Ok(ImageFormat::Bmp),
+            "tif" | "tiff" => Ok(ImageFormat::Tiff),
+            _ => Err(XCapError::new(format!(
+                "Unsupported image extension: {extension}"
+            ))),
+        }
+    }
+}
+
+/// PNG deflate compression level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    Fast,
+    #[default]
+    Default,
+    Best,
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveOptions {
+    /// Output format, guessed from the file extension when `None`.
+    pub format: Option<ImageFormat>,
+    /// JPEG quality in the range 1..=100. WebP is always encoded lossless.
+    pub quality: u8,
+    /// PNG compression level.
+    pub compression: Compression,
+    /// ICC profile of the pixels, embedded as an `iCCP` chunk in PNG files and ignored by other
+    /// formats. See [`CapturedImage::icc_profile`](crate::CapturedImage::icc_profile).
+    pub icc_profile: Option<Vec<u8>>,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        SaveOptions {
+            format: None,
+            quality: 90,
+            compression: Compression::Default,
+            icc_profile: None,
+        }
+    }
+}
+
+#[cfg(not(all(
+    feature = "jpeg",
+    feature = "webp",
+    feature = "qoi",
+    feature = "bmp",
+    feature = "tiff"
+)))]
+fn feature_required(format: ImageFormat, feature: &str) -> XCapError {
+    XCapError::new(format!(
+        "Saving {format:?} images requires the `{feature}` feature"
+    ))
+}
+
+pub(crate) fn encode_image<W: Write + Seek>(
+    image: &RgbaImage,
+    writer: W,
+    format: ImageFormat,
+    options: &SaveOptions,
+) -> XCapResult<()> {
+    let (width, height) = image.dimensions();
+
+    match format {
+        ImageFormat::Png => {
+            let compression = match options.compression {
+                Compression::Fast => png::CompressionType::Fast,
+                Compression::Default => png::CompressionType::Default,
+                Compression::Best => png::CompressionType::Best,
+            };
+            let mut encoder =
+                PngEncoder::new_with_quality(writer, compression, png::FilterType::Adaptive);
+            if let Some(icc_profile) = &options.icc_profile {
+                encoder
+                    .set_icc_profile(icc_profile.clone())
+                    .map_err(image::ImageError::Unsupported)?;
+            }
+            encoder.write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)?;
+        }
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => {
+            use image::{RgbImage, buffer::ConvertBuffer, codecs::jpeg::JpegEncoder};
+
+            // JPEG has no alpha channel
+            let rgb_image: RgbImage = image.convert();
+            let encoder = JpegEncoder::new_with_quality(writer, options.quality.clamp(1, 100));
+            encoder.write_image(rgb_image.as_raw(), width, height, ExtendedColorType::Rgb8)?;
+        }
+        #[cfg(feature = "webp")]
+        ImageFormat::WebP => {
+            let encoder = image::codecs::webp::WebPEncoder::new_lossless(writer);
+            encoder.write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)?;
+        }
+        #[cfg(feature = "qoi")]
+        ImageFormat::Qoi => {
+            let encoder = image::codecs::qoi::QoiEncoder::new(writer);
+            encoder.write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)?;
+        }
+        #[cfg(feature = "bmp")]
+        ImageFormat::Bmp => {
+            let mut writer = writer;
+            let encoder = image::codecs::bmp::BmpEncoder::new(&mut writer);
+            encoder.write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)?;
+        }
+        #[cfg(feature = "tiff")]
+        ImageFormat::Tiff => {
+            let encoder = image::codecs::tiff::TiffEncoder::new(writer);
+            encoder.write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)?;
+        }
+        #[cfg(not(feature = "jpeg"))]
+        ImageFormat::Jpeg => return Err(feature_required(format, "jpeg")),
+        #[cfg(not(feature = "webp"))]
+        ImageFormat::WebP => return Err(feature_required(format, "webp")),
+        #[cfg(not(feature = "qoi"))]
+        ImageFormat::Qoi => return Err(feature_required(format, "qoi")),
+        #[cfg(not(feature = "bmp"))]
+        ImageFormat::Bmp => return Err(feature_required(format, "bmp")),
+        #[cfg(not(feature = "tiff"))]
+        ImageFormat::Tiff => return Err(feature_required(format, "tiff")),
+    }
+
+    Ok(())
+}
+
+pub(crate) fn save_image<P: AsRef<Path>>(
+    image: &RgbaImage,
+    path: P,
+    options: &SaveOptions,
+) -> XCapResult<()> {
+    let path = path.as_ref();
+    let format = match options.format {
+        Some(format) => format,
+        None => ImageFormat::from_path(path)?,
+    };
+
+    // 先编码到内存，编码失败（例如格式对应的 feature 未启用）时不会截断已有的文件
+    let mut buffer = Cursor::new(Vec::new());
+    encode_image(image, &mut buffer, format, options)?;
+    fs::write(path, buffer.into_inner())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ImageFormat::from_path("a/b.PNG").unwrap(), ImageFormat::Png);
+        assert_eq!(
+            ImageFormat::from_path("shot.jpg").unwrap(),
+            ImageFormat::Jpeg
+        );
+        assert_eq!(
+            ImageFormat::from_path("shot.tif").unwrap(),
+            ImageFormat::Tiff
+        );
+        assert!(ImageFormat::from_path("shot.gif").is_err());
+        assert!(ImageFormat::from_path("shot").is_err());
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let image = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        let mut buffer = Cursor::new(Vec::new());
+        encode_image(
+            &image,
+            &mut buffer,
+            ImageFormat::Png,
+            &SaveOptions::default(),
+        )
+        .unwrap();
+
+        assert!(buffer.into_inner().starts_with(b"\x89PNG"));
+
+        let options = SaveOptions {
+            icc_profile: Some(vec![0; 128]),
+            ..SaveOptions::default()
+        };
+        let mut buffer = Cursor::new(Vec::new());
+        encode_image(&image, &mut buffer, ImageFormat::Png, &options).unwrap();
+
+        let data = buffer.into_inner();
+        assert!(data.windows(4).any(|chunk| chunk == b"iCCP"));
+    }
+}
@@ -1,7 +1,31 @@
+mod capture_options;
+mod color_profile;
+mod edid;
 mod error;
+mod image_diff;
+mod image_file;
+#[cfg(feature = "mjpeg")]
+mod mjpeg_server;
 mod monitor;
+mod perceptual_hash;
+mod pixel_format;
+mod polling_watcher;
+mod process_info;
+mod redaction;
+mod replay_recorder;
+#[cfg(all(feature = "shm", target_os = "linux", not(target_env = "ohos")))]
+mod shared_memory;
+mod thumbnail;
+mod timelapse;
+mod transform;
 mod video_recorder;
+#[cfg(feature = "vnc")]
+mod vnc_server;
+mod watcher;
 mod window;
+mod window_query;
+mod window_type;
+mod workspace;
 
 #[cfg(target_os = "macos")]
 #[path = "macos/mod.rs"]
@@ -24,10 +48,39 @@ mod platform;
 mod platform;
 
 pub use image;
+#[cfg(feature = "regex")]
+pub use regex;
 
+pub use capture_options::{CaptureOptions, CapturedImage, Orientation, Resolution};
+pub use color_profile::convert_to_srgb;
+pub use edid::{Edid, EdidTiming, HdrMetadata};
 pub use error::{XCapError, XCapResult};
+pub use image_diff::{DiffOptions, ImageDiff, Region, diff_frames, diff_images};
+pub use image_file::{Compression, ImageFormat, SaveOptions};
+#[cfg(feature = "mjpeg")]
+pub use mjpeg_server::{MjpegServer, MjpegServerOptions};
 pub use monitor::Monitor;
+pub use perceptual_hash::{
+    HashAlgorithm, ImageHash, SimilarityFilter, SimilarityOptions, frame_hash, image_hash,
+};
+pub use pixel_format::PixelFormat;
+pub use process_info::ProcessInfo;
+pub use redaction::{RedactionStyle, WindowFilter};
+pub use replay_recorder::{ReplayFormat, ReplayRecorder, ReplayRecorderOptions};
+#[cfg(all(feature = "shm", target_os = "linux", not(target_env = "ohos")))]
+pub use shared_memory::{ShmFrame, ShmPublisher, ShmPublisherOptions, ShmSubscriber};
+pub use timelapse::{Timelapse, TimelapseOptions, TimelapseTarget};
+pub use transform::Transform;
+pub use watcher::{
+    ActiveWindowWatcher, MonitorEvent, MonitorField, MonitorWatcher, WindowEvent, WindowWatcher,
+};
 pub use window::Window;
+pub use window_query::WindowQuery;
+pub use window_type::WindowType;
+pub use workspace::Workspace;
 
 pub use video_recorder::Frame;
-pub use video_recorder::VideoRecorder;
+pub use video_recorder::{VideoRecorder, VideoRecorderOptions};
+
+#[cfg(feature = "vnc")]
+pub use vnc_server::{VncServer, VncServerOptions};
@@ -1,13 +1,18 @@
 use image::RgbaImage;
 
-use crate::error::XCapResult;
+use crate::{
+    error::XCapResult,
+    pixel_format::{PixelFormat, copy_rows},
+    thumbnail::thumbnail_image,
+    video_recorder::Frame,
+};
 
 use super::{
     impl_monitor::ImplMonitor,
     impl_window::ImplWindow,
     utils::{get_current_screen_buf, get_monitor_info_buf, wayland_detect},
     wayland_capture::wayland_capture,
-    xorg_capture::xorg_capture,
+    xorg_capture::{xorg_capture, xorg_capture_as, xorg_capture_into, xorg_capture_thumbnail},
 };
 
 pub fn capture_monitor(impl_monitor: &ImplMonitor) -> XCapResult<RgbaImage> {
@@ -33,6 +38,97 @@ pub fn capture_monitor(impl_monitor: &ImplMonitor) -> XCapResult<RgbaImage> {
     }
 }
 
+pub fn capture_monitor_as(impl_monitor: &ImplMonitor, format: PixelFormat) -> XCapResult<Frame> {
+    let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+
+    if wayland_detect() {
+        let image = wayland_capture(
+            monitor_info_buf.x() as i32,
+            monitor_info_buf.y() as i32,
+            monitor_info_buf.width() as i32,
+            monitor_info_buf.height() as i32,
+        )?;
+
+        Ok(Frame::from(image).convert(format))
+    } else {
+        let screen_buf = get_current_screen_buf()?;
+
+        xorg_capture_as(
+            screen_buf.root(),
+            monitor_info_buf.x() as i32,
+            monitor_info_buf.y() as i32,
+            monitor_info_buf.width() as u32,
+            monitor_info_buf.height() as u32,
+            format,
+        )
+    }
+}
+
+pub fn capture_monitor_into(
+    impl_monitor: &ImplMonitor,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    if wayland_detect() {
+        let frame = capture_monitor_as(impl_monitor, format)?;
+        copy_rows(
+            &frame.raw,
+            frame.width,
+            frame.height,
+            format,
+            buffer,
+            stride,
+        )?;
+
+        return Ok((frame.width, frame.height));
+    }
+
+    let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+    let screen_buf = get_current_screen_buf()?;
+    let width = monitor_info_buf.width() as u32;
+    let height = monitor_info_buf.height() as u32;
+
+    xorg_capture_into(
+        screen_buf.root(),
+        monitor_info_buf.x() as i32,
+        monitor_info_buf.y() as i32,
+        width,
+        height,
+        format,
+        buffer,
+        stride,
+    )?;
+
+    Ok((width, height))
+}
+
+pub fn capture_monitor_thumbnail(
+    impl_monitor: &ImplMonitor,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    // GNOME Shell 与 xdg-desktop-portal 的截图接口都只返回完整尺寸的 PNG，无法指定尺寸
+    if wayland_detect() {
+        let image = capture_monitor(impl_monitor)?;
+
+        return Ok(thumbnail_image(image, max_width, max_height));
+    }
+
+    let monitor_info_buf = get_monitor_info_buf(impl_monitor.output)?;
+    let screen_buf = get_current_screen_buf()?;
+
+    xorg_capture_thumbnail(
+        screen_buf.root(),
+        monitor_info_buf.x() as i32,
+        monitor_info_buf.y() as i32,
+        monitor_info_buf.width() as u32,
+        monitor_info_buf.height() as u32,
+        max_width,
+        max_height,
+    )
+}
+
 pub fn capture_region(
     impl_monitor: &ImplMonitor,
     x: u32,
@@ -63,3 +159,52 @@ pub fn capture_window(impl_window: &ImplWindow) -> XCapResult<RgbaImage> {
 
     xorg_capture(impl_window.window, 0, 0, width, height)
 }
+
+pub fn capture_window_as(impl_window: &ImplWindow, format: PixelFormat) -> XCapResult<Frame> {
+    let width = impl_window.width()?;
+    let height = impl_window.height()?;
+
+    xorg_capture_as(impl_window.window, 0, 0, width, height, format)
+}
+
+pub fn capture_window_into(
+    impl_window: &ImplWindow,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    let width = impl_window.width()?;
+    let height = impl_window.height()?;
+
+    xorg_capture_into(
+        impl_window.window,
+        0,
+        0,
+        width,
+        height,
+        format,
+        buffer,
+        stride,
+    )?;
+
+    Ok((width, height))
+}
+
+pub fn capture_window_thumbnail(
+    impl_window: &ImplWindow,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    let width = impl_window.width()?;
+    let height = impl_window.height()?;
+
+    xorg_capture_thumbnail(
+        impl_window.window,
+        0,
+        0,
+        width,
+        height,
+        max_width,
+        max_height,
+    )
+}
@@ -0,0 +1,133 @@
+use std::fs;
+
+use xcb::{
+    randr::{GetMonitors, GetOutputInfo, GetOutputProperty, Output},
+    x::{ATOM_ANY, CURRENT_TIME, GetProperty},
+};
+use zbus::{
+    blocking::{Connection as ZBusConnection, Proxy},
+    zvariant::OwnedObjectPath,
+};
+
+use crate::error::{XCapError, XCapResult};
+
+use super::utils::{get_atom, get_current_screen_buf, get_xcb_connection_and_index};
+
+/// `_ICC_PROFILE` property of the RandR output.
+fn get_output_icc_profile(output: Output) -> XCapResult<Option<Vec<u8>>> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let atom = get_atom("_ICC_PROFILE")?;
+
+    let get_output_property_cookie = conn.send_request(&GetOutputProperty {
+        output,
+        property: atom,
+        r#type: ATOM_ANY,
+        long_offset: 0,
+        long_length: u32::MAX / 4,
+        delete: false,
+        pending: false,
+    });
+    let get_output_property_reply = conn.wait_for_reply(get_output_property_cookie)?;
+
+    if get_output_property_reply.format() != 8 {
+        return Ok(None);
+    }
+
+    let icc_profile = get_output_property_reply.data::<u8>().to_vec();
+
+    Ok((!icc_profile.is_empty()).then_some(icc_profile))
+}
+
+/// `_ICC_PROFILE` (first monitor) or `_ICC_PROFILE_n` root window property, per the
+/// ICC Profiles in X Specification.
+fn get_root_icc_profile(output: Output) -> XCapResult<Option<Vec<u8>>> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let screen_buf = get_current_screen_buf()?;
+
+    let get_monitors_cookie = conn.send_request(&GetMonitors {
+        window: screen_buf.root(),
+        get_active: true,
+    });
+    let get_monitors_reply = conn.wait_for_reply(get_monitors_cookie)?;
+
+    let index = get_monitors_reply
+        .monitors()
+        .position(|monitor_info| monitor_info.outputs().contains(&output))
+        .ok_or_else(|| XCapError::new("Not found monitor"))?;
+
+    let name = match index {
+        0 => "_ICC_PROFILE".to_string(),
+        index => format!("_ICC_PROFILE_{index}"),
+    };
+    let atom = get_atom(&name)?;
+
+    let get_property_cookie = conn.send_request(&GetProperty {
+        delete: false,
+        window: screen_buf.root(),
+        property: atom,
+        r#type: ATOM_ANY,
+        long_offset: 0,
+        long_length: u32::MAX / 4,
+    });
+    let get_property_reply = conn.wait_for_reply(get_property_cookie)?;
+
+    if get_property_reply.format() != 8 || get_property_reply.length() == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(get_property_reply.value::<u8>().to_vec()))
+}
+
+/// Default profile colord assigned to the output's device.
+fn get_colord_icc_profile(output: Output) -> XCapResult<Option<Vec<u8>>> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let get_output_info_cookie = conn.send_request(&GetOutputInfo {
+        output,
+        config_timestamp: CURRENT_TIME,
+    });
+    let get_output_info_reply = conn.wait_for_reply(get_output_info_cookie)?;
+    let output_name = String::from_utf8(get_output_info_reply.name().to_vec())?;
+
+    let conn = ZBusConnection::system()?;
+    let color_manager = Proxy::new(
+        &conn,
+        "org.freedesktop.ColorManager",
+        "/org/freedesktop/ColorManager",
+        "org.freedesktop.ColorManager",
+    )?;
+    let device_path: OwnedObjectPath =
+        color_manager.call("FindDeviceByProperty", &("XRANDR_name", &output_name))?;
+
+    let device = Proxy::new(
+        &conn,
+        "org.freedesktop.ColorManager",
+        device_path,
+        "org.freedesktop.ColorManager.Device",
+    )?;
+    // 第一个 profile 是设备的默认 profile
+    let profiles: Vec<OwnedObjectPath> = device.get_property("Profiles")?;
+    let Some(profile_path) = profiles.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let profile = Proxy::new(
+        &conn,
+        "org.freedesktop.ColorManager",
+        profile_path,
+        "org.freedesktop.ColorManager.Profile",
+    )?;
+    let filename: String = profile.get_property("Filename")?;
+
+    Ok(Some(fs::read(filename)?))
+}
+
+pub fn get_icc_profile(output: Output) -> XCapResult<Option<Vec<u8>>> {
+    // 缺少属性或 colord 不可用时继续尝试下一个来源
+    let icc_profile = get_output_icc_profile(output)
+        .ok()
+        .flatten()
+        .or_else(|| get_root_icc_profile(output).ok().flatten())
+        .or_else(|| get_colord_icc_profile(output).ok().flatten());
+
+    Ok(icc_profile)
+}
@@ -1,4 +1,4 @@
-use std::{ffi::CStr, sync::mpsc::Receiver};
+use std::sync::mpsc::Receiver;
 
 use image::RgbaImage;
 use xcb::{
@@ -11,12 +11,19 @@ use xcb::{
 };
 
 use crate::{
+    edid::Edid,
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    pixel_format::PixelFormat,
+    transform::Transform,
+    video_recorder::{Frame, FramePool, VideoRecorderOptions},
 };
 
 use super::{
-    capture::{capture_monitor, capture_region},
+    capture::{
+        capture_monitor, capture_monitor_as, capture_monitor_into, capture_monitor_thumbnail,
+        capture_region,
+    },
+    icc_profile::get_icc_profile,
     impl_video_recorder::ImplVideoRecorder,
     utils::{
         get_atom, get_current_screen_buf, get_monitor_info_buf, get_xcb_connection_and_index,
@@ -54,7 +61,7 @@ fn get_current_frequency(mode_infos: Vec<ModeInfo>, mode: Mode) -> f32 {
     }
 }
 
-fn get_scale_factor() -> XCapResult<f32> {
+pub fn get_scale_factor() -> XCapResult<f32> {
     if wayland_detect() {
         // for wayland we can get all the outputs, and get the maximum scaling of them.
         let wayshot_conn = libwayshot_xcap::WayshotConnection::new()?;
@@ -103,7 +110,30 @@ fn get_scale_factor() -> XCapResult<f32> {
     Ok(dpi / 96.0)
 }
 
-fn get_rotation_frequency(mode_infos: Vec<ModeInfo>, output: &Output) -> XCapResult<(f32, f32)> {
+/// rotation 是位标志，旋转和镜像可以同时存在。
+/// RandR 的旋转方向是逆时针，Transform 的旋转方向是顺时针
+fn randr_transform(crtc_rotation: Rotation) -> Transform {
+    let rotation = if crtc_rotation.contains(Rotation::ROTATE_90) {
+        270
+    } else if crtc_rotation.contains(Rotation::ROTATE_180) {
+        180
+    } else if crtc_rotation.contains(Rotation::ROTATE_270) {
+        90
+    } else {
+        0
+    };
+
+    Transform::new(
+        rotation,
+        crtc_rotation.contains(Rotation::REFLECT_X),
+        crtc_rotation.contains(Rotation::REFLECT_Y),
+    )
+}
+
+fn get_transform_frequency(
+    mode_infos: Vec<ModeInfo>,
+    output: &Output,
+) -> XCapResult<(Transform, f32)> {
     let (conn, _) = get_xcb_connection_and_index()?;
     let get_output_info_cookie = conn.send_request(&GetOutputInfo {
         output: *output,
@@ -121,17 +151,11 @@ fn get_rotation_frequency(mode_infos: Vec<ModeInfo>, output: &Output) -> XCapRes
 
     let mode = get_crtc_info_reply.mode();
 
-    let rotation = match get_crtc_info_reply.rotation() {
-        Rotation::ROTATE_0 => 0.0,
-        Rotation::ROTATE_90 => 90.0,
-        Rotation::ROTATE_180 => 180.0,
-        Rotation::ROTATE_270 => 270.0,
-        _ => 0.0,
-    };
+    let transform = randr_transform(get_crtc_info_reply.rotation());
 
     let frequency = get_current_frequency(mode_infos, mode);
 
-    Ok((rotation, frequency))
+    Ok((transform, frequency))
 }
 
 fn get_mode_infos() -> XCapResult<Vec<ModeInfo>> {
@@ -159,7 +183,8 @@ fn get_output_edid(output: Output) -> XCapResult<Vec<u8>> {
         property: atom,
         r#type: ATOM_ANY,
         long_offset: 0,
-        long_length: 128,
+        // 包含所有扩展块
+        long_length: u32::MAX / 4,
         delete: false,
         pending: false,
     });
@@ -170,28 +195,10 @@ fn get_output_edid(output: Output) -> XCapResult<Vec<u8>> {
     Ok(edid)
 }
 
-fn is_builtin_edid(edid: &[u8]) -> bool {
-    const DESCRIPTOR_OFFSET: usize = 0x36;
-
-    // 遍历 EDID 描述符块
-    for i in 0..4 {
-        let offset = DESCRIPTOR_OFFSET + i * 18;
-        if offset + 5 >= edid.len() {
-            break;
-        }
-
-        // 检查描述符类型 (0xFC 为显示器名称)
-        if edid[offset] == 0xFC {
-            let text = &edid[offset + 5..offset + 18];
-            if let Ok(name) = CStr::from_bytes_until_nul(text)
-                && name.to_string_lossy().contains("Internal")
-            {
-                return true;
-            }
-        }
-    }
-
-    false
+fn is_builtin_edid(edid: &Edid) -> bool {
+    edid.name
+        .as_deref()
+        .is_some_and(|name| name.contains("Internal"))
 }
 
 impl ImplMonitor {
@@ -281,7 +288,14 @@ impl ImplMonitor {
     }
 
     pub fn friendly_name(&self) -> XCapResult<String> {
-        self.name()
+        match self.edid().ok().and_then(|edid| edid.name) {
+            Some(name) if !name.is_empty() => Ok(name),
+            _ => self.name(),
+        }
+    }
+
+    pub fn edid(&self) -> XCapResult<Edid> {
+        Edid::parse(&get_output_edid(self.output)?)
     }
 
     pub fn x(&self) -> XCapResult<i32> {
@@ -312,11 +326,46 @@ impl ImplMonitor {
         Ok(((height as f32) / scale_factor) as u32)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        let monitor_info_buf = get_monitor_info_buf(self.output)?;
+
+        Ok((
+            monitor_info_buf.width() as u32,
+            monitor_info_buf.height() as u32,
+        ))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
+    /// Bounds of the monitor in the coordinate space of window geometry, X11 windows use
+    /// unscaled pixels.
+    pub fn window_space_bounds(&self) -> XCapResult<(i32, i32, u32, u32)> {
+        let monitor_info_buf = get_monitor_info_buf(self.output)?;
+
+        Ok((
+            monitor_info_buf.x() as i32,
+            monitor_info_buf.y() as i32,
+            monitor_info_buf.width() as u32,
+            monitor_info_buf.height() as u32,
+        ))
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
+        Ok(self.transform()?.rotation as f32)
+    }
+
+    pub fn transform(&self) -> XCapResult<Transform> {
         let mode_infos = get_mode_infos()?;
-        let (rotation, _) = get_rotation_frequency(mode_infos, &self.output).unwrap_or((0.0, 0.0));
+        let (transform, _) = get_transform_frequency(mode_infos, &self.output)
+            .unwrap_or((Transform::default(), 0.0));
+
+        Ok(transform)
+    }
 
-        Ok(rotation)
+    pub fn icc_profile(&self) -> XCapResult<Option<Vec<u8>>> {
+        get_icc_profile(self.output)
     }
 
     pub fn scale_factor(&self) -> XCapResult<f32> {
@@ -327,7 +376,8 @@ impl ImplMonitor {
 
     pub fn frequency(&self) -> XCapResult<f32> {
         let mode_infos = get_mode_infos()?;
-        let (_, frequency) = get_rotation_frequency(mode_infos, &self.output).unwrap_or((0.0, 0.0));
+        let (_, frequency) = get_transform_frequency(mode_infos, &self.output)
+            .unwrap_or((Transform::default(), 0.0));
         Ok(frequency)
     }
 
@@ -346,13 +396,25 @@ impl ImplMonitor {
 
         let edid = get_output_edid(self.output)?;
 
-        Ok(is_builtin_edid(&edid))
+        Ok(Edid::parse(&edid).is_ok_and(|edid| is_builtin_edid(&edid)))
     }
 
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         capture_monitor(self)
     }
 
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        capture_monitor_as(self, format)
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        capture_monitor_into(self, PixelFormat::Rgba, buffer, stride)
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        capture_monitor_thumbnail(self, max_width, max_height)
+    }
+
     pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
         // Validate region bounds
         let monitor_x = self.x()?;
@@ -372,7 +434,34 @@ impl ImplMonitor {
         capture_region(self, x, y, width, height)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
-        ImplVideoRecorder::new(self.clone())
+    pub fn video_recorder(
+        &self,
+        options: &VideoRecorderOptions,
+        pool: FramePool,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        ImplVideoRecorder::new(self.clone(), options.format, pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_randr_transform() {
+        // xrandr --rotate left 对应 ROTATE_90，画面逆时针旋转，即顺时针旋转 270 度
+        assert_eq!(
+            randr_transform(Rotation::ROTATE_90),
+            Transform::new(270, false, false)
+        );
+        assert_eq!(
+            randr_transform(Rotation::ROTATE_270),
+            Transform::new(90, false, false)
+        );
+        assert_eq!(
+            randr_transform(Rotation::ROTATE_180 | Rotation::REFLECT_X),
+            Transform::new(180, true, false)
+        );
+        assert!(randr_transform(Rotation::ROTATE_0).is_identity());
     }
 }
@@ -1,6 +1,10 @@
 use std::sync::mpsc::Receiver;
 
-use crate::{XCapResult, video_recorder::Frame};
+use crate::{
+    XCapResult,
+    pixel_format::PixelFormat,
+    video_recorder::{Frame, FramePool},
+};
 
 use super::{
     impl_monitor::ImplMonitor, utils::wayland_detect, wayland_video_recorder::WaylandVideoRecorder,
@@ -14,12 +18,16 @@ pub enum ImplVideoRecorder {
 }
 
 impl ImplVideoRecorder {
-    pub fn new(monitor: ImplMonitor) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        monitor: ImplMonitor,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         if wayland_detect() {
-            let (recorder, receiver) = WaylandVideoRecorder::new(monitor)?;
+            let (recorder, receiver) = WaylandVideoRecorder::new(monitor, format, pool)?;
             Ok((ImplVideoRecorder::Wayland(recorder), receiver))
         } else {
-            let (recorder, receiver) = XorgVideoRecorder::new(monitor)?;
+            let (recorder, receiver) = XorgVideoRecorder::new(monitor, format, pool)?;
             Ok((ImplVideoRecorder::Xorg(recorder), receiver))
         }
     }
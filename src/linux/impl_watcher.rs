@@ -0,0 +1,553 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt, io,
+    os::fd::{AsRawFd, RawFd},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use wayland_client::{
+    Connection as WaylandConnection, Dispatch, Proxy, QueueHandle,
+    protocol::{
+        wl_output::{self, WlOutput},
+        wl_registry::{self, WlRegistry},
+    },
+};
+use xcb::{
+    Connection as XcbConnection, Extension, XidNew,
+    randr::{self, NotifyMask, SelectInput},
+    x::{
+        self, ATOM_WINDOW, ATOM_WM_NAME, Atom, ChangeWindowAttributes, Cw, EventMask, GetProperty,
+        Window,
+    },
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    polling_watcher::MonitorSnapshots,
+    watcher::{MonitorEvent, WindowEvent},
+};
+
+use super::{
+    impl_window::{ImplWindow, get_position_and_size},
+    utils::{get_atom, get_xcb_connection_and_index, wayland_detect},
+};
+
+/// A dedicated connection that receives events from the root window, requests of other
+/// connections are not blocked while waiting.
+fn connect_root(event_mask: EventMask) -> XCapResult<(XcbConnection, Window)> {
+    let (conn, index) = get_xcb_connection_and_index()?;
+
+    let root = conn
+        .get_setup()
+        .roots()
+        .nth(index as usize)
+        .ok_or_else(|| XCapError::new("Not found screen"))?
+        .root();
+
+    conn.send_and_check_request(&ChangeWindowAttributes {
+        window: root,
+        value_list: &[Cw::EventMask(event_mask)],
+    })
+    .map_err(xcb::Error::from)?;
+
+    Ok((conn, root))
+}
+
+fn get_active_window(
+    conn: &XcbConnection,
+    root: Window,
+    active_window_atom: Atom,
+) -> XCapResult<Option<Window>> {
+    let get_property_cookie = conn.send_request(&GetProperty {
+        delete: false,
+        window: root,
+        property: active_window_atom,
+        r#type: ATOM_WINDOW,
+        long_offset: 0,
+        long_length: 1,
+    });
+    let get_property_reply = conn.wait_for_reply(get_property_cookie)?;
+
+    // 没有活动窗口时值为 None(0) 或者属性不存在
+    let active_window = get_property_reply
+        .value::<u32>()
+        .first()
+        .filter(|&&id| id != 0)
+        .map(|&id| Window::new(id));
+
+    Ok(active_window)
+}
+
+pub(crate) struct ImplActiveWindowWatcher {
+    conn: XcbConnection,
+    root: Window,
+    active_window_atom: Atom,
+    active_window: Option<Window>,
+}
+
+impl fmt::Debug for ImplActiveWindowWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImplActiveWindowWatcher")
+            .field("root", &self.root)
+            .field("active_window", &self.active_window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ImplActiveWindowWatcher {
+    pub fn new() -> XCapResult<ImplActiveWindowWatcher> {
+        let (conn, root) = connect_root(EventMask::PROPERTY_CHANGE)?;
+        let active_window_atom = get_atom("_NET_ACTIVE_WINDOW")?;
+        let active_window = get_active_window(&conn, root, active_window_atom)?;
+
+        Ok(ImplActiveWindowWatcher {
+            conn,
+            root,
+            active_window_atom,
+            active_window,
+        })
+    }
+
+    pub fn next_change(&mut self) -> XCapResult<Option<ImplWindow>> {
+        loop {
+            let event = self.conn.wait_for_event()?;
+            let xcb::Event::X(x::Event::PropertyNotify(property_notify_event)) = event else {
+                continue;
+            };
+
+            if property_notify_event.window() != self.root
+                || property_notify_event.atom() != self.active_window_atom
+            {
+                continue;
+            }
+
+            // 窗口管理器可能重复写入相同的值
+            let active_window = get_active_window(&self.conn, self.root, self.active_window_atom)?;
+            if active_window != self.active_window {
+                self.active_window = active_window;
+                return Ok(active_window.map(ImplWindow::new));
+            }
+        }
+    }
+}
+
+fn get_client_list(
+    conn: &XcbConnection,
+    root: Window,
+    client_list_atom: Atom,
+) -> XCapResult<Vec<Window>> {
+    let get_property_cookie = conn.send_request(&GetProperty {
+        delete: false,
+        window: root,
+        property: client_list_atom,
+        r#type: ATOM_WINDOW,
+        long_offset: 0,
+        // 读取完整的列表，窗口数量没有上限
+        long_length: u32::MAX / 4,
+    });
+    let get_property_reply = conn.wait_for_reply(get_property_cookie)?;
+
+    Ok(get_property_reply.value::<Window>().to_vec())
+}
+
+pub(crate) struct ImplWindowWatcher {
+    conn: XcbConnection,
+    root: Window,
+    client_list_atom: Atom,
+    title_atoms: [Atom; 2],
+    state_atom: Atom,
+    /// 已知窗口及其位置和大小
+    windows: HashMap<Window, (i32, i32, u32, u32)>,
+    events: VecDeque<WindowEvent>,
+}
+
+impl fmt::Debug for ImplWindowWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImplWindowWatcher")
+            .field("root", &self.root)
+            .field("windows", &self.windows)
+            .field("events", &self.events)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ImplWindowWatcher {
+    pub fn new() -> XCapResult<ImplWindowWatcher> {
+        let (conn, root) =
+            connect_root(EventMask::SUBSTRUCTURE_NOTIFY | EventMask::PROPERTY_CHANGE)?;
+
+        let mut impl_window_watcher = ImplWindowWatcher {
+            conn,
+            root,
+            client_list_atom: get_atom("_NET_CLIENT_LIST")?,
+            title_atoms: [get_atom("_NET_WM_NAME")?, ATOM_WM_NAME],
+            state_atom: get_atom("_NET_WM_STATE")?,
+            windows: HashMap::new(),
+            events: VecDeque::new(),
+        };
+
+        for window in get_client_list(
+            &impl_window_watcher.conn,
+            root,
+            impl_window_watcher.client_list_atom,
+        )? {
+            impl_window_watcher.track(window);
+        }
+
+        Ok(impl_window_watcher)
+    }
+
+    /// Listen to the window's own property and geometry changes, false when the window is gone.
+    fn track(&mut self, window: Window) -> bool {
+        let is_selected = self
+            .conn
+            .send_and_check_request(&ChangeWindowAttributes {
+                window,
+                value_list: &[Cw::EventMask(
+                    EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY,
+                )],
+            })
+            .is_ok();
+
+        match get_position_and_size(&window) {
+            Ok(geometry) if is_selected => {
+                self.windows.insert(window, geometry);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn window_event(window: Window, event: fn(crate::Window) -> WindowEvent) -> WindowEvent {
+        event(crate::Window::new(ImplWindow::new(window)))
+    }
+
+    fn update_client_list(&mut self) -> XCapResult<()> {
+        let client_list = get_client_list(&self.conn, self.root, self.client_list_atom)?;
+
+        for &window in &client_list {
+            if !self.windows.contains_key(&window) && self.track(window) {
+                self.events
+                    .push_back(Self::window_event(window, WindowEvent::Created));
+            }
+        }
+
+        let removed: Vec<Window> = self
+            .windows
+            .keys()
+            .filter(|window| !client_list.contains(window))
+            .copied()
+            .collect();
+        for window in removed {
+            self.windows.remove(&window);
+            self.events
+                .push_back(Self::window_event(window, WindowEvent::Destroyed));
+        }
+
+        Ok(())
+    }
+
+    fn update_geometry(&mut self, window: Window) {
+        let Some(&(x, y, width, height)) = self.windows.get(&window) else {
+            return;
+        };
+        // 窗口管理器重新设置父窗口后，事件中的坐标是相对于边框窗口的，这里统一读取根窗口坐标
+        let Ok(geometry) = get_position_and_size(&window) else {
+            return;
+        };
+        self.windows.insert(window, geometry);
+
+        if (geometry.0, geometry.1) != (x, y) {
+            self.events
+                .push_back(Self::window_event(window, WindowEvent::Moved));
+        }
+        if (geometry.2, geometry.3) != (width, height) {
+            self.events
+                .push_back(Self::window_event(window, WindowEvent::Resized));
+        }
+    }
+
+    fn handle_event(&mut self, event: x::Event) -> XCapResult<()> {
+        match event {
+            x::Event::PropertyNotify(property_notify_event) => {
+                let (window, atom) = (property_notify_event.window(), property_notify_event.atom());
+
+                if window == self.root {
+                    if atom == self.client_list_atom {
+                        self.update_client_list()?;
+                    }
+                } else if self.windows.contains_key(&window) {
+                    if self.title_atoms.contains(&atom) {
+                        self.events
+                            .push_back(Self::window_event(window, WindowEvent::TitleChanged));
+                    } else if atom == self.state_atom {
+                        self.events
+                            .push_back(Self::window_event(window, WindowEvent::StateChanged));
+                    }
+                }
+            }
+            x::Event::ConfigureNotify(configure_notify_event) => {
+                self.update_geometry(configure_notify_event.window());
+            }
+            x::Event::DestroyNotify(destroy_notify_event) => {
+                let window = destroy_notify_event.window();
+                if self.windows.remove(&window).is_some() {
+                    self.events
+                        .push_back(Self::window_event(window, WindowEvent::Destroyed));
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<WindowEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+
+            match self.conn.wait_for_event() {
+                Ok(xcb::Event::X(event)) => self.handle_event(event)?,
+                Ok(_) => {}
+                // 请求已销毁的窗口会产生错误事件，忽略即可
+                Err(xcb::Error::Protocol(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+/// Sends a signal whenever an output is announced, changed or removed.
+struct OutputListener {
+    sender: Sender<XCapResult<()>>,
+    output_names: Vec<u32>,
+    is_disconnected: bool,
+}
+
+impl OutputListener {
+    fn signal(&mut self) {
+        if self.sender.send(Ok(())).is_err() {
+            self.is_disconnected = true;
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for OutputListener {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &WaylandConnection,
+        qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == WlOutput::interface().name => {
+                registry.bind::<WlOutput, _, _>(name, version.min(4), qh, ());
+                state.output_names.push(name);
+            }
+            wl_registry::Event::GlobalRemove { name } if state.output_names.contains(&name) => {
+                state
+                    .output_names
+                    .retain(|output_name| *output_name != name);
+                state.signal();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, ()> for OutputListener {
+    fn event(
+        state: &mut Self,
+        _: &WlOutput,
+        event: wl_output::Event,
+        _: &(),
+        _: &WaylandConnection,
+        _: &QueueHandle<Self>,
+    ) {
+        // done 在一组输出属性发送完之后触发，新增的输出也会收到
+        if let wl_output::Event::Done = event {
+            state.signal();
+        }
+    }
+}
+
+/// 监听线程等待事件的最长时间，超时后检查监听器是否已被丢弃
+const LISTEN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Wait until `fd` is readable, returns false on timeout.
+fn wait_readable(fd: RawFd, timeout: Duration) -> XCapResult<bool> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    let result = unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) };
+    if result < 0 {
+        let err = io::Error::last_os_error();
+        if err.kind() == io::ErrorKind::Interrupted {
+            return Ok(false);
+        }
+
+        return Err(err.into());
+    }
+
+    Ok(result > 0)
+}
+
+fn listen_wayland_outputs(
+    sender: Sender<XCapResult<()>>,
+    stopped: Arc<AtomicBool>,
+) -> XCapResult<()> {
+    let conn =
+        WaylandConnection::connect_to_env().map_err(|err| XCapError::new(err.to_string()))?;
+    let mut event_queue = conn.new_event_queue();
+    conn.display().get_registry(&event_queue.handle(), ());
+
+    thread::spawn(move || {
+        let mut output_listener = OutputListener {
+            sender,
+            output_names: Vec::new(),
+            is_disconnected: false,
+        };
+
+        // 与 blocking_dispatch 相同，只是等待时带超时，监听器被丢弃后线程和连接能及时释放
+        let result = (|| -> XCapResult<()> {
+            while !output_listener.is_disconnected && !stopped.load(Ordering::Relaxed) {
+                event_queue
+                    .dispatch_pending(&mut output_listener)
+                    .map_err(|err| XCapError::new(err.to_string()))?;
+                event_queue
+                    .flush()
+                    .map_err(|err| XCapError::new(err.to_string()))?;
+
+                // 返回 None 说明还有未处理的事件
+                let Some(guard) = event_queue.prepare_read() else {
+                    continue;
+                };
+                if !wait_readable(guard.connection_fd().as_raw_fd(), LISTEN_TIMEOUT)? {
+                    continue;
+                }
+
+                match guard.read() {
+                    Ok(_) => {}
+                    Err(wayland_client::backend::WaylandError::Io(err))
+                        if err.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(err) => return Err(XCapError::new(err.to_string())),
+                }
+            }
+
+            Ok(())
+        })();
+
+        if let Err(err) = result {
+            log::error!("Wayland output listener stopped: {err:?}");
+        }
+    });
+
+    Ok(())
+}
+
+fn listen_randr(sender: Sender<XCapResult<()>>, stopped: Arc<AtomicBool>) -> XCapResult<()> {
+    // 需要启用 RandR 扩展才能解析它的事件
+    let (conn, index) = XcbConnection::connect_with_extensions(None, &[], &[Extension::RandR])?;
+    let root = conn
+        .get_setup()
+        .roots()
+        .nth(index as usize)
+        .ok_or_else(|| XCapError::new("Not found screen"))?
+        .root();
+
+    conn.send_and_check_request(&SelectInput {
+        window: root,
+        enable: NotifyMask::SCREEN_CHANGE | NotifyMask::CRTC_CHANGE | NotifyMask::OUTPUT_CHANGE,
+    })
+    .map_err(xcb::Error::from)?;
+
+    thread::spawn(move || {
+        while !stopped.load(Ordering::Relaxed) {
+            // 先取出 xcb 已经读取的事件，队列为空时再带超时等待连接可读
+            let signal = match conn.poll_for_event() {
+                Ok(Some(xcb::Event::RandR(
+                    randr::Event::ScreenChangeNotify(_) | randr::Event::Notify(_),
+                ))) => Ok(()),
+                Ok(Some(_)) | Err(xcb::Error::Protocol(_)) => continue,
+                Ok(None) => match wait_readable(conn.as_raw_fd(), LISTEN_TIMEOUT) {
+                    Ok(_) => continue,
+                    Err(err) => Err(err),
+                },
+                Err(err) => Err(err.into()),
+            };
+
+            let is_err = signal.is_err();
+            if sender.send(signal).is_err() || is_err {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) struct ImplMonitorWatcher {
+    receiver: Receiver<XCapResult<()>>,
+    /// 通知监听线程退出
+    stopped: Arc<AtomicBool>,
+    snapshots: MonitorSnapshots,
+    events: VecDeque<MonitorEvent>,
+}
+
+impl ImplMonitorWatcher {
+    pub fn new() -> XCapResult<ImplMonitorWatcher> {
+        let (sender, receiver) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        listen_randr(sender.clone(), stopped.clone())?;
+        // XWayland 的 RandR 信息会滞后于 wl_output，两个来源都监听，快照相同时不会产生事件
+        if wayland_detect()
+            && let Err(err) = listen_wayland_outputs(sender, stopped.clone())
+        {
+            log::warn!("Failed to listen for wl_output changes: {err:?}");
+        }
+
+        Ok(ImplMonitorWatcher {
+            receiver,
+            stopped,
+            snapshots: MonitorSnapshots::new()?,
+            events: VecDeque::new(),
+        })
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<MonitorEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+
+            self.receiver.recv()??;
+            self.events.extend(self.snapshots.update()?);
+        }
+    }
+}
+
+impl Drop for ImplMonitorWatcher {
+    fn drop(&mut self) {
+        // 监听线程最多在 LISTEN_TIMEOUT 之后退出并关闭连接
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
@@ -2,26 +2,39 @@ use image::RgbaImage;
 use xcb::{
     Xid,
     x::{
-        ATOM_ATOM, ATOM_CARDINAL, ATOM_NONE, ATOM_STRING, ATOM_WM_CLASS, ATOM_WM_NAME, Atom,
-        Drawable, GetGeometry, GetProperty, GetPropertyReply, QueryPointer, TranslateCoordinates,
+        ATOM_ATOM, ATOM_CARDINAL, ATOM_NONE, ATOM_STRING, ATOM_WINDOW, ATOM_WM_CLASS, ATOM_WM_NAME,
+        ATOM_WM_TRANSIENT_FOR, Atom, Drawable, GetAtomName, GetGeometry, GetProperty,
+        GetPropertyReply, GetWindowAttributes, MapState, QueryPointer, TranslateCoordinates,
         Window,
     },
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    capture_options::scale_size,
+    error::{XCapError, XCapResult},
+    pixel_format::PixelFormat,
+    process_info::ProcessInfo,
+    video_recorder::Frame,
+    window_type::WindowType,
+};
 
 use super::{
-    capture::capture_window,
-    impl_monitor::ImplMonitor,
+    capture::{capture_window, capture_window_as, capture_window_into, capture_window_thumbnail},
+    impl_monitor::{ImplMonitor, get_scale_factor},
+    process_info::get_process_info,
     utils::{get_atom, get_xcb_connection_and_index},
+    window_icon::get_window_icon,
 };
 
+/// `_NET_WM_DESKTOP` value of windows shown on all desktops.
+const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+
 #[derive(Debug, Clone)]
 pub(crate) struct ImplWindow {
     pub window: Window,
 }
 
-fn get_window_property(
+pub fn get_window_property(
     window: Window,
     property: Atom,
     r#type: Atom,
@@ -73,7 +86,7 @@ fn get_active_window_id() -> XCapResult<u32> {
     Err(XCapError::new("Get active window id failed"))
 }
 
-fn get_position_and_size(window: &Window) -> XCapResult<(i32, i32, u32, u32)> {
+pub fn get_position_and_size(window: &Window) -> XCapResult<(i32, i32, u32, u32)> {
     let (conn, _) = get_xcb_connection_and_index()?;
     let get_geometry_cookie = conn.send_request(&GetGeometry {
         drawable: Drawable::Window(*window),
@@ -118,8 +131,50 @@ fn get_window_state(window: &Window) -> XCapResult<(bool, bool)> {
     ))
 }
 
+/// Whether `_NET_WM_STATE` contains the state, false when no window uses that state yet.
+fn has_window_state(window: &Window, state: &str) -> XCapResult<bool> {
+    let Ok(state_atom) = get_atom(state) else {
+        return Ok(false);
+    };
+    let wm_state_atom = get_atom("_NET_WM_STATE")?;
+
+    let wm_state_reply = get_window_property(*window, wm_state_atom, ATOM_ATOM, 0, 32)?;
+
+    Ok(wm_state_reply.value::<Atom>().contains(&state_atom))
+}
+
+fn get_cardinal_property(window: &Window, property: &str) -> XCapResult<Option<u32>> {
+    let Ok(property_atom) = get_atom(property) else {
+        return Ok(None);
+    };
+
+    let reply = get_window_property(*window, property_atom, ATOM_CARDINAL, 0, 1)?;
+
+    Ok(reply.value::<u32>().first().copied())
+}
+
+fn get_transient_for(window: &Window) -> XCapResult<Option<Window>> {
+    let reply = get_window_property(*window, ATOM_WM_TRANSIENT_FOR, ATOM_WINDOW, 0, 1)?;
+
+    let transient_for = reply
+        .value::<Window>()
+        .first()
+        .copied()
+        .filter(|window| !window.is_none());
+
+    Ok(transient_for)
+}
+
+fn is_viewable(window: &Window) -> XCapResult<bool> {
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let get_window_attributes_cookie = conn.send_request(&GetWindowAttributes { window: *window });
+    let get_window_attributes_reply = conn.wait_for_reply(get_window_attributes_cookie)?;
+
+    Ok(get_window_attributes_reply.map_state() == MapState::Viewable)
+}
+
 impl ImplWindow {
-    fn new(window: Window) -> ImplWindow {
+    pub fn new(window: Window) -> ImplWindow {
         ImplWindow { window }
     }
 
@@ -164,6 +219,30 @@ impl ImplWindow {
 
         Ok(impl_windows)
     }
+
+    pub fn from_point(x: i32, y: i32) -> XCapResult<ImplWindow> {
+        // 与 ImplMonitor::from_point 一样，坐标是缩放后的坐标
+        let scale_factor = get_scale_factor().unwrap_or(1.0);
+        let x = (x as f32 * scale_factor) as i32;
+        let y = (y as f32 * scale_factor) as i32;
+
+        for impl_window in ImplWindow::all()? {
+            // 窗口可能已经关闭，忽略获取失败的窗口
+            let Ok((left, top, width, height)) = get_position_and_size(&impl_window.window) else {
+                continue;
+            };
+
+            if x < left || x >= left + width as i32 || y < top || y >= top + height as i32 {
+                continue;
+            }
+
+            if impl_window.is_viewable().unwrap_or(false) {
+                return Ok(impl_window);
+            }
+        }
+
+        Err(XCapError::new("Not found window"))
+    }
 }
 
 impl ImplWindow {
@@ -175,6 +254,10 @@ impl ImplWindow {
         get_window_pid(&self.window)
     }
 
+    pub fn process(&self) -> XCapResult<ProcessInfo> {
+        get_process_info(&self.window, self.pid()?)
+    }
+
     pub fn app_name(&self) -> XCapResult<String> {
         let get_class_reply =
             get_window_property(self.window, ATOM_WM_CLASS, ATOM_STRING, 0, 1024)?;
@@ -303,12 +386,29 @@ impl ImplWindow {
         Ok(height)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        let scale_factor = self.current_monitor()?.scale_factor()?;
+
+        Ok(scale_size(self.physical_size()?, 1.0 / scale_factor))
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         let (is_minimized, _) = get_window_state(&self.window)?;
 
         Ok(is_minimized)
     }
 
+    /// 未最小化且已映射，位于其他工作区的窗口通常不会被映射
+    pub fn is_viewable(&self) -> XCapResult<bool> {
+        let (is_minimized, _) = get_window_state(&self.window)?;
+
+        Ok(!is_minimized && is_viewable(&self.window)?)
+    }
+
     pub fn is_maximized(&self) -> XCapResult<bool> {
         let (is_minimized, _) = get_window_state(&self.window)?;
 
@@ -321,7 +421,101 @@ impl ImplWindow {
         Ok(active_window_id == self.id()?)
     }
 
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        let window_type_atom = get_atom("_NET_WM_WINDOW_TYPE")?;
+        let window_type_reply =
+            get_window_property(self.window, window_type_atom, ATOM_ATOM, 0, 32)?;
+
+        let (conn, _) = get_xcb_connection_and_index()?;
+        // 属性按优先级排列，使用第一个能识别的类型
+        for &atom in window_type_reply.value::<Atom>() {
+            let get_atom_name_cookie = conn.send_request(&GetAtomName { atom });
+            let get_atom_name_reply = conn.wait_for_reply(get_atom_name_cookie)?;
+
+            let window_type = match get_atom_name_reply.name().to_utf8().as_ref() {
+                "_NET_WM_WINDOW_TYPE_NORMAL" => WindowType::Normal,
+                "_NET_WM_WINDOW_TYPE_DIALOG" => WindowType::Dialog,
+                "_NET_WM_WINDOW_TYPE_DESKTOP" => WindowType::Desktop,
+                "_NET_WM_WINDOW_TYPE_DOCK" => WindowType::Dock,
+                "_NET_WM_WINDOW_TYPE_TOOLBAR" => WindowType::Toolbar,
+                "_NET_WM_WINDOW_TYPE_MENU" => WindowType::Menu,
+                "_NET_WM_WINDOW_TYPE_UTILITY" => WindowType::Utility,
+                "_NET_WM_WINDOW_TYPE_SPLASH" => WindowType::Splash,
+                "_NET_WM_WINDOW_TYPE_DROPDOWN_MENU" => WindowType::DropdownMenu,
+                "_NET_WM_WINDOW_TYPE_POPUP_MENU" => WindowType::PopupMenu,
+                "_NET_WM_WINDOW_TYPE_TOOLTIP" => WindowType::Tooltip,
+                "_NET_WM_WINDOW_TYPE_NOTIFICATION" => WindowType::Notification,
+                "_NET_WM_WINDOW_TYPE_COMBO" => WindowType::Combo,
+                "_NET_WM_WINDOW_TYPE_DND" => WindowType::Dnd,
+                _ => continue,
+            };
+
+            return Ok(window_type);
+        }
+
+        // 没有设置类型时，有 WM_TRANSIENT_FOR 的窗口视为对话框
+        if get_transient_for(&self.window)?.is_some() {
+            Ok(WindowType::Dialog)
+        } else {
+            Ok(WindowType::Normal)
+        }
+    }
+
+    pub fn is_fullscreen(&self) -> XCapResult<bool> {
+        has_window_state(&self.window, "_NET_WM_STATE_FULLSCREEN")
+    }
+
+    pub fn is_always_on_top(&self) -> XCapResult<bool> {
+        has_window_state(&self.window, "_NET_WM_STATE_ABOVE")
+    }
+
+    pub fn is_sticky(&self) -> XCapResult<bool> {
+        let on_all_desktops = get_cardinal_property(&self.window, "_NET_WM_DESKTOP")?
+            .is_some_and(|desktop| desktop == ALL_DESKTOPS);
+
+        Ok(on_all_desktops || has_window_state(&self.window, "_NET_WM_STATE_STICKY")?)
+    }
+
+    pub fn is_skip_taskbar(&self) -> XCapResult<bool> {
+        has_window_state(&self.window, "_NET_WM_STATE_SKIP_TASKBAR")
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        let opacity = get_cardinal_property(&self.window, "_NET_WM_WINDOW_OPACITY")?
+            .map(|opacity| opacity as f32 / u32::MAX as f32)
+            .unwrap_or(1.0);
+
+        Ok(opacity)
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        Ok(get_transient_for(&self.window)?.map(ImplWindow::new))
+    }
+
+    pub fn desktop(&self) -> XCapResult<Option<u32>> {
+        let desktop = get_cardinal_property(&self.window, "_NET_WM_DESKTOP")?
+            .filter(|&desktop| desktop != ALL_DESKTOPS);
+
+        Ok(desktop)
+    }
+
+    pub fn icon(&self, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        get_window_icon(&self.window, preferred_size)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         capture_window(self)
     }
+
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        capture_window_as(self, format)
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        capture_window_into(self, PixelFormat::Rgba, buffer, stride)
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        capture_window_thumbnail(self, max_width, max_height)
+    }
 }
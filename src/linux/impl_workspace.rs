@@ -0,0 +1,93 @@
+use xcb::x::{ATOM_CARDINAL, Window};
+
+use crate::{error::XCapResult, workspace::Workspace};
+
+use super::{
+    impl_window::get_window_property,
+    utils::{get_atom, get_current_screen_buf},
+};
+
+fn get_root_cardinals(root: Window, property: &str, len: u32) -> XCapResult<Vec<u32>> {
+    let Ok(property_atom) = get_atom(property) else {
+        return Ok(Vec::new());
+    };
+
+    let reply = get_window_property(root, property_atom, ATOM_CARDINAL, 0, len)?;
+
+    Ok(reply.value::<u32>().to_vec())
+}
+
+/// `_NET_DESKTOP_NAMES` is a list of null terminated UTF-8 strings.
+fn parse_desktop_names(names: &[u8]) -> Vec<String> {
+    let names = names.strip_suffix(&[0]).unwrap_or(names);
+    if names.is_empty() {
+        return Vec::new();
+    }
+
+    names
+        .split(|&byte| byte == 0)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect()
+}
+
+fn get_desktop_names(root: Window) -> XCapResult<Vec<String>> {
+    let (Ok(desktop_names_atom), Ok(utf8_string_atom)) =
+        (get_atom("_NET_DESKTOP_NAMES"), get_atom("UTF8_STRING"))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let reply = get_window_property(root, desktop_names_atom, utf8_string_atom, 0, 1024)?;
+
+    Ok(parse_desktop_names(reply.value::<u8>()))
+}
+
+pub fn get_workspaces() -> XCapResult<Vec<Workspace>> {
+    let root = get_current_screen_buf()?.root();
+
+    // 窗口管理器不支持 EWMH 时没有工作区
+    let Some(&number_of_desktops) = get_root_cardinals(root, "_NET_NUMBER_OF_DESKTOPS", 1)?.first()
+    else {
+        return Ok(Vec::new());
+    };
+
+    let current_desktop = get_root_cardinals(root, "_NET_CURRENT_DESKTOP", 1)?
+        .first()
+        .copied();
+    let viewports = get_root_cardinals(root, "_NET_DESKTOP_VIEWPORT", number_of_desktops * 2)?;
+    let mut names = get_desktop_names(root)?.into_iter();
+
+    let workspaces = (0..number_of_desktops)
+        .map(|index| {
+            let viewport = viewports
+                .get(index as usize * 2..index as usize * 2 + 2)
+                .map(|viewport| (viewport[0] as i32, viewport[1] as i32))
+                .unwrap_or_default();
+
+            Workspace {
+                index,
+                name: names.next().unwrap_or_default(),
+                viewport,
+                is_current: current_desktop == Some(index),
+            }
+        })
+        .collect();
+
+    Ok(workspaces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_desktop_names() {
+        assert_eq!(
+            parse_desktop_names("Web\0Code\0Chat\0".as_bytes()),
+            vec!["Web", "Code", "Chat"]
+        );
+        // 最后一个名称可以不带结束符，名称少于工作区数量时其余为空
+        assert_eq!(parse_desktop_names(b"Web\0\0Mail"), vec!["Web", "", "Mail"]);
+        assert!(parse_desktop_names(b"").is_empty());
+    }
+}
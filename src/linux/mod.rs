@@ -1,10 +1,15 @@
 mod capture;
+mod icc_profile;
+mod process_info;
 pub mod utils;
 mod wayland_capture;
 mod wayland_video_recorder;
+mod window_icon;
 pub mod xorg_capture;
 mod xorg_video_recorder;
 
 pub mod impl_monitor;
 pub mod impl_video_recorder;
+pub mod impl_watcher;
 pub mod impl_window;
+pub mod impl_workspace;
@@ -0,0 +1,151 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use xcb::x::{ATOM_ANY, ATOM_WM_CLIENT_MACHINE, Window};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    process_info::ProcessInfo,
+};
+
+use super::impl_window::get_window_property;
+
+/// `/proc` reports times in `USER_HZ` ticks, which is 100 on every architecture.
+const USER_HZ: u64 = 100;
+
+fn is_local_machine(client_machine: &str, hostname: &str) -> bool {
+    let client_machine = client_machine.to_ascii_lowercase();
+    let hostname = hostname.to_ascii_lowercase();
+    let short_name = |name: &str| name.split('.').next().unwrap_or_default().to_string();
+
+    // WM_CLIENT_MACHINE 和主机名都可能是完整域名，也可能只是主机名
+    client_machine == "localhost"
+        || client_machine == hostname
+        || client_machine == short_name(&hostname)
+        || short_name(&client_machine) == hostname
+}
+
+/// `WM_CLIENT_MACHINE` of windows of remote X clients names another host, their `_NET_WM_PID`
+/// is meaningless here. Windows without `WM_CLIENT_MACHINE` are treated as local.
+fn is_local_client(window: &Window) -> XCapResult<bool> {
+    // 客户端可能使用 STRING、UTF8_STRING 或 COMPOUND_TEXT，文本类型的数据都是 8 位的
+    let reply = get_window_property(*window, ATOM_WM_CLIENT_MACHINE, ATOM_ANY, 0, 64)?;
+    if reply.format() != 8 {
+        return Ok(true);
+    }
+    let client_machine = String::from_utf8_lossy(reply.value::<u8>());
+    let client_machine = client_machine.trim_end_matches('\0');
+    if client_machine.is_empty() {
+        return Ok(true);
+    }
+
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname")?;
+
+    Ok(is_local_machine(client_machine, hostname.trim()))
+}
+
+fn parse_cmdline(cmdline: &[u8]) -> Vec<String> {
+    let cmdline = cmdline.strip_suffix(&[0]).unwrap_or(cmdline);
+    if cmdline.is_empty() {
+        return Vec::new();
+    }
+
+    cmdline
+        .split(|&byte| byte == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect()
+}
+
+/// `starttime` of `/proc/<pid>/stat`, in ticks since boot.
+fn parse_start_ticks(stat: &str) -> Option<u64> {
+    // comm 可能包含空格和括号，从最后一个 ')' 之后开始解析，第一个字段是第 3 个字段 state
+    let (_, fields) = stat.rsplit_once(')')?;
+
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// `btime` of `/proc/stat`, the boot time in seconds since the epoch.
+fn parse_boot_time(stat: &str) -> Option<u64> {
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn get_start_time(pid: u32) -> Option<SystemTime> {
+    let start_ticks = parse_start_ticks(&fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)?;
+    let boot_time = parse_boot_time(&fs::read_to_string("/proc/stat").ok()?)?;
+
+    Some(
+        UNIX_EPOCH
+            + Duration::from_secs(boot_time)
+            + Duration::from_millis(start_ticks * 1000 / USER_HZ),
+    )
+}
+
+pub fn get_process_info(window: &Window, pid: u32) -> XCapResult<ProcessInfo> {
+    if !is_local_client(window)? {
+        return Err(XCapError::new("Window belongs to a remote X client"));
+    }
+
+    let proc_dir = PathBuf::from(format!("/proc/{pid}"));
+    if !proc_dir.exists() {
+        return Err(XCapError::new(format!("Process {pid} not found")));
+    }
+
+    // 其他用户的进程没有权限读取 exe
+    let exe_path = fs::read_link(proc_dir.join("exe")).ok();
+    let cmdline = fs::read(proc_dir.join("cmdline"))
+        .map(|cmdline| parse_cmdline(&cmdline))
+        .unwrap_or_default();
+    let name = fs::read_to_string(proc_dir.join("comm"))
+        .map(|comm| comm.trim_end().to_string())
+        .unwrap_or_default();
+
+    Ok(ProcessInfo {
+        pid,
+        name,
+        exe_path,
+        cmdline,
+        start_time: get_start_time(pid),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_local_machine() {
+        assert!(is_local_machine("workstation", "workstation"));
+        assert!(is_local_machine("workstation.example.com", "Workstation"));
+        assert!(is_local_machine("workstation", "workstation.example.com"));
+        assert!(is_local_machine("localhost", "workstation"));
+        assert!(!is_local_machine("server", "workstation"));
+        assert!(!is_local_machine(
+            "server.example.com",
+            "workstation.example.com"
+        ));
+    }
+
+    #[test]
+    fn test_parse_proc() {
+        let stat = "1234 (Web (Content)) S 1 1234 1234 0 -1 4194560 100 0 0 0 5 3 0 0 20 0 \
+                    12 0 987654 123456789 1000";
+        assert_eq!(parse_start_ticks(stat), Some(987654));
+        assert_eq!(
+            parse_boot_time("cpu  1 2 3\nbtime 1700000000\nprocesses 42\n"),
+            Some(1700000000)
+        );
+
+        assert_eq!(
+            parse_cmdline(b"/usr/bin/app\0--flag\0\0value\0"),
+            vec!["/usr/bin/app", "--flag", "", "value"]
+        );
+        assert!(parse_cmdline(b"").is_empty());
+    }
+}
@@ -33,7 +33,11 @@ use zbus::{
     zvariant::{DeserializeDict, OwnedFd, OwnedObjectPath, Type, Value},
 };
 
-use crate::{XCapError, XCapResult, video_recorder::Frame};
+use crate::{
+    XCapError, XCapResult,
+    pixel_format::{PixelFormat, convert_pixels_into, set_opaque},
+    video_recorder::{Frame, FramePool},
+};
 
 use super::{
     impl_monitor::ImplMonitor,
@@ -169,6 +173,8 @@ impl ScreenCast<'_> {
 pub struct WaylandVideoRecorder {
     #[allow(dead_code)]
     monitor: ImplMonitor,
+    format: PixelFormat,
+    pool: FramePool,
     sender: Sender<Frame>,
     is_running: Arc<AtomicBool>,
     active_sender: channel::Sender<bool>,
@@ -178,6 +184,7 @@ impl fmt::Debug for WaylandVideoRecorder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("WaylandVideoRecorder")
             .field("monitor", &self.monitor)
+            .field("format", &self.format)
             .field("sender", &self.sender)
             .field("is_running", &self.is_running)
             // Sender is not Debug
@@ -192,7 +199,11 @@ struct ListenerUserData {
 }
 
 impl WaylandVideoRecorder {
-    pub fn new(monitor: ImplMonitor) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        monitor: ImplMonitor,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         let (sender, receiver) = mpsc::channel();
         let (active_sender, active_receiver) = channel::channel();
 
@@ -211,6 +222,8 @@ impl WaylandVideoRecorder {
 
         let recorder = Self {
             monitor,
+            format,
+            pool,
             sender,
             is_running: Arc::new(AtomicBool::new(false)),
             active_sender,
@@ -226,6 +239,8 @@ impl WaylandVideoRecorder {
         stream_id: u32,
         active_receiver: channel::Receiver<bool>,
     ) -> XCapResult<()> {
+        let format = self.format;
+        let pool = self.pool.clone();
         let sender = self.sender.clone();
         let is_running = self.is_running.clone();
 
@@ -288,34 +303,12 @@ impl WaylandVideoRecorder {
                             }
                             let size = user_data.format.size();
                             if let Some(frame_data) = datas[0].data() {
-                                let buffer = match user_data.format.format() {
-                                    VideoFormat::RGB => {
-                                        let mut buf =
-                                            vec![0; (size.width * size.height * 4) as usize];
-                                        for (src, dst) in frame_data
-                                            .as_chunks::<3>()
-                                            .0
-                                            .iter()
-                                            .zip(buf.as_chunks_mut::<4>().0)
-                                        {
-                                            dst[0] = src[0];
-                                            dst[1] = src[1];
-                                            dst[2] = src[2];
-                                            dst[3] = 255;
-                                        }
-
-                                        buf
-                                    }
-                                    VideoFormat::RGBA => frame_data.to_vec(),
-                                    VideoFormat::RGBx => frame_data.to_vec(),
-                                    VideoFormat::BGRx => {
-                                        let mut buf = frame_data.to_vec();
-                                        for src in buf.as_chunks_mut::<4>().0 {
-                                            src.swap(0, 2);
-                                        }
-
-                                        buf
-                                    }
+                                // RGBx/BGRx 的第四个字节是填充，不是 alpha
+                                let (source_format, is_opaque) = match user_data.format.format() {
+                                    VideoFormat::RGB => (PixelFormat::Rgb, false),
+                                    VideoFormat::RGBA => (PixelFormat::Rgba, false),
+                                    VideoFormat::RGBx => (PixelFormat::Rgba, true),
+                                    VideoFormat::BGRx => (PixelFormat::Bgra, true),
                                     _ => {
                                         log::error!(
                                             "Unsupported format: {:?}",
@@ -325,10 +318,29 @@ impl WaylandVideoRecorder {
                                     }
                                 };
 
-                                if state {
-                                    let _ =
-                                        sender.send(Frame::new(size.width, size.height, buffer));
+                                // 暂停时不取缓冲区，避免把池中的缓冲区丢弃
+                                if !state {
+                                    return;
                                 }
+
+                                // 与请求的格式一致时只做一次拷贝，缓冲区从池中复用
+                                let pixels = (size.width * size.height) as usize;
+                                let frame_data: &[u8] = frame_data;
+                                let frame_data = frame_data
+                                    .get(..pixels * source_format.bytes_per_pixel())
+                                    .unwrap_or(frame_data);
+                                let mut buffer = pool.take(pixels * format.bytes_per_pixel());
+                                convert_pixels_into(frame_data, source_format, &mut buffer, format);
+                                if is_opaque {
+                                    set_opaque(&mut buffer, format);
+                                }
+
+                                let _ = sender.send(Frame::new_with_format(
+                                    size.width,
+                                    size.height,
+                                    format,
+                                    buffer,
+                                ));
                             }
                         }
                     }
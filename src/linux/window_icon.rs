@@ -0,0 +1,139 @@
+use image::{Rgba, RgbaImage};
+use xcb::{
+    XidNew,
+    x::{ATOM_CARDINAL, ATOM_WM_HINTS, Drawable, GetGeometry, Pixmap, Window},
+};
+
+use crate::error::XCapResult;
+
+use super::{
+    impl_window::get_window_property,
+    utils::{get_atom, get_xcb_connection_and_index},
+    xorg_capture::{xorg_capture_pixmap, xorg_get_bitmap},
+};
+
+/// `WM_HINTS` flags, see ICCCM 4.1.2.4.
+const ICON_PIXMAP_HINT: u32 = 1 << 2;
+const ICON_MASK_HINT: u32 = 1 << 5;
+
+/// Pick the icon closest to `preferred_size` from `_NET_WM_ICON` data, an array of
+/// `width, height, width * height ARGB pixels` entries. Ties go to the larger icon.
+fn parse_net_wm_icon(data: &[u32], preferred_size: u32) -> Option<RgbaImage> {
+    let mut icons = Vec::new();
+    let mut rest = data;
+
+    while let [width, height, pixels @ ..] = rest {
+        let len = (*width as usize).checked_mul(*height as usize)?;
+        if len == 0 || pixels.len() < len {
+            break;
+        }
+
+        icons.push((*width, *height, &pixels[..len]));
+        rest = &pixels[len..];
+    }
+
+    let (width, height, pixels) = icons.into_iter().min_by_key(|&(width, height, _)| {
+        let size = width.max(height);
+        (size.abs_diff(preferred_size), u32::MAX - size)
+    })?;
+
+    let mut image = RgbaImage::new(width, height);
+    for (pixel, argb) in image.pixels_mut().zip(pixels) {
+        let [a, r, g, b] = argb.to_be_bytes();
+        *pixel = Rgba([r, g, b, a]);
+    }
+
+    Some(image)
+}
+
+fn get_net_wm_icon(window: &Window, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+    let Ok(net_wm_icon_atom) = get_atom("_NET_WM_ICON") else {
+        return Ok(None);
+    };
+
+    let reply = get_window_property(*window, net_wm_icon_atom, ATOM_CARDINAL, 0, u32::MAX / 4)?;
+
+    Ok(parse_net_wm_icon(reply.value::<u32>(), preferred_size))
+}
+
+/// Legacy icon pixmap of `WM_HINTS`, the icon mask becomes the alpha channel.
+fn get_wm_hints_icon(window: &Window) -> XCapResult<Option<RgbaImage>> {
+    let reply = get_window_property(*window, ATOM_WM_HINTS, ATOM_WM_HINTS, 0, 9)?;
+    let wm_hints = reply.value::<u32>();
+
+    let (Some(&flags), Some(&icon_pixmap)) = (wm_hints.first(), wm_hints.get(3)) else {
+        return Ok(None);
+    };
+    if flags & ICON_PIXMAP_HINT == 0 || icon_pixmap == 0 {
+        return Ok(None);
+    }
+
+    let icon_pixmap = Pixmap::new(icon_pixmap);
+
+    let (conn, _) = get_xcb_connection_and_index()?;
+    let get_geometry_cookie = conn.send_request(&GetGeometry {
+        drawable: Drawable::Pixmap(icon_pixmap),
+    });
+    let get_geometry_reply = conn.wait_for_reply(get_geometry_cookie)?;
+    let width = get_geometry_reply.width() as u32;
+    let height = get_geometry_reply.height() as u32;
+
+    let mut image = xorg_capture_pixmap(icon_pixmap, width, height)?;
+
+    let icon_mask = wm_hints
+        .get(7)
+        .filter(|&&icon_mask| flags & ICON_MASK_HINT != 0 && icon_mask != 0);
+
+    if let Some(&icon_mask) = icon_mask {
+        // 掩码尺寸和图标不一致时忽略掩码
+        if let Ok(mask) = xorg_get_bitmap(Pixmap::new(icon_mask), width, height) {
+            for (pixel, visible) in image.pixels_mut().zip(mask) {
+                pixel[3] = if visible { 255 } else { 0 };
+            }
+        }
+    }
+
+    Ok(Some(image))
+}
+
+/// `_NET_WM_ICON` closest to `preferred_size`, falling back to the `WM_HINTS` icon pixmap.
+pub fn get_window_icon(window: &Window, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+    if let Some(icon) = get_net_wm_icon(window, preferred_size)? {
+        return Ok(Some(icon));
+    }
+
+    get_wm_hints_icon(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_net_wm_icon() {
+        let mut data = vec![1, 1, 0x80FF0000];
+        data.extend([2, 2]);
+        data.extend([0xFF00FF00; 4]);
+        data.extend([4, 4]);
+        data.extend([0xFF0000FF; 16]);
+
+        let icon = parse_net_wm_icon(&data, 1).unwrap();
+        assert_eq!(icon.dimensions(), (1, 1));
+        assert_eq!(icon.get_pixel(0, 0), &Rgba([255, 0, 0, 128]));
+
+        // 2 和 4 与 3 距离相同，取较大的
+        let icon = parse_net_wm_icon(&data, 3).unwrap();
+        assert_eq!(icon.dimensions(), (4, 4));
+        assert_eq!(icon.get_pixel(3, 3), &Rgba([0, 0, 255, 255]));
+
+        let icon = parse_net_wm_icon(&data, 256).unwrap();
+        assert_eq!(icon.dimensions(), (4, 4));
+
+        // 截断的数据只保留完整的图标
+        assert_eq!(
+            parse_net_wm_icon(&data[..10], 64).unwrap().dimensions(),
+            (2, 2)
+        );
+        assert!(parse_net_wm_icon(&[16, 16, 0], 16).is_none());
+    }
+}
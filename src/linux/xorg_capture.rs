@@ -1,10 +1,15 @@
-use image::RgbaImage;
+use image::{Rgba, RgbaImage};
 use xcb::{
     Connection,
-    x::{Drawable, GetImage, ImageFormat, ImageOrder, Window},
+    x::{Drawable, GetImage, GetImageReply, ImageFormat, ImageOrder, Pixmap, Window},
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    error::{XCapError, XCapResult},
+    pixel_format::{PixelFormat, check_buffer, write_pixel},
+    thumbnail::{box_downscale, thumbnail_size},
+    video_recorder::Frame,
+};
 
 fn get_pixel8_rgba(
     bytes: &[u8],
@@ -69,20 +74,42 @@ fn get_pixel24_32_rgba(
     }
 }
 
-pub fn xorg_capture(
-    window: Window,
+type GetPixelRgba = fn(&[u8], u32, u32, u32, u32, ImageOrder) -> (u8, u8, u8, u8);
+
+/// Pixels of a window area as returned by the X server.
+struct XorgImage {
+    reply: GetImageReply,
+    bits_per_pixel: u32,
+    scanline_pad: u32,
+    bit_order: ImageOrder,
+}
+
+impl XorgImage {
+    fn get_pixel_rgba(&self) -> XCapResult<GetPixelRgba> {
+        match self.reply.depth() {
+            8 => Ok(get_pixel8_rgba),
+            16 => Ok(get_pixel16_rgba),
+            24 => Ok(get_pixel24_32_rgba),
+            32 => Ok(get_pixel24_32_rgba),
+            depth => Err(XCapError::new(format!("Unsupported {depth} depth"))),
+        }
+    }
+}
+
+fn xorg_get_image(
+    drawable: Drawable,
     x: i32,
     y: i32,
     width: u32,
     height: u32,
-) -> XCapResult<RgbaImage> {
+) -> XCapResult<XorgImage> {
     let (conn, _) = Connection::connect(None)?;
 
     let setup = conn.get_setup();
 
     let get_image_cookie = conn.send_request(&GetImage {
         format: ImageFormat::ZPixmap,
-        drawable: Drawable::Window(window),
+        drawable,
         x: x as i16,
         y: y as i16,
         width: width as u16,
@@ -90,9 +117,8 @@ pub fn xorg_capture(
         plane_mask: u32::MAX,
     });
 
-    let get_image_reply = conn.wait_for_reply(get_image_cookie)?;
-    let bytes = get_image_reply.data();
-    let depth = get_image_reply.depth();
+    let reply = conn.wait_for_reply(get_image_cookie)?;
+    let depth = reply.depth();
 
     let pixmap_format = setup
         .pixmap_formats()
@@ -100,30 +126,215 @@ pub fn xorg_capture(
         .find(|item| item.depth() == depth)
         .ok_or(XCapError::new("Not found pixmap format"))?;
 
-    let bits_per_pixel = pixmap_format.bits_per_pixel() as u32;
-    let bit_order = setup.bitmap_format_bit_order();
+    Ok(XorgImage {
+        bits_per_pixel: pixmap_format.bits_per_pixel() as u32,
+        scanline_pad: pixmap_format.scanline_pad() as u32,
+        bit_order: setup.bitmap_format_bit_order(),
+        reply,
+    })
+}
 
-    let get_pixel_rgba = match depth {
-        8 => get_pixel8_rgba,
-        16 => get_pixel16_rgba,
-        24 => get_pixel24_32_rgba,
-        32 => get_pixel24_32_rgba,
-        _ => return Err(XCapError::new(format!("Unsupported {depth} depth"))),
-    };
+#[allow(clippy::too_many_arguments)]
+pub fn xorg_capture_into(
+    window: Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<()> {
+    check_buffer(width, height, format, buffer.len(), stride)?;
+
+    let xorg_image = xorg_get_image(Drawable::Window(window), x, y, width, height)?;
+    let bytes = xorg_image.reply.data();
+    let depth = xorg_image.reply.depth();
+
+    let bits_per_pixel = xorg_image.bits_per_pixel;
+    let bit_order = xorg_image.bit_order;
+    let bytes_per_pixel = format.bytes_per_pixel();
+    let row_len = width as usize * bytes_per_pixel;
+
+    // 24/32 位深度下 X11 原生就是 BGRX，只需补齐 alpha
+    if format == PixelFormat::Bgra
+        && (depth == 24 || depth == 32)
+        && bits_per_pixel == 32
+        && bit_order == ImageOrder::LsbFirst
+    {
+        let bytes = bytes
+            .get(..row_len * height as usize)
+            .ok_or_else(|| XCapError::new("GetImage reply is too short"))?;
+
+        for (src_row, dst_row) in bytes.chunks_exact(row_len).zip(buffer.chunks_mut(stride)) {
+            let dst_row = &mut dst_row[..row_len];
+            dst_row.copy_from_slice(src_row);
+            for pixel in dst_row.chunks_exact_mut(4) {
+                pixel[3] = 255;
+            }
+        }
+
+        return Ok(());
+    }
+
+    let get_pixel_rgba = xorg_image.get_pixel_rgba()?;
 
-    let mut rgba = vec![0u8; (width * height * 4) as usize];
     for y in 0..height {
+        let row = y as usize * stride;
         for x in 0..width {
-            let index = ((y * width + x) * 4) as usize;
+            let index = row + x as usize * bytes_per_pixel;
             let (r, g, b, a) = get_pixel_rgba(bytes, x, y, width, bits_per_pixel, bit_order);
 
-            rgba[index] = r;
-            rgba[index + 1] = g;
-            rgba[index + 2] = b;
-            rgba[index + 3] = a;
+            write_pixel(
+                &mut buffer[index..index + bytes_per_pixel],
+                format,
+                [r, g, b, a],
+            );
         }
     }
 
-    RgbaImage::from_raw(width, height, rgba)
+    Ok(())
+}
+
+pub fn xorg_capture_as(
+    window: Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+) -> XCapResult<Frame> {
+    let stride = width as usize * format.bytes_per_pixel();
+    let mut raw = vec![0u8; stride * height as usize];
+    xorg_capture_into(window, x, y, width, height, format, &mut raw, stride)?;
+
+    Ok(Frame::new_with_format(width, height, format, raw))
+}
+
+pub fn xorg_capture(
+    window: Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> XCapResult<RgbaImage> {
+    let frame = xorg_capture_as(window, x, y, width, height, PixelFormat::Rgba)?;
+
+    RgbaImage::from_raw(width, height, frame.raw)
         .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
 }
+
+/// Downscale while converting from the server's pixel format, the full size image is never built.
+pub fn xorg_capture_thumbnail(
+    window: Window,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    let xorg_image = xorg_get_image(Drawable::Window(window), x, y, width, height)?;
+    let get_pixel_rgba = xorg_image.get_pixel_rgba()?;
+    let bytes = xorg_image.reply.data();
+
+    let required =
+        (width as usize * height as usize * xorg_image.bits_per_pixel as usize).div_ceil(8);
+    if bytes.len() < required {
+        return Err(XCapError::new("GetImage reply is too short"));
+    }
+
+    let (dst_width, dst_height) = thumbnail_size(width, height, max_width, max_height);
+
+    Ok(box_downscale(
+        width,
+        height,
+        dst_width,
+        dst_height,
+        |x, y| {
+            let (r, g, b, a) = get_pixel_rgba(
+                bytes,
+                x,
+                y,
+                width,
+                xorg_image.bits_per_pixel,
+                xorg_image.bit_order,
+            );
+            [r, g, b, a]
+        },
+    ))
+}
+
+impl XorgImage {
+    /// Bits of a depth 1 image, rows are padded to the scanline pad.
+    fn bitmap_bits(&self, width: u32, height: u32) -> XCapResult<Vec<bool>> {
+        let bytes = self.reply.data();
+        let row_len = width.div_ceil(self.scanline_pad) as usize * self.scanline_pad as usize / 8;
+        if bytes.len() < row_len * height as usize {
+            return Err(XCapError::new("GetImage reply is too short"));
+        }
+
+        let bits = (0..height as usize)
+            .flat_map(|y| (0..width as usize).map(move |x| (y, x)))
+            .map(|(y, x)| {
+                let byte = bytes[y * row_len + x / 8];
+                let bit = if self.bit_order == ImageOrder::LsbFirst {
+                    x % 8
+                } else {
+                    7 - x % 8
+                };
+
+                byte & (1 << bit) != 0
+            })
+            .collect();
+
+        Ok(bits)
+    }
+}
+
+/// Read a depth 1 pixmap, e.g. an icon mask. `true` for set bits.
+pub fn xorg_get_bitmap(pixmap: Pixmap, width: u32, height: u32) -> XCapResult<Vec<bool>> {
+    let xorg_image = xorg_get_image(Drawable::Pixmap(pixmap), 0, 0, width, height)?;
+    if xorg_image.reply.depth() != 1 {
+        return Err(XCapError::new("Pixmap is not a bitmap"));
+    }
+
+    xorg_image.bitmap_bits(width, height)
+}
+
+/// Read a pixmap, e.g. the legacy `WM_HINTS` icon. Bitmaps are drawn black on white.
+pub fn xorg_capture_pixmap(pixmap: Pixmap, width: u32, height: u32) -> XCapResult<RgbaImage> {
+    let xorg_image = xorg_get_image(Drawable::Pixmap(pixmap), 0, 0, width, height)?;
+
+    if xorg_image.reply.depth() == 1 {
+        let raw = xorg_image
+            .bitmap_bits(width, height)?
+            .into_iter()
+            .flat_map(|bit| if bit { [0, 0, 0, 255] } else { [255; 4] })
+            .collect();
+
+        return RgbaImage::from_raw(width, height, raw)
+            .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"));
+    }
+
+    let get_pixel_rgba = xorg_image.get_pixel_rgba()?;
+    let bytes = xorg_image.reply.data();
+
+    let required =
+        (width as usize * height as usize * xorg_image.bits_per_pixel as usize).div_ceil(8);
+    if bytes.len() < required {
+        return Err(XCapError::new("GetImage reply is too short"));
+    }
+
+    Ok(RgbaImage::from_fn(width, height, |x, y| {
+        let (r, g, b, a) = get_pixel_rgba(
+            bytes,
+            x,
+            y,
+            width,
+            xorg_image.bits_per_pixel,
+            xorg_image.bit_order,
+        );
+        Rgba([r, g, b, a])
+    }))
+}
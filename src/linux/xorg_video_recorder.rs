@@ -1,6 +1,9 @@
-use super::impl_monitor::ImplMonitor;
+use super::{
+    capture::capture_monitor_into, impl_monitor::ImplMonitor, utils::get_monitor_info_buf,
+};
 use crate::error::{XCapError, XCapResult};
-use crate::video_recorder::{Frame, RecorderWaker};
+use crate::pixel_format::PixelFormat;
+use crate::video_recorder::{Frame, FramePool, RecorderWaker};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -9,16 +12,24 @@ use std::time::Duration;
 #[derive(Debug, Clone)]
 pub struct XorgVideoRecorder {
     monitor: ImplMonitor,
+    format: PixelFormat,
+    pool: FramePool,
     sender: Sender<Frame>,
     running: Arc<Mutex<bool>>,
     recorder_waker: Arc<RecorderWaker>,
 }
 
 impl XorgVideoRecorder {
-    pub fn new(monitor: ImplMonitor) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        monitor: ImplMonitor,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         let (sender, receiver) = mpsc::channel();
         let recorder = Self {
             monitor,
+            format,
+            pool,
             sender,
             running: Arc::new(Mutex::new(false)),
             recorder_waker: Arc::new(RecorderWaker::new()),
@@ -31,6 +42,8 @@ impl XorgVideoRecorder {
 
     pub fn on_frame(&self) -> XCapResult<()> {
         let monitor = self.monitor.clone();
+        let format = self.format;
+        let pool = self.pool.clone();
         let sender = self.sender.clone();
         let running_flag = self.running.clone();
         let recorder_waker = self.recorder_waker.clone();
@@ -54,13 +67,25 @@ impl XorgVideoRecorder {
                     break Ok(());
                 }
 
-                match monitor.capture_image() {
-                    Ok(image) => {
-                        let width = image.width();
-                        let height = image.height();
-                        let raw = image.into_raw();
+                let (width, height) = match get_monitor_info_buf(monitor.output) {
+                    Ok(monitor_info_buf) => (
+                        monitor_info_buf.width() as usize,
+                        monitor_info_buf.height() as usize,
+                    ),
+                    Err(e) => {
+                        log::error!("Failed to get monitor info: {e:?}");
+                        thread::sleep(Duration::from_millis(10));
+                        continue;
+                    }
+                };
+
+                // 复用消费者归还的缓冲区，稳定录制时不再分配内存
+                let stride = width * format.bytes_per_pixel();
+                let mut raw = pool.take(stride * height);
 
-                        let frame = Frame::new(width, height, raw);
+                match capture_monitor_into(&monitor, format, &mut raw, stride) {
+                    Ok((width, height)) => {
+                        let frame = Frame::new_with_format(width, height, format, raw);
                         if let Err(e) = sender.send(frame) {
                             log::error!("Failed to send frame: {e:?}");
                             break Err(XCapError::new(format!("Failed to send frame: {e}")));
@@ -68,6 +93,7 @@ impl XorgVideoRecorder {
                     }
                     Err(e) => {
                         log::error!("Failed to capture frame: {e:?}");
+                        pool.put(raw);
                         thread::sleep(Duration::from_millis(10));
                         continue;
                     }
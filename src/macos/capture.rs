@@ -5,43 +5,149 @@ use objc2_core_graphics::{
     CGWindowListOption,
 };
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    error::{XCapError, XCapResult},
+    pixel_format::{PixelFormat, convert_rows_into, read_pixel},
+    thumbnail::{box_downscale, thumbnail_size},
+    video_recorder::Frame,
+};
 
-pub fn capture(
+/// Capture and hand the BGRA pixels to `read` as `(data, bytes_per_row, width, height)`.
+fn capture_with<T>(
     cg_rect: CGRect,
     list_option: CGWindowListOption,
     window_id: CGWindowID,
-) -> XCapResult<RgbaImage> {
-    let cg_image = CGWindowListCreateImage(
-        cg_rect,
-        list_option,
-        window_id,
-        CGWindowImageOption::Default,
-    );
+    image_option: CGWindowImageOption,
+    read: impl FnOnce(&[u8], usize, u32, u32) -> XCapResult<T>,
+) -> XCapResult<T> {
+    let cg_image = CGWindowListCreateImage(cg_rect, list_option, window_id, image_option);
 
     let width = CGImage::width(cg_image.as_deref());
     let height = CGImage::height(cg_image.as_deref());
     let data_provider = CGImage::data_provider(cg_image.as_deref());
 
     let data = CGDataProvider::data(data_provider.as_deref())
-        .ok_or_else(|| XCapError::new("Failed to copy data"))?
-        .to_vec();
-
-    let bytes_per_row = CGImage::bytes_per_row(cg_image.as_deref());
+        .ok_or_else(|| XCapError::new("Failed to copy data"))?;
 
     // Some platforms e.g. MacOS can have extra bytes at the end of each row.
     // See
     // https://github.com/nashaofu/xcap/issues/29
     // https://github.com/nashaofu/xcap/issues/38
-    let mut buffer = Vec::with_capacity(width * height * 4);
-    for row in data.chunks_exact(bytes_per_row) {
-        buffer.extend_from_slice(&row[..width * 4]);
-    }
+    let bytes_per_row = CGImage::bytes_per_row(cg_image.as_deref());
 
-    for bgra in buffer.chunks_exact_mut(4) {
-        bgra.swap(0, 2);
-    }
+    // data 由当前函数持有，读取期间不会被修改
+    let bytes = unsafe { data.as_bytes_unchecked() };
 
-    RgbaImage::from_raw(width as u32, height as u32, buffer)
+    read(bytes, bytes_per_row, width as u32, height as u32)
+}
+
+pub fn capture(
+    cg_rect: CGRect,
+    list_option: CGWindowListOption,
+    window_id: CGWindowID,
+) -> XCapResult<RgbaImage> {
+    let frame = capture_as(cg_rect, list_option, window_id, PixelFormat::Rgba)?;
+
+    RgbaImage::from_raw(frame.width, frame.height, frame.raw)
         .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
 }
+
+/// Capture in `format`, the native BGRA rows are converted once.
+pub fn capture_as(
+    cg_rect: CGRect,
+    list_option: CGWindowListOption,
+    window_id: CGWindowID,
+    format: PixelFormat,
+) -> XCapResult<Frame> {
+    capture_with(
+        cg_rect,
+        list_option,
+        window_id,
+        CGWindowImageOption::Default,
+        |data, bytes_per_row, width, height| {
+            let stride = width as usize * format.bytes_per_pixel();
+            let mut raw = vec![0u8; stride * height as usize];
+            convert_rows_into(
+                data,
+                bytes_per_row,
+                PixelFormat::Bgra,
+                width,
+                height,
+                &mut raw,
+                stride,
+                format,
+            )?;
+
+            Ok(Frame::new_with_format(width, height, format, raw))
+        },
+    )
+}
+
+/// Capture in `format` straight into `buffer`, rows are `stride` bytes apart.
+pub fn capture_into(
+    cg_rect: CGRect,
+    list_option: CGWindowListOption,
+    window_id: CGWindowID,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    capture_with(
+        cg_rect,
+        list_option,
+        window_id,
+        CGWindowImageOption::Default,
+        |data, bytes_per_row, width, height| {
+            convert_rows_into(
+                data,
+                bytes_per_row,
+                PixelFormat::Bgra,
+                width,
+                height,
+                buffer,
+                stride,
+                format,
+            )?;
+
+            Ok((width, height))
+        },
+    )
+}
+
+/// Capture a thumbnail that fits in `max_width` x `max_height`.
+///
+/// The image is requested at nominal resolution, a quarter of the pixels on Retina displays,
+/// and the BGRA rows are downscaled while they are converted.
+pub fn capture_thumbnail(
+    cg_rect: CGRect,
+    list_option: CGWindowListOption,
+    window_id: CGWindowID,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    capture_with(
+        cg_rect,
+        list_option,
+        window_id,
+        CGWindowImageOption::NominalResolution,
+        |data, bytes_per_row, width, height| {
+            let (dst_width, dst_height) = thumbnail_size(width, height, max_width, max_height);
+
+            let row_len = width as usize * 4;
+            if data.len() < bytes_per_row * height.saturating_sub(1) as usize + row_len {
+                return Err(XCapError::new("Captured image is smaller than expected"));
+            }
+
+            Ok(box_downscale(
+                width,
+                height,
+                dst_width,
+                dst_height,
+                |x, y| {
+                    let index = y as usize * bytes_per_row + x as usize * 4;
+                    read_pixel(&data[index..index + 4], PixelFormat::Bgra)
+                },
+            ))
+        },
+    )
+}
@@ -5,18 +5,26 @@ use objc2::MainThreadMarker;
 use objc2_app_kit::NSScreen;
 use objc2_core_foundation::CGPoint;
 use objc2_core_graphics::{
-    CGDirectDisplayID, CGDisplayBounds, CGDisplayCopyDisplayMode, CGDisplayIsActive,
-    CGDisplayIsBuiltin, CGDisplayIsMain, CGDisplayMode, CGDisplayModelNumber, CGDisplayRotation,
-    CGError, CGGetActiveDisplayList, CGGetDisplaysWithPoint, CGWindowListOption,
+    CGColorSpace, CGDirectDisplayID, CGDisplayBounds, CGDisplayCopyColorSpace,
+    CGDisplayCopyDisplayMode, CGDisplayIsActive, CGDisplayIsBuiltin, CGDisplayIsMain,
+    CGDisplayMode, CGDisplayModelNumber, CGDisplayRotation, CGError, CGGetActiveDisplayList,
+    CGGetDisplaysWithPoint, CGWindowListOption,
 };
 use objc2_foundation::{NSNumber, NSString};
 
 use crate::{
+    capture_options::scale_size,
+    edid::Edid,
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    pixel_format::PixelFormat,
+    transform::Transform,
+    video_recorder::{Frame, FramePool, VideoRecorderOptions},
 };
 
-use super::{capture::capture, impl_video_recorder::ImplVideoRecorder};
+use super::{
+    capture::{capture, capture_as, capture_into, capture_thumbnail},
+    impl_video_recorder::ImplVideoRecorder,
+};
 
 #[derive(Debug, Clone)]
 pub(crate) struct ImplMonitor {
@@ -143,6 +151,10 @@ impl ImplMonitor {
         Ok(name)
     }
 
+    pub fn edid(&self) -> XCapResult<Edid> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let rect = CGDisplayBounds(self.cg_direct_display_id);
 
@@ -167,12 +179,46 @@ impl ImplMonitor {
         Ok(cg_rect.size.height as u32)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        let scale_factor = self.scale_factor()?;
+
+        Ok(scale_size(self.logical_size()?, scale_factor))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
+    /// Bounds of the monitor in the coordinate space of window geometry.
+    pub fn window_space_bounds(&self) -> XCapResult<(i32, i32, u32, u32)> {
+        Ok((self.x()?, self.y()?, self.width()?, self.height()?))
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         let rotation = CGDisplayRotation(self.cg_direct_display_id);
 
         Ok(rotation as f32)
     }
 
+    pub fn transform(&self) -> XCapResult<Transform> {
+        // Quartz 只提供旋转，系统也没有镜像显示器画面的设置，CGDisplayIsInMirrorSet 表示的是多个显示器
+        // 显示相同内容，与画面翻转无关。IOFramebuffer 的 IOFBTransform 也只是用翻转组合出旋转，
+        // 并且在 Apple Silicon 上不可用，所以这里的翻转总是 false
+        let rotation = (self.rotation()?.round() as i32).rem_euclid(360) as u32;
+
+        Ok(Transform::new(rotation, false, false))
+    }
+
+    pub fn icc_profile(&self) -> XCapResult<Option<Vec<u8>>> {
+        let color_space = CGDisplayCopyColorSpace(self.cg_direct_display_id);
+        // 不是基于 ICC profile 的色彩空间没有 ICC 数据
+        let icc_data = CGColorSpace::icc_data(Some(&color_space));
+
+        Ok(icc_data
+            .map(|icc_data| icc_data.to_vec())
+            .filter(|icc_profile| !icc_profile.is_empty()))
+    }
+
     pub fn scale_factor(&self) -> XCapResult<f32> {
         let display_mode = CGDisplayCopyDisplayMode(self.cg_direct_display_id);
         let pixel_width = CGDisplayMode::pixel_width(display_mode.as_deref());
@@ -208,6 +254,37 @@ impl ImplMonitor {
         capture(cg_rect, CGWindowListOption::OptionAll, 0)
     }
 
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        let cg_rect = CGDisplayBounds(self.cg_direct_display_id);
+
+        capture_as(cg_rect, CGWindowListOption::OptionAll, 0, format)
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        let cg_rect = CGDisplayBounds(self.cg_direct_display_id);
+
+        capture_into(
+            cg_rect,
+            CGWindowListOption::OptionAll,
+            0,
+            PixelFormat::Rgba,
+            buffer,
+            stride,
+        )
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        let cg_rect = CGDisplayBounds(self.cg_direct_display_id);
+
+        capture_thumbnail(
+            cg_rect,
+            CGWindowListOption::OptionAll,
+            0,
+            max_width,
+            max_height,
+        )
+    }
+
     pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
         // Validate region bounds
         let monitor_x = self.x()?;
@@ -241,7 +318,11 @@ impl ImplMonitor {
         capture(cg_rect, CGWindowListOption::OptionAll, 0)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
-        ImplVideoRecorder::new(self.cg_direct_display_id)
+    pub fn video_recorder(
+        &self,
+        options: &VideoRecorderOptions,
+        pool: FramePool,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        ImplVideoRecorder::new(self.cg_direct_display_id, options.format, pool)
     }
 }
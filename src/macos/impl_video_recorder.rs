@@ -31,12 +31,18 @@ use objc2_core_video::{
 use objc2_foundation::{NSDictionary, NSNumber, NSObject, NSObjectProtocol, NSString};
 use scopeguard::defer;
 
-use crate::{XCapError, XCapResult, video_recorder::Frame};
+use crate::{
+    XCapError, XCapResult,
+    pixel_format::{PixelFormat, convert_pixels_into, convert_rows_into},
+    video_recorder::{Frame, FramePool},
+};
 
 #[derive(Debug, Clone)]
 struct DataOutputSampleBufferDelegateVars {
     tx: SyncSender<Frame>,
     running: Arc<AtomicBool>,
+    format: PixelFormat,
+    pool: FramePool,
 }
 
 impl DataOutputSampleBufferDelegateVars {
@@ -109,27 +115,6 @@ impl DataOutputSampleBufferDelegateVars {
         )
     }
 
-    fn copy_packed_rows(width: usize, height: usize, bytes_per_row: usize, data: &[u8]) -> Vec<u8> {
-        let row_len = width * 4;
-
-        // When there is no row padding, a single contiguous copy is faster than row-by-row copy.
-        if bytes_per_row == row_len {
-            return data[..row_len * height].to_vec();
-        }
-
-        let mut buffer = vec![0; row_len * height];
-
-        for row_index in 0..height {
-            let src_row_start = row_index * bytes_per_row;
-            let dst_row_start = row_index * row_len;
-            let src_row = &data[src_row_start..src_row_start + row_len];
-            let dst_row = &mut buffer[dst_row_start..dst_row_start + row_len];
-            dst_row.copy_from_slice(src_row);
-        }
-
-        buffer
-    }
-
     fn yuv422_to_rgba(width: usize, height: usize, bytes_per_row: usize, data: &[u8]) -> Vec<u8> {
         let mut buffer = vec![0; width * height * 4];
         let src_row_len = width * 2;
@@ -198,27 +183,6 @@ impl DataOutputSampleBufferDelegateVars {
         buffer
     }
 
-    fn bgra_to_rgba(width: usize, height: usize, bytes_per_row: usize, data: &[u8]) -> Vec<u8> {
-        let row_len = width * 4;
-        let mut buffer = vec![0; row_len * height];
-
-        for row_index in 0..height {
-            let src_row_start = row_index * bytes_per_row;
-            let dst_row_start = row_index * row_len;
-            let src_row = &data[src_row_start..src_row_start + row_len];
-            let dst_row = &mut buffer[dst_row_start..dst_row_start + row_len];
-
-            for (src, dst) in src_row.chunks_exact(4).zip(dst_row.chunks_exact_mut(4)) {
-                dst[0] = src[2];
-                dst[1] = src[1];
-                dst[2] = src[0];
-                dst[3] = src[3];
-            }
-        }
-
-        buffer
-    }
-
     fn argb_to_rgba(width: usize, height: usize, bytes_per_row: usize, data: &[u8]) -> Vec<u8> {
         let row_len = width * 4;
         let mut buffer = vec![0; row_len * height];
@@ -370,11 +334,37 @@ impl DataOutputSampleBufferDelegateVars {
 
                 let data = slice::from_raw_parts(base_address.cast::<u8>(), size);
 
-                if format_type == kCVPixelFormatType_32RGBA {
-                    Self::copy_packed_rows(width, height, bytes_per_row, data)
+                // RGBA 与 BGRA 直接转换为请求的格式，写入池中复用的缓冲区
+                let source_format = if format_type == kCVPixelFormatType_32RGBA {
+                    Some(PixelFormat::Rgba)
                 } else if format_type == kCVPixelFormatType_32BGRA {
-                    Self::bgra_to_rgba(width, height, bytes_per_row, data)
-                } else if format_type == kCVPixelFormatType_32ARGB {
+                    Some(PixelFormat::Bgra)
+                } else {
+                    None
+                };
+
+                if let Some(source_format) = source_format {
+                    let row_len = width * self.format.bytes_per_pixel();
+                    let mut buffer = self.pool.take(row_len * height);
+                    if let Err(err) = convert_rows_into(
+                        data,
+                        bytes_per_row,
+                        source_format,
+                        width as u32,
+                        height as u32,
+                        &mut buffer,
+                        row_len,
+                        self.format,
+                    ) {
+                        log::error!("Failed to convert pixels: {err:?}");
+                        return;
+                    }
+
+                    self.send(width, height, buffer);
+                    return;
+                }
+
+                if format_type == kCVPixelFormatType_32ARGB {
                     Self::argb_to_rgba(width, height, bytes_per_row, data)
                 } else if format_type == kCVPixelFormatType_422YpCbCr8 {
                     Self::yuv422_to_rgba(width, height, bytes_per_row, data)
@@ -385,18 +375,35 @@ impl DataOutputSampleBufferDelegateVars {
                 }
             };
 
-            // After stop, there may still be callbacks in the queue converting pixels; check again before sending.
-            if !self.running.load(Ordering::Acquire) {
-                return;
-            }
+            // 其他格式先转换为 RGBA
+            let buffer = if self.format == PixelFormat::Rgba {
+                buffer
+            } else {
+                let mut converted = self
+                    .pool
+                    .take(width * height * self.format.bytes_per_pixel());
+                convert_pixels_into(&buffer, PixelFormat::Rgba, &mut converted, self.format);
+                converted
+            };
 
-            let _ = self.tx.send(Frame {
-                width: width as u32,
-                height: height as u32,
-                raw: buffer,
-            });
+            self.send(width, height, buffer);
         }
     }
+
+    fn send(&self, width: usize, height: usize, buffer: Vec<u8>) {
+        // After stop, there may still be callbacks in the queue converting pixels; check again before sending.
+        if !self.running.load(Ordering::Acquire) {
+            self.pool.put(buffer);
+            return;
+        }
+
+        let _ = self.tx.send(Frame::new_with_format(
+            width as u32,
+            height as u32,
+            self.format,
+            buffer,
+        ));
+    }
 }
 
 define_class!(
@@ -422,8 +429,18 @@ define_class!(
 unsafe impl NSObjectProtocol for DataOutputSampleBufferDelegate {}
 
 impl DataOutputSampleBufferDelegate {
-    fn new(tx: SyncSender<Frame>, running: Arc<AtomicBool>) -> Retained<Self> {
-        let this = Self::alloc().set_ivars(DataOutputSampleBufferDelegateVars { tx, running });
+    fn new(
+        tx: SyncSender<Frame>,
+        running: Arc<AtomicBool>,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(DataOutputSampleBufferDelegateVars {
+            tx,
+            running,
+            format,
+            pool,
+        });
         unsafe { msg_send![super(this), init] }
     }
 }
@@ -437,8 +454,17 @@ pub struct ImplVideoRecorder {
     running: Arc<AtomicBool>,
 }
 
+// AVCaptureSession 的 startRunning/stopRunning 可以在任意线程调用，
+// input、output 和 delegate 只用于保持引用，创建后不再访问
+unsafe impl Send for ImplVideoRecorder {}
+unsafe impl Sync for ImplVideoRecorder {}
+
 impl ImplVideoRecorder {
-    pub fn new(cg_direct_display_id: CGDirectDisplayID) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        cg_direct_display_id: CGDirectDisplayID,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         unsafe {
             let session = AVCaptureSession::new();
             let input = AVCaptureScreenInput::initWithDisplayID(
@@ -464,9 +490,15 @@ impl ImplVideoRecorder {
             let format_type_key =
                 NSString::from_str(kCVPixelBufferPixelFormatTypeKey.to_string().as_str());
             let available_format_types = output.availableVideoCVPixelFormatTypes();
+            // 优先使用与请求格式相同的像素格式，避免转换
+            let (first_format_type, second_format_type) = if format == PixelFormat::Rgba {
+                (kCVPixelFormatType_32RGBA, kCVPixelFormatType_32BGRA)
+            } else {
+                (kCVPixelFormatType_32BGRA, kCVPixelFormatType_32RGBA)
+            };
             let preferred_format_types = [
-                kCVPixelFormatType_32RGBA,
-                kCVPixelFormatType_32BGRA,
+                first_format_type,
+                second_format_type,
                 kCVPixelFormatType_32ARGB,
                 kCVPixelFormatType_422YpCbCr8,
                 kCVPixelFormatType_422YpCbCr8_yuvs,
@@ -506,7 +538,8 @@ impl ImplVideoRecorder {
             let (tx, rx) = sync_channel(0);
             let running = Arc::new(AtomicBool::new(false));
 
-            let delegate = DataOutputSampleBufferDelegate::new(tx.clone(), running.clone());
+            let delegate =
+                DataOutputSampleBufferDelegate::new(tx.clone(), running.clone(), format, pool);
 
             let sample_buffer_delegate = ProtocolObject::<
                 dyn AVCaptureVideoDataOutputSampleBufferDelegate,
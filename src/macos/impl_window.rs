@@ -1,20 +1,31 @@
-use std::ffi::c_void;
+use std::{
+    ffi::c_void,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
 
 use image::RgbaImage;
-use objc2_app_kit::NSWorkspace;
+use objc2_app_kit::{NSRunningApplication, NSWorkspace};
 use objc2_core_foundation::{
-    CFBoolean, CFDictionary, CFNumber, CFNumberType, CFRetained, CFString, CGPoint, CGRect,
+    CFBoolean, CFDictionary, CFNumber, CFNumberType, CFRetained, CFString, CGPoint, CGRect, CGSize,
 };
 use objc2_core_graphics::{
-    CGDisplayBounds, CGMainDisplayID, CGPreflightScreenCaptureAccess, CGRectContainsPoint,
+    CGBitmapContextCreate, CGColorSpace, CGContext, CGDisplayBounds, CGImageAlphaInfo,
+    CGImageByteOrderInfo, CGMainDisplayID, CGPreflightScreenCaptureAccess, CGRectContainsPoint,
     CGRectIntersectsRect, CGRectMakeWithDictionaryRepresentation, CGWindowListCopyWindowInfo,
     CGWindowListOption,
 };
 use objc2_foundation::{NSNumber, NSString};
 
-use crate::{XCapError, error::XCapResult};
+use crate::{
+    XCapError, capture_options::scale_size, error::XCapResult, pixel_format::PixelFormat,
+    process_info::ProcessInfo, video_recorder::Frame, window_type::WindowType,
+};
 
-use super::{capture::capture, impl_monitor::ImplMonitor};
+use super::{
+    capture::{capture, capture_as, capture_into, capture_thumbnail},
+    impl_monitor::ImplMonitor,
+};
 
 #[derive(Debug, Clone)]
 pub(crate) struct ImplWindow {
@@ -63,6 +74,27 @@ fn get_cf_number_i32_value(cf_dictionary: &CFDictionary, key: &str) -> XCapResul
     }
 }
 
+fn get_cf_number_f64_value(cf_dictionary: &CFDictionary, key: &str) -> XCapResult<f64> {
+    unsafe {
+        let cf_number = get_cf_dictionary_get_value(cf_dictionary, key)? as *const CFNumber;
+
+        let mut value: f64 = 0.0;
+        let is_success = (*cf_number).value(
+            CFNumberType::DoubleType,
+            &mut value as *mut _ as *mut c_void,
+        );
+
+        if !is_success {
+            return Err(XCapError::new(format!(
+                "Get {} CFNumberGetValue failed",
+                key
+            )));
+        }
+
+        Ok(value)
+    }
+}
+
 fn get_cf_string_value(cf_dictionary: &CFDictionary, key: &str) -> XCapResult<String> {
     let value_ref = get_cf_dictionary_get_value(cf_dictionary, key)? as *const CFString;
     let value = unsafe { (*value_ref).to_string() };
@@ -153,6 +185,95 @@ pub fn get_window_cf_dictionary(window_id: u32) -> XCapResult<CFRetained<CFDicti
     }
 }
 
+fn get_process_info(pid: u32) -> XCapResult<ProcessInfo> {
+    let app = NSRunningApplication::runningApplicationWithProcessIdentifier(pid as i32)
+        .ok_or_else(|| XCapError::new(format!("Process {pid} not found")))?;
+
+    let exe_path = app
+        .executableURL()
+        .and_then(|url| url.path())
+        .map(|path| PathBuf::from(path.to_string()));
+
+    let name = exe_path
+        .as_ref()
+        .and_then(|exe_path| exe_path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // 不是通过 LaunchServices 启动的进程没有 launchDate
+    let start_time = app.launchDate().and_then(|launch_date| {
+        Duration::try_from_secs_f64(launch_date.timeIntervalSince1970())
+            .ok()
+            .map(|duration| UNIX_EPOCH + duration)
+    });
+
+    Ok(ProcessInfo {
+        pid,
+        name,
+        exe_path,
+        // 读取其他进程的参数需要 sysctl KERN_PROCARGS2
+        cmdline: Vec::new(),
+        start_time,
+    })
+}
+
+/// Icon of the application owning the window, macOS windows have no icons of their own.
+fn get_app_icon(pid: u32, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+    let Some(app) = NSRunningApplication::runningApplicationWithProcessIdentifier(pid as i32)
+    else {
+        return Ok(None);
+    };
+    let Some(ns_image) = app.icon() else {
+        return Ok(None);
+    };
+
+    let size = preferred_size.max(1) as usize;
+    let rect = CGRect::new(
+        CGPoint::new(0.0, 0.0),
+        CGSize::new(size as f64, size as f64),
+    );
+
+    // NSImage 会按目标尺寸挑选最合适的图标表示
+    let mut proposed_rect = rect;
+    let Some(cg_image) =
+        (unsafe { ns_image.CGImageForProposedRect_context_hints(&mut proposed_rect, None, None) })
+    else {
+        return Ok(None);
+    };
+
+    let mut buffer = vec![0u8; size * size * 4];
+    let color_space = CGColorSpace::new_device_rgb();
+    let context = unsafe {
+        CGBitmapContextCreate(
+            buffer.as_mut_ptr().cast(),
+            size,
+            size,
+            8,
+            size * 4,
+            color_space.as_deref(),
+            CGImageByteOrderInfo::Order32Big.0 | CGImageAlphaInfo::PremultipliedLast.0,
+        )
+    }
+    .ok_or_else(|| XCapError::new("CGBitmapContextCreate failed"))?;
+
+    CGContext::draw_image(Some(&*context), rect, Some(&*cg_image));
+    drop(context);
+
+    // 位图上下文只支持预乘的 alpha，需要还原
+    for pixel in buffer.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha != 0 && alpha != 255 {
+            for channel in &mut pixel[..3] {
+                *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+    }
+
+    RgbaImage::from_raw(size as u32, size as u32, buffer)
+        .map(Some)
+        .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
+}
+
 impl ImplWindow {
     pub fn new(window_id: u32) -> ImplWindow {
         ImplWindow { window_id }
@@ -201,6 +322,24 @@ impl ImplWindow {
             Ok(impl_window)
         }
     }
+
+    pub fn from_point(x: i32, y: i32) -> XCapResult<ImplWindow> {
+        // all 返回的窗口从顶层到底层排列，第一个包含该点的可见窗口即为结果
+        for impl_window in ImplWindow::all()? {
+            let contains_point = (|| -> XCapResult<bool> {
+                let (left, top) = (impl_window.x()?, impl_window.y()?);
+                let (width, height) = (impl_window.width()?, impl_window.height()?);
+
+                Ok(x >= left && x < left + width as i32 && y >= top && y < top + height as i32)
+            })();
+
+            if contains_point.unwrap_or(false) && impl_window.is_viewable().unwrap_or(false) {
+                return Ok(impl_window);
+            }
+        }
+
+        Err(XCapError::new("Not found window"))
+    }
 }
 
 impl ImplWindow {
@@ -216,6 +355,10 @@ impl ImplWindow {
         Ok(pid as u32)
     }
 
+    pub fn process(&self) -> XCapResult<ProcessInfo> {
+        get_process_info(self.pid()?)
+    }
+
     pub fn app_name(&self) -> XCapResult<String> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
@@ -326,6 +469,16 @@ impl ImplWindow {
         Ok(cg_rect.size.height as u32)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        let scale_factor = self.current_monitor()?.scale_factor()?;
+
+        Ok(scale_size(self.logical_size()?, scale_factor))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
         let is_on_screen = get_cf_bool_value(window_cf_dictionary.as_ref(), "kCGWindowIsOnscreen")?;
@@ -334,6 +487,13 @@ impl ImplWindow {
         Ok(!is_on_screen && !is_maximized)
     }
 
+    /// 在屏幕上显示，最小化、隐藏或位于其他桌面的窗口都不在屏幕上
+    pub fn is_viewable(&self) -> XCapResult<bool> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        get_cf_bool_value(window_cf_dictionary.as_ref(), "kCGWindowIsOnscreen")
+    }
+
     pub fn is_maximized(&self) -> XCapResult<bool> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
@@ -372,6 +532,74 @@ impl ImplWindow {
         Ok(false)
     }
 
+    fn layer(&self) -> XCapResult<i32> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        get_cf_number_i32_value(window_cf_dictionary.as_ref(), "kCGWindowLayer")
+    }
+
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        // 根据 CGWindowLevelKey 对应的窗口层级推断窗口类型
+        let window_type = match self.layer()? {
+            layer if layer < 0 => WindowType::Desktop,
+            3 | 19 => WindowType::Utility,
+            8 => WindowType::Dialog,
+            20 | 24 | 25 => WindowType::Dock,
+            101 => WindowType::PopupMenu,
+            200 => WindowType::Tooltip,
+            500 => WindowType::Dnd,
+            _ => WindowType::Normal,
+        };
+
+        Ok(window_type)
+    }
+
+    pub fn is_fullscreen(&self) -> XCapResult<bool> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+        let impl_monitor = self.current_monitor()?;
+
+        Ok(cg_rect.origin.x as i32 <= impl_monitor.x()?
+            && cg_rect.origin.y as i32 <= impl_monitor.y()?
+            && cg_rect.size.width as u32 >= impl_monitor.width()?
+            && cg_rect.size.height as u32 >= impl_monitor.height()?)
+    }
+
+    pub fn is_always_on_top(&self) -> XCapResult<bool> {
+        Ok(self.layer()? > 0)
+    }
+
+    pub fn is_sticky(&self) -> XCapResult<bool> {
+        // 其他进程窗口的 collectionBehavior 无法读取
+        Ok(false)
+    }
+
+    pub fn is_skip_taskbar(&self) -> XCapResult<bool> {
+        // 只有普通层级的窗口会出现在 Dock 和调度中心
+        Ok(self.layer()? != 0)
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+        let alpha = get_cf_number_f64_value(window_cf_dictionary.as_ref(), "kCGWindowAlpha")?;
+
+        Ok(alpha as f32)
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        // CGWindowList 不包含窗口之间的从属关系
+        Ok(None)
+    }
+
+    pub fn desktop(&self) -> XCapResult<Option<u32>> {
+        // Spaces 没有公开的 API
+        Ok(None)
+    }
+
+    pub fn icon(&self, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        get_app_icon(self.pid()?, preferred_size)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
 
@@ -383,4 +611,46 @@ impl ImplWindow {
             self.window_id,
         )
     }
+
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+
+        capture_as(
+            cg_rect,
+            CGWindowListOption::OptionIncludingWindow,
+            self.window_id,
+            format,
+        )
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+
+        capture_into(
+            cg_rect,
+            CGWindowListOption::OptionIncludingWindow,
+            self.window_id,
+            PixelFormat::Rgba,
+            buffer,
+            stride,
+        )
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        let window_cf_dictionary = get_window_cf_dictionary(self.window_id)?;
+
+        let cg_rect = get_window_cg_rect(window_cf_dictionary.as_ref())?;
+
+        capture_thumbnail(
+            cg_rect,
+            CGWindowListOption::OptionIncludingWindow,
+            self.window_id,
+            max_width,
+            max_height,
+        )
+    }
 }
@@ -0,0 +1,6 @@
+use crate::{error::XCapResult, workspace::Workspace};
+
+pub fn get_workspaces() -> XCapResult<Vec<Workspace>> {
+    // Spaces 没有公开的 API
+    Ok(Vec::new())
+}
@@ -2,4 +2,6 @@ mod capture;
 
 pub mod impl_monitor;
 pub mod impl_video_recorder;
+pub mod impl_watcher;
 pub mod impl_window;
+pub mod impl_workspace;
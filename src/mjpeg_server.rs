@@ -0,0 +1,462 @@
+use std::{
+    io::{Cursor, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use image::RgbaImage;
+
+use crate::{
+    Monitor, VideoRecorder,
+    error::{XCapError, XCapResult},
+    image_file::{ImageFormat, SaveOptions, encode_image},
+    video_recorder::Frame,
+};
+
+const BOUNDARY: &str = "xcapframe";
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone)]
+pub struct MjpegServerOptions {
+    /// Address the HTTP server listens on.
+    pub address: SocketAddr,
+    /// Maximum number of frames per second sent to each client.
+    pub frame_rate: f32,
+    /// JPEG quality in the range 1..=100.
+    pub quality: u8,
+    /// Maximum number of simultaneous stream clients, and separately of snapshot requests being
+    /// served. Further clients get `503`.
+    pub max_clients: usize,
+}
+
+impl Default for MjpegServerOptions {
+    fn default() -> Self {
+        MjpegServerOptions {
+            address: SocketAddr::from(([127, 0, 0, 1], 8080)),
+            frame_rate: 10.0,
+            quality: 80,
+            max_clients: 4,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LatestFrame {
+    sequence: u64,
+    jpeg: Option<Arc<Vec<u8>>>,
+}
+
+#[derive(Debug)]
+struct ServerState {
+    monitor: Monitor,
+    video_recorder: VideoRecorder,
+    options: MjpegServerOptions,
+    running: AtomicBool,
+    clients: Mutex<usize>,
+    snapshot_clients: Mutex<usize>,
+    latest_frame: Mutex<LatestFrame>,
+    frame_condvar: Condvar,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    Stream,
+    Snapshot,
+    NotFound,
+    MethodNotAllowed,
+}
+
+fn parse_request(request: &str) -> Option<Route> {
+    let request_line = request.lines().next()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    let path = target.split('?').next().unwrap_or(target);
+
+    if method != "GET" {
+        return Some(Route::MethodNotAllowed);
+    }
+
+    let route = match path {
+        "/" | "/stream" | "/stream.mjpg" => Route::Stream,
+        "/snapshot" | "/snapshot.jpg" => Route::Snapshot,
+        _ => Route::NotFound,
+    };
+
+    Some(route)
+}
+
+fn read_request(stream: &mut TcpStream) -> XCapResult<String> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let size = stream.read(&mut buf)?;
+        if size == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..size]);
+
+        if request.len() > MAX_REQUEST_SIZE {
+            return Err(XCapError::new("Request header too large"));
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&request).into_owned())
+}
+
+fn write_status(stream: &mut TcpStream, status: &str) -> XCapResult<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{status}",
+        status.len()
+    )?;
+
+    Ok(())
+}
+
+fn encode_jpeg(image: &RgbaImage, quality: u8) -> XCapResult<Vec<u8>> {
+    let options = SaveOptions {
+        quality,
+        ..SaveOptions::default()
+    };
+
+    let mut buffer = Cursor::new(Vec::new());
+    encode_image(image, &mut buffer, ImageFormat::Jpeg, &options)?;
+
+    Ok(buffer.into_inner())
+}
+
+impl ServerState {
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.options.frame_rate.max(0.1))
+    }
+
+    fn acquire_client(&self) -> XCapResult<bool> {
+        let mut clients = self.clients.lock()?;
+        if *clients >= self.options.max_clients {
+            return Ok(false);
+        }
+
+        *clients += 1;
+        if *clients == 1 {
+            self.video_recorder.start()?;
+        }
+
+        Ok(true)
+    }
+
+    fn release_client(&self) -> XCapResult<()> {
+        let mut clients = self.clients.lock()?;
+        *clients = clients.saturating_sub(1);
+
+        // 没有客户端时停止录制，避免空转
+        if *clients == 0 {
+            self.video_recorder.stop()?;
+            self.latest_frame.lock()?.jpeg = None;
+        }
+
+        Ok(())
+    }
+
+    fn acquire_snapshot_client(&self) -> XCapResult<bool> {
+        let mut snapshot_clients = self.snapshot_clients.lock()?;
+        if *snapshot_clients >= self.options.max_clients {
+            return Ok(false);
+        }
+
+        *snapshot_clients += 1;
+
+        Ok(true)
+    }
+
+    fn release_snapshot_client(&self) -> XCapResult<()> {
+        let mut snapshot_clients = self.snapshot_clients.lock()?;
+        *snapshot_clients = snapshot_clients.saturating_sub(1);
+
+        Ok(())
+    }
+
+    fn publish(&self, jpeg: Vec<u8>) -> XCapResult<()> {
+        let mut latest_frame = self.latest_frame.lock()?;
+        latest_frame.sequence += 1;
+        latest_frame.jpeg = Some(Arc::new(jpeg));
+        self.frame_condvar.notify_all();
+
+        Ok(())
+    }
+
+    fn wait_frame(&self, sequence: u64) -> XCapResult<Option<(u64, Arc<Vec<u8>>)>> {
+        let mut latest_frame = self.latest_frame.lock()?;
+
+        loop {
+            if !self.is_running() {
+                return Ok(None);
+            }
+
+            if latest_frame.sequence != sequence
+                && let Some(jpeg) = &latest_frame.jpeg
+            {
+                return Ok(Some((latest_frame.sequence, jpeg.clone())));
+            }
+
+            latest_frame = self
+                .frame_condvar
+                .wait_timeout(latest_frame, POLL_INTERVAL)?
+                .0;
+        }
+    }
+
+    fn snapshot(&self) -> XCapResult<Arc<Vec<u8>>> {
+        if let Some(jpeg) = &self.latest_frame.lock()?.jpeg {
+            return Ok(jpeg.clone());
+        }
+
+        let image = self.monitor.capture_image()?;
+
+        Ok(Arc::new(encode_jpeg(&image, self.options.quality)?))
+    }
+}
+
+fn encode_frames(state: Arc<ServerState>, receiver: Receiver<Frame>) {
+    let mut last_encoded: Option<Instant> = None;
+    // 间隔内到达的帧先保留，间隔结束后再编码，静止画面的最后一次变化也能发出去
+    let mut pending_frame: Option<Frame> = None;
+
+    while state.is_running() {
+        let timeout = match (&pending_frame, last_encoded) {
+            (Some(_), Some(last_encoded)) => state
+                .frame_interval()
+                .saturating_sub(last_encoded.elapsed()),
+            (Some(_), None) => Duration::ZERO,
+            (None, _) => POLL_INTERVAL,
+        };
+
+        match receiver.recv_timeout(timeout) {
+            Ok(frame) => pending_frame = Some(frame),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        // 只编码最新的一帧
+        while let Ok(next_frame) = receiver.try_recv() {
+            pending_frame = Some(next_frame);
+        }
+
+        if last_encoded.is_some_and(|last_encoded| last_encoded.elapsed() < state.frame_interval())
+        {
+            continue;
+        }
+
+        let Some(frame) = pending_frame.take() else {
+            continue;
+        };
+        last_encoded = Some(Instant::now());
+
+        let Some(image) = RgbaImage::from_raw(frame.width, frame.height, frame.raw) else {
+            log::error!("Invalid frame size {}x{}", frame.width, frame.height);
+            continue;
+        };
+
+        let result =
+            encode_jpeg(&image, state.options.quality).and_then(|jpeg| state.publish(jpeg));
+
+        if let Err(err) = result {
+            log::error!("Failed to encode frame: {err:?}");
+        }
+    }
+}
+
+fn serve_stream(state: &ServerState, stream: &mut TcpStream) -> XCapResult<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: multipart/x-mixed-replace; boundary={BOUNDARY}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+
+    let mut sequence = 0;
+    while let Some((next_sequence, jpeg)) = state.wait_frame(sequence)? {
+        sequence = next_sequence;
+
+        write!(
+            stream,
+            "--{BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            jpeg.len()
+        )?;
+        stream.write_all(&jpeg)?;
+        stream.write_all(b"\r\n")?;
+        stream.flush()?;
+    }
+
+    Ok(())
+}
+
+fn serve_snapshot(state: &ServerState, stream: &mut TcpStream) -> XCapResult<()> {
+    let jpeg = state.snapshot()?;
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        jpeg.len()
+    )?;
+    stream.write_all(&jpeg)?;
+
+    Ok(())
+}
+
+fn handle_connection(state: &ServerState, mut stream: TcpStream) -> XCapResult<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+    let request = read_request(&mut stream)?;
+
+    match parse_request(&request) {
+        Some(Route::Stream) => {
+            if !state.acquire_client()? {
+                return write_status(&mut stream, "503 Service Unavailable");
+            }
+
+            let result = serve_stream(state, &mut stream);
+            state.release_client()?;
+
+            result
+        }
+        Some(Route::Snapshot) => {
+            if !state.acquire_snapshot_client()? {
+                return write_status(&mut stream, "503 Service Unavailable");
+            }
+
+            let result = serve_snapshot(state, &mut stream);
+            state.release_snapshot_client()?;
+
+            result
+        }
+        Some(Route::NotFound) => write_status(&mut stream, "404 Not Found"),
+        Some(Route::MethodNotAllowed) => write_status(&mut stream, "405 Method Not Allowed"),
+        None => write_status(&mut stream, "400 Bad Request"),
+    }
+}
+
+/// Serves a monitor as an MJPEG stream (`GET /stream`) and single JPEG
+/// snapshots (`GET /snapshot.jpg`) over HTTP.
+///
+/// Recording only runs while at least one stream client is connected.
+#[derive(Debug)]
+pub struct MjpegServer {
+    state: Arc<ServerState>,
+    local_addr: SocketAddr,
+}
+
+impl MjpegServer {
+    pub fn new(monitor: &Monitor, options: MjpegServerOptions) -> XCapResult<MjpegServer> {
+        let listener = TcpListener::bind(options.address)?;
+        let local_addr = listener.local_addr()?;
+        let (video_recorder, receiver) = monitor.video_recorder()?;
+
+        let state = Arc::new(ServerState {
+            monitor: monitor.clone(),
+            video_recorder,
+            options,
+            running: AtomicBool::new(true),
+            clients: Mutex::new(0),
+            snapshot_clients: Mutex::new(0),
+            latest_frame: Mutex::new(LatestFrame::default()),
+            frame_condvar: Condvar::new(),
+        });
+
+        let encoder_state = state.clone();
+        thread::spawn(move || encode_frames(encoder_state, receiver));
+
+        let listener_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !listener_state.is_running() {
+                    break;
+                }
+
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        log::error!("Failed to accept connection: {err:?}");
+                        continue;
+                    }
+                };
+
+                let state = listener_state.clone();
+                thread::spawn(move || {
+                    if let Err(err) = handle_connection(&state, stream) {
+                        log::debug!("MJPEG connection closed: {err:?}");
+                    }
+                });
+            }
+        });
+
+        Ok(MjpegServer { state, local_addr })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections and disconnect all clients.
+    pub fn stop(&self) -> XCapResult<()> {
+        if !self.state.running.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        self.state.frame_condvar.notify_all();
+        self.state.video_recorder.stop()?;
+
+        // 唤醒阻塞在 accept 上的监听线程
+        if let Ok(stream) = TcpStream::connect(self.local_addr) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for MjpegServer {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log::error!("Failed to stop MJPEG server: {err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(
+            parse_request("GET /stream HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+            Some(Route::Stream)
+        );
+        assert_eq!(
+            parse_request("GET /snapshot.jpg?t=1 HTTP/1.1\r\n\r\n"),
+            Some(Route::Snapshot)
+        );
+        assert_eq!(
+            parse_request("GET /favicon.ico HTTP/1.1\r\n\r\n"),
+            Some(Route::NotFound)
+        );
+        assert_eq!(
+            parse_request("POST / HTTP/1.1\r\n\r\n"),
+            Some(Route::MethodNotAllowed)
+        );
+        assert_eq!(parse_request(""), None);
+    }
+}
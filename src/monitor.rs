@@ -1,19 +1,36 @@
-use std::sync::mpsc::Receiver;
+use std::{
+    path::Path,
+    sync::{Arc, OnceLock, mpsc::Receiver},
+};
 
 use image::RgbaImage;
 
 use crate::{
-    VideoRecorder, error::XCapResult, platform::impl_monitor::ImplMonitor, video_recorder::Frame,
+    VideoRecorder,
+    capture_options::{CaptureOptions, CapturedImage, apply_color, apply_options},
+    edid::Edid,
+    error::XCapResult,
+    image_file::{SaveOptions, save_image},
+    pixel_format::PixelFormat,
+    platform::impl_monitor::ImplMonitor,
+    redaction::redact_monitor_image,
+    transform::Transform,
+    video_recorder::{Frame, FramePool, VideoRecorderOptions},
 };
 
 #[derive(Debug, Clone)]
 pub struct Monitor {
     pub(crate) impl_monitor: ImplMonitor,
+    /// 读取 ICC profile 可能需要阻塞的 D-Bus 调用，每个显示器只读取一次
+    icc_profile: Arc<OnceLock<Option<Vec<u8>>>>,
 }
 
 impl Monitor {
     pub(crate) fn new(impl_monitor: ImplMonitor) -> Monitor {
-        Monitor { impl_monitor }
+        Monitor {
+            impl_monitor,
+            icc_profile: Arc::default(),
+        }
     }
 }
 
@@ -67,6 +84,29 @@ impl Monitor {
     pub fn rotation(&self) -> XCapResult<f32> {
         self.impl_monitor.rotation()
     }
+    /// The parsed EDID of the monitor.
+    pub fn edid(&self) -> XCapResult<Edid> {
+        self.impl_monitor.edid()
+    }
+    /// The ICC profile of the monitor, `None` when no profile is assigned.
+    ///
+    /// The profile is read once and cached, call `Monitor::all()` again to pick up a new one.
+    pub fn icc_profile(&self) -> XCapResult<Option<Vec<u8>>> {
+        if let Some(icc_profile) = self.icc_profile.get() {
+            return Ok(icc_profile.clone());
+        }
+
+        // 读取失败时不缓存，下次调用时重试
+        let icc_profile = self.impl_monitor.icc_profile()?;
+
+        Ok(self.icc_profile.get_or_init(|| icc_profile).clone())
+    }
+    /// The monitor rotation and reflection, from panel-native to visible orientation.
+    ///
+    /// Reflections are only reported on X11, macOS and Windows have no reflected monitors.
+    pub fn transform(&self) -> XCapResult<Transform> {
+        self.impl_monitor.transform()
+    }
     /// Output device's pixel scale factor.
     pub fn scale_factor(&self) -> XCapResult<f32> {
         self.impl_monitor.scale_factor()
@@ -96,10 +136,76 @@ impl Monitor {
         self.impl_monitor.capture_region(x, y, width, height)
     }
 
+    /// Capture image of the monitor as RGBA into `buffer`, rows are `stride` bytes apart.
+    /// Returns the size of the image. On Wayland and with the `wgc` feature the capture
+    /// is copied from a temporary frame, other backends write into `buffer` directly.
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        self.impl_monitor.capture_into(buffer, stride)
+    }
+
+    /// Capture image of the monitor in `format`, converting at most once from the native pixels.
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        self.impl_monitor.capture_image_as(format)
+    }
+
+    /// Capture a thumbnail of the monitor that fits in `max_width` x `max_height`, keeping the
+    /// aspect ratio.
+    ///
+    /// X11 and GDI downscale while capturing and macOS captures at nominal resolution, WGC and
+    /// Wayland capture at full size and downscale afterwards.
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        self.impl_monitor.capture_thumbnail(max_width, max_height)
+    }
+
+    /// Capture image of the monitor in the resolution requested by `options`.
+    pub fn capture_image_with_options(
+        &self,
+        options: &CaptureOptions,
+    ) -> XCapResult<CapturedImage> {
+        let mut image = self.impl_monitor.capture_image()?;
+        redact_monitor_image(
+            &mut image,
+            self.impl_monitor.window_space_bounds()?,
+            &options.redact,
+            options.redaction_style,
+        )?;
+        let icc_profile = apply_color(&mut image, options, || self.icc_profile())?;
+
+        Ok(apply_options(
+            image,
+            options,
+            self.impl_monitor.physical_size()?,
+            self.impl_monitor.logical_size()?,
+            self.impl_monitor.scale_factor()?,
+            self.impl_monitor.transform()?,
+            icc_profile,
+        ))
+    }
+
+    /// Capture image of the monitor and save it to `path`, the format is taken from
+    /// `options.format` or guessed from the file extension.
+    pub fn capture_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &SaveOptions,
+    ) -> XCapResult<()> {
+        let image = self.capture_image()?;
+
+        save_image(&image, path, options)
+    }
+
     pub fn video_recorder(&self) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
-        let (impl_video_recorder, sx) = self.impl_monitor.video_recorder()?;
+        self.video_recorder_with_options(VideoRecorderOptions::default())
+    }
+
+    pub fn video_recorder_with_options(
+        &self,
+        options: VideoRecorderOptions,
+    ) -> XCapResult<(VideoRecorder, Receiver<Frame>)> {
+        let pool = FramePool::new(options.pool_size);
+        let (impl_video_recorder, sx) = self.impl_monitor.video_recorder(&options, pool.clone())?;
 
-        Ok((VideoRecorder::new(impl_video_recorder), sx))
+        Ok((VideoRecorder::new(impl_video_recorder, pool), sx))
     }
 }
 
@@ -2,7 +2,11 @@
 
 use image::RgbaImage;
 
-use crate::error::{XCapError, XCapResult};
+use crate::{
+    error::{XCapError, XCapResult},
+    pixel_format::{PixelFormat, convert_rows_into},
+    video_recorder::Frame,
+};
 
 use super::ffi;
 
@@ -14,10 +18,12 @@ fn ensure_image_info_query(result: i32, query_name: &str) -> XCapResult<()> {
     Err(XCapError::new(format!("{query_name} failed: {result}",)))
 }
 
-/// Capture one RGBA frame from `display_id`.
-///
-/// `width` and `height` are kept for API compatibility with other platforms.
-pub fn capture_screen(display_id: u64, _width: u32, _height: u32) -> XCapResult<RgbaImage> {
+/// Capture one frame from `display_id` and hand the native rows to `read` as
+/// `(data, row_stride, format, width, height)`.
+fn capture_screen_with<T>(
+    display_id: u64,
+    read: impl FnOnce(&[u8], usize, PixelFormat, u32, u32) -> XCapResult<T>,
+) -> XCapResult<T> {
     let mut pixelmap: *mut ffi::OH_PixelmapNative = std::ptr::null_mut();
     let rc = unsafe {
         ffi::OH_NativeDisplayManager_CaptureScreenPixelmap(display_id as u32, &mut pixelmap)
@@ -120,23 +126,68 @@ pub fn capture_screen(display_id: u64, _width: u32, _height: u32) -> XCapResult<
         )));
     }
 
-    let is_bgra = pixel_format == ffi::PIXEL_FORMAT_BGRA_8888;
-    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
-    for row in 0..height as usize {
-        let src_start = row * stride;
-        let src_row = &buf[src_start..src_start + bytes_per_row];
-        if is_bgra {
-            for chunk in src_row.chunks_exact(4) {
-                pixels.push(chunk[2]);
-                pixels.push(chunk[1]);
-                pixels.push(chunk[0]);
-                pixels.push(chunk[3]);
-            }
-        } else {
-            pixels.extend_from_slice(src_row);
-        }
-    }
+    let source_format = if pixel_format == ffi::PIXEL_FORMAT_BGRA_8888 {
+        PixelFormat::Bgra
+    } else {
+        PixelFormat::Rgba
+    };
 
-    RgbaImage::from_raw(width, height, pixels)
+    read(&buf, stride, source_format, width, height)
+}
+
+/// Capture one RGBA frame from `display_id`.
+///
+/// `width` and `height` are kept for API compatibility with other platforms.
+pub fn capture_screen(display_id: u64, _width: u32, _height: u32) -> XCapResult<RgbaImage> {
+    let frame = capture_screen_as(display_id, PixelFormat::Rgba)?;
+
+    RgbaImage::from_raw(frame.width, frame.height, frame.raw)
         .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed for pixelmap capture"))
 }
+
+/// Capture one frame from `display_id` in `format`, the pixelmap rows are converted once.
+pub fn capture_screen_as(display_id: u64, format: PixelFormat) -> XCapResult<Frame> {
+    capture_screen_with(display_id, |data, stride, source_format, width, height| {
+        let row_len = width as usize * format.bytes_per_pixel();
+        let mut raw = vec![0u8; row_len * height as usize];
+        convert_rows_into(
+            data,
+            stride,
+            source_format,
+            width,
+            height,
+            &mut raw,
+            row_len,
+            format,
+        )?;
+
+        Ok(Frame::new_with_format(width, height, format, raw))
+    })
+}
+
+/// Capture one frame from `display_id` in `format` straight into `buffer`, rows are
+/// `stride` bytes apart.
+pub fn capture_screen_into(
+    display_id: u64,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    capture_screen_with(
+        display_id,
+        |data, src_stride, source_format, width, height| {
+            convert_rows_into(
+                data,
+                src_stride,
+                source_format,
+                width,
+                height,
+                buffer,
+                stride,
+                format,
+            )?;
+
+            Ok((width, height))
+        },
+    )
+}
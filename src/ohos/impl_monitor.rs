@@ -6,11 +6,20 @@ use std::sync::mpsc::Receiver;
 use image::RgbaImage;
 
 use crate::{
+    capture_options::scale_size,
+    edid::Edid,
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    pixel_format::PixelFormat,
+    thumbnail::thumbnail_image,
+    transform::Transform,
+    video_recorder::{Frame, FramePool, VideoRecorderOptions},
 };
 
-use super::{capture::capture_screen, ffi, impl_video_recorder::ImplVideoRecorder};
+use super::{
+    capture::{capture_screen, capture_screen_as, capture_screen_into},
+    ffi,
+    impl_video_recorder::ImplVideoRecorder,
+};
 
 // ── Struct ────────────────────────────────────────────────────────────────────
 
@@ -133,6 +142,10 @@ impl ImplMonitor {
         Ok(self.name.clone())
     }
 
+    pub fn edid(&self) -> XCapResult<Edid> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         Ok(self.x)
     }
@@ -149,6 +162,21 @@ impl ImplMonitor {
         Ok(self.height as u32)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        let scale_factor = self.scale_factor()?;
+
+        Ok(scale_size(self.physical_size()?, 1.0 / scale_factor))
+    }
+
+    /// Bounds of the monitor in the coordinate space of window geometry.
+    pub fn window_space_bounds(&self) -> XCapResult<(i32, i32, u32, u32)> {
+        Ok((self.x()?, self.y()?, self.width()?, self.height()?))
+    }
+
     /// Clockwise rotation in degrees (0, 90, 180, 270).
     pub fn rotation(&self) -> XCapResult<f32> {
         let degrees = match self.rotation {
@@ -160,6 +188,15 @@ impl ImplMonitor {
         Ok(degrees)
     }
 
+    pub fn transform(&self) -> XCapResult<Transform> {
+        Ok(Transform::new(self.rotation()? as u32, false, false))
+    }
+
+    pub fn icc_profile(&self) -> XCapResult<Option<Vec<u8>>> {
+        // 尚未实现读取显示器的 ICC profile
+        Ok(None)
+    }
+
     /// Logical pixel density (≈ DPR), e.g. `3.0` for a high-DPI phone screen.
     pub fn scale_factor(&self) -> XCapResult<f32> {
         Ok(self.density_pixels)
@@ -188,6 +225,22 @@ impl ImplMonitor {
         )
     }
 
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        capture_screen_as(self.display_id as u64, format)
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        capture_screen_into(self.display_id as u64, PixelFormat::Rgba, buffer, stride)
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        Ok(thumbnail_image(
+            self.capture_image()?,
+            max_width,
+            max_height,
+        ))
+    }
+
     /// Capture a sub-region of this monitor.
     ///
     /// `x`, `y` are relative to the monitor's top-left corner.
@@ -203,11 +256,17 @@ impl ImplMonitor {
         Ok(image::imageops::crop_imm(&full, x, y, width, height).to_image())
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+    pub fn video_recorder(
+        &self,
+        options: &VideoRecorderOptions,
+        pool: FramePool,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
         ImplVideoRecorder::new(
             self.display_id as u64,
             self.width as u32,
             self.height as u32,
+            options.format,
+            pool,
         )
     }
 }
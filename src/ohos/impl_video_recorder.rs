@@ -18,12 +18,13 @@ use std::os::raw::c_void;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{
     Arc,
-    mpsc::{self, Receiver, SyncSender},
+    mpsc::{self, Receiver, SyncSender, TrySendError},
 };
 
 use crate::{
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    pixel_format::{PixelFormat, convert_pixels_into},
+    video_recorder::{Frame, FramePool},
 };
 
 use super::ffi;
@@ -49,6 +50,10 @@ struct RecorderShared {
     capture: *mut ffi::OH_AVScreenCapture,
     width: u32,
     height: u32,
+    /// Pixel format of the delivered frames, converted from the RGBA stream.
+    format: PixelFormat,
+    /// Buffers handed back by the caller, reused for new frames.
+    pool: FramePool,
     /// Channel endpoint used to push frames to the caller.
     tx: SyncSender<Frame>,
     /// `true` while the caller wants frames delivered to `tx`.
@@ -101,7 +106,13 @@ impl ImplVideoRecorder {
     ///
     /// Returns `(recorder, rx)` where `rx` is the channel from which callers
     /// receive `Frame` values after calling `start()`.
-    pub fn new(display_id: u64, width: u32, height: u32) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        display_id: u64,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         let capture = unsafe { ffi::OH_AVScreenCapture_Create() };
         if capture.is_null() {
             return Err(XCapError::new("OH_AVScreenCapture_Create returned null"));
@@ -132,6 +143,8 @@ impl ImplVideoRecorder {
             capture,
             width,
             height,
+            format,
+            pool,
             tx,
             frame_running: AtomicBool::new(false),
             capture_active: AtomicBool::new(false),
@@ -278,10 +291,19 @@ unsafe extern "C" fn on_buffer_recorder(
 
     // SAFETY: `addr` points to `capacity` valid, CPU-accessible bytes for the
     // duration of this callback invocation.
-    let data = std::slice::from_raw_parts(addr, capacity as usize).to_vec();
-    let frame = Frame::new(shared.width, shared.height, data);
+    let data = std::slice::from_raw_parts(addr, capacity as usize);
+    let pixels = shared.width as usize * shared.height as usize;
+    let data = data.get(..pixels * 4).unwrap_or(data);
+
+    // Convert straight from the RGBA stream into a pooled buffer.
+    let mut raw = shared.pool.take(pixels * shared.format.bytes_per_pixel());
+    convert_pixels_into(data, PixelFormat::Rgba, &mut raw, shared.format);
+    let frame = Frame::new_with_format(shared.width, shared.height, shared.format, raw);
 
     if let Err(e) = shared.tx.try_send(frame) {
         log::warn!("xcap: OHOS frame dropped: {}", e);
+        if let TrySendError::Full(frame) = e {
+            shared.pool.put(frame.raw);
+        }
     }
 }
@@ -0,0 +1,36 @@
+use crate::{
+    error::{XCapError, XCapResult},
+    polling_watcher::PollingMonitorWatcher,
+    watcher::WindowEvent,
+};
+
+use super::impl_window::ImplWindow;
+
+#[derive(Debug)]
+pub(crate) struct ImplActiveWindowWatcher;
+
+impl ImplActiveWindowWatcher {
+    pub fn new() -> XCapResult<ImplActiveWindowWatcher> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn next_change(&mut self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ImplWindowWatcher;
+
+impl ImplWindowWatcher {
+    pub fn new() -> XCapResult<ImplWindowWatcher> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<WindowEvent> {
+        Err(XCapError::NotSupported)
+    }
+}
+
+// 没有可用的显示器变化通知，通过轮询实现
+pub(crate) type ImplMonitorWatcher = PollingMonitorWatcher;
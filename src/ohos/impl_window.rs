@@ -8,7 +8,11 @@ use image::RgbaImage;
 
 use crate::{
     error::{XCapError, XCapResult},
+    pixel_format::PixelFormat,
     platform::impl_monitor::ImplMonitor,
+    process_info::ProcessInfo,
+    video_recorder::Frame,
+    window_type::WindowType,
 };
 
 #[derive(Debug, Clone)]
@@ -19,6 +23,10 @@ impl ImplWindow {
         Ok(Vec::new())
     }
 
+    pub fn from_point(_x: i32, _y: i32) -> XCapResult<ImplWindow> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn id(&self) -> XCapResult<u32> {
         Err(XCapError::NotSupported)
     }
@@ -59,10 +67,22 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         Err(XCapError::NotSupported)
     }
 
+    pub fn is_viewable(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn is_maximized(&self) -> XCapResult<bool> {
         Err(XCapError::NotSupported)
     }
@@ -71,7 +91,59 @@ impl ImplWindow {
         Err(XCapError::NotSupported)
     }
 
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_fullscreen(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_always_on_top(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_sticky(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn is_skip_taskbar(&self) -> XCapResult<bool> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn desktop(&self) -> XCapResult<Option<u32>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn icon(&self, _preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn process(&self) -> XCapResult<ProcessInfo> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         Err(XCapError::NotSupported)
     }
+
+    pub fn capture_image_as(&self, _format: PixelFormat) -> XCapResult<Frame> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_into(&self, _buffer: &mut [u8], _stride: usize) -> XCapResult<(u32, u32)> {
+        Err(XCapError::NotSupported)
+    }
+
+    pub fn capture_thumbnail(&self, _max_width: u32, _max_height: u32) -> XCapResult<RgbaImage> {
+        Err(XCapError::NotSupported)
+    }
 }
@@ -0,0 +1,8 @@
+use crate::{
+    error::{XCapError, XCapResult},
+    workspace::Workspace,
+};
+
+pub fn get_workspaces() -> XCapResult<Vec<Workspace>> {
+    Err(XCapError::NotSupported)
+}
@@ -6,7 +6,9 @@ mod capture;
 mod ffi;
 pub mod impl_monitor;
 pub mod impl_video_recorder;
+pub mod impl_watcher;
 pub mod impl_window;
+pub mod impl_workspace;
 
 // Keep symbol export layout consistent with other platform modules.
 #[allow(unused_imports)]
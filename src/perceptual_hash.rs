@@ -0,0 +1,295 @@
+use std::f32::consts::PI;
+
+use image::{RgbaImage, imageops::FilterType};
+
+use crate::{
+    thumbnail::{box_downscale, downscale_pixels},
+    video_recorder::Frame,
+};
+
+/// Perceptual hash algorithms, all produce 64 bit hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HashAlgorithm {
+    /// Average hash, pixels brighter than the mean of an 8x8 thumbnail.
+    Average,
+    /// Difference hash, brightness gradients of a 9x8 thumbnail. Fast and robust for screenshots.
+    #[default]
+    Difference,
+    /// DCT hash, low frequencies of a 32x32 thumbnail. Slowest, most robust against rescaling.
+    Perceptual,
+}
+
+impl HashAlgorithm {
+    fn thumbnail_size(&self) -> (u32, u32) {
+        match self {
+            HashAlgorithm::Average => (8, 8),
+            HashAlgorithm::Difference => (9, 8),
+            HashAlgorithm::Perceptual => (32, 32),
+        }
+    }
+}
+
+/// A 64 bit perceptual hash, similar images have hashes with a small Hamming distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ImageHash(pub u64);
+
+impl ImageHash {
+    /// Number of differing bits, 0 for identical hashes and at most 64.
+    pub fn distance(&self, other: &ImageHash) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+}
+
+fn luma(image: &RgbaImage) -> Vec<f32> {
+    image
+        .pixels()
+        .map(|pixel| 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32)
+        .collect()
+}
+
+fn bits(values: impl Iterator<Item = bool>) -> ImageHash {
+    ImageHash(
+        values
+            .enumerate()
+            .filter(|(_, bit)| *bit)
+            .fold(0, |hash, (index, _)| hash | (1 << index)),
+    )
+}
+
+/// First 8x8 coefficients of the 2D DCT-II of a 32x32 image.
+fn dct_8x8(pixels: &[f32]) -> Vec<f32> {
+    const SIZE: usize = 32;
+
+    let cosines: Vec<f32> = (0..8)
+        .flat_map(|k| {
+            (0..SIZE).map(move |n| (PI / SIZE as f32 * (n as f32 + 0.5) * k as f32).cos())
+        })
+        .collect();
+
+    // 先对每一行变换，再对列变换
+    let mut rows = vec![0.0; SIZE * 8];
+    for y in 0..SIZE {
+        for k in 0..8 {
+            rows[y * 8 + k] = (0..SIZE)
+                .map(|x| pixels[y * SIZE + x] * cosines[k * SIZE + x])
+                .sum();
+        }
+    }
+
+    let mut coefficients = vec![0.0; 64];
+    for k in 0..8 {
+        for column in 0..8 {
+            coefficients[k * 8 + column] = (0..SIZE)
+                .map(|y| rows[y * 8 + column] * cosines[k * SIZE + y])
+                .sum();
+        }
+    }
+
+    coefficients
+}
+
+fn hash_thumbnail(thumbnail: &RgbaImage, algorithm: HashAlgorithm) -> ImageHash {
+    let pixels = luma(thumbnail);
+
+    match algorithm {
+        HashAlgorithm::Average => {
+            let mean = pixels.iter().sum::<f32>() / pixels.len() as f32;
+            bits(pixels.iter().map(|value| *value > mean))
+        }
+        HashAlgorithm::Difference => bits(
+            pixels
+                .chunks_exact(9)
+                .flat_map(|row| row.windows(2).map(|pair| pair[0] < pair[1])),
+        ),
+        HashAlgorithm::Perceptual => {
+            let coefficients = dct_8x8(&pixels);
+            // 中位数不包括直流分量
+            let mut sorted = coefficients[1..].to_vec();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            let median = sorted[sorted.len() / 2];
+
+            bits(coefficients.iter().map(|value| *value > median))
+        }
+    }
+}
+
+/// box_downscale 只能缩小，源图像比缩略图小时部分格子没有源像素，需要插值放大
+fn needs_upscale(source_width: u32, source_height: u32, algorithm: HashAlgorithm) -> bool {
+    let (width, height) = algorithm.thumbnail_size();
+
+    source_width > 0 && source_height > 0 && (source_width < width || source_height < height)
+}
+
+/// Perceptual hash of an image.
+pub fn image_hash(image: &RgbaImage, algorithm: HashAlgorithm) -> ImageHash {
+    let (width, height) = algorithm.thumbnail_size();
+    let thumbnail = if needs_upscale(image.width(), image.height(), algorithm) {
+        image::imageops::resize(image, width, height, FilterType::Triangle)
+    } else {
+        box_downscale(image.width(), image.height(), width, height, |x, y| {
+            image.get_pixel(x, y).0
+        })
+    };
+
+    hash_thumbnail(&thumbnail, algorithm)
+}
+
+/// Perceptual hash of a recorder frame, the pixels are read in the frame's format.
+pub fn frame_hash(frame: &Frame, algorithm: HashAlgorithm) -> ImageHash {
+    if needs_upscale(frame.width, frame.height, algorithm) {
+        // 先按原尺寸转换为 RGBA
+        let image = downscale_pixels(
+            frame.width,
+            frame.height,
+            frame.format,
+            &frame.raw,
+            frame.width,
+            frame.height,
+        );
+        return image_hash(&image, algorithm);
+    }
+
+    let (width, height) = algorithm.thumbnail_size();
+    let thumbnail = downscale_pixels(
+        frame.width,
+        frame.height,
+        frame.format,
+        &frame.raw,
+        width,
+        height,
+    );
+
+    hash_thumbnail(&thumbnail, algorithm)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimilarityOptions {
+    pub algorithm: HashAlgorithm,
+    /// Images whose hashes differ in at most this many bits are similar.
+    pub max_distance: u32,
+}
+
+impl Default for SimilarityOptions {
+    fn default() -> Self {
+        SimilarityOptions {
+            algorithm: HashAlgorithm::Difference,
+            max_distance: 4,
+        }
+    }
+}
+
+/// Drops images that are similar to the last image that was kept.
+#[derive(Debug, Clone)]
+pub struct SimilarityFilter {
+    options: SimilarityOptions,
+    previous: Option<ImageHash>,
+}
+
+impl SimilarityFilter {
+    pub fn new(options: SimilarityOptions) -> SimilarityFilter {
+        SimilarityFilter {
+            options,
+            previous: None,
+        }
+    }
+
+    fn check(&mut self, hash: ImageHash) -> bool {
+        if let Some(previous) = self.previous
+            && previous.distance(&hash) <= self.options.max_distance
+        {
+            return true;
+        }
+
+        self.previous = Some(hash);
+        false
+    }
+
+    /// Whether the image is similar to the last kept one, otherwise it becomes the kept image.
+    pub fn is_similar(&mut self, image: &RgbaImage) -> bool {
+        self.check(image_hash(image, self.options.algorithm))
+    }
+
+    /// Whether the frame is similar to the last kept one, otherwise it becomes the kept frame.
+    pub fn is_similar_frame(&mut self, frame: &Frame) -> bool {
+        self.check(frame_hash(frame, self.options.algorithm))
+    }
+
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use crate::pixel_format::PixelFormat;
+
+    use super::*;
+
+    #[test]
+    fn test_image_hash() {
+        // 有明显结构的图像，水平翻转后明显不同
+        let image = RgbaImage::from_fn(64, 48, |x, y| {
+            let (fx, fy) = (x as f32, y as f32);
+            let value = 100.0 + 60.0 * (fx / 9.0).sin() * (fy / 7.0).cos() + fx * 1.2;
+            Rgba([value as u8, (value * 0.8) as u8, 90, 255])
+        });
+        let mut noisy = image.clone();
+        for pixel in noisy.pixels_mut().step_by(7) {
+            pixel[0] = pixel[0].saturating_add(6);
+        }
+        let flipped = image::imageops::flip_horizontal(&image);
+
+        for algorithm in [
+            HashAlgorithm::Average,
+            HashAlgorithm::Difference,
+            HashAlgorithm::Perceptual,
+        ] {
+            let hash = image_hash(&image, algorithm);
+            assert!(hash.distance(&image_hash(&noisy, algorithm)) <= 4);
+            assert!(hash.distance(&image_hash(&flipped, algorithm)) > 16);
+        }
+
+        let frame = Frame::from(image.clone()).convert(PixelFormat::Bgra);
+        assert_eq!(
+            frame_hash(&frame, HashAlgorithm::Difference),
+            image_hash(&image, HashAlgorithm::Difference)
+        );
+
+        let mut filter = SimilarityFilter::new(SimilarityOptions::default());
+        assert!(!filter.is_similar(&image));
+        assert!(filter.is_similar(&noisy));
+        assert!(!filter.is_similar(&flipped));
+    }
+
+    #[test]
+    fn test_small_image_hash() {
+        // 比缩略图小的图像不能留下没有源像素的黑色格子
+        let white = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        let black = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+
+        for algorithm in [HashAlgorithm::Average, HashAlgorithm::Difference] {
+            // 均匀的图像没有明暗差异
+            assert_eq!(image_hash(&white, algorithm), image_hash(&black, algorithm));
+        }
+
+        // 左暗右亮的图像，每一行的亮度都在增加
+        let gradient = RgbaImage::from_fn(4, 4, |x, _| {
+            let value = (x * 60) as u8;
+            Rgba([value, value, value, 255])
+        });
+        let hash = image_hash(&gradient, HashAlgorithm::Difference);
+        assert_eq!(hash.0.count_ones(), 64);
+
+        let flipped = image::imageops::flip_horizontal(&gradient);
+        assert!(
+            image_hash(&gradient, HashAlgorithm::Perceptual)
+                .distance(&image_hash(&flipped, HashAlgorithm::Perceptual))
+                > 16
+        );
+
+        let frame = Frame::from(gradient.clone()).convert(PixelFormat::Bgra);
+        assert_eq!(frame_hash(&frame, HashAlgorithm::Difference), hash);
+    }
+}
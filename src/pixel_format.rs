@@ -0,0 +1,284 @@
+use image::RgbaImage;
+
+use crate::error::{XCapError, XCapResult};
+
+/// Memory layout of captured pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PixelFormat {
+    /// 4 bytes per pixel in R, G, B, A order.
+    #[default]
+    Rgba,
+    /// 4 bytes per pixel in B, G, R, A order, the native order of most platforms.
+    Bgra,
+    /// 3 bytes per pixel in R, G, B order.
+    Rgb,
+    /// 1 byte per pixel, BT.601 luma.
+    Gray,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgba | PixelFormat::Bgra => 4,
+            PixelFormat::Rgb => 3,
+            PixelFormat::Gray => 1,
+        }
+    }
+
+    /// FourCC code of the format.
+    pub fn fourcc(&self) -> [u8; 4] {
+        match self {
+            PixelFormat::Rgba => *b"RGBA",
+            PixelFormat::Bgra => *b"BGRA",
+            PixelFormat::Rgb => *b"RGB3",
+            PixelFormat::Gray => *b"GREY",
+        }
+    }
+
+    pub fn from_fourcc(fourcc: [u8; 4]) -> Option<PixelFormat> {
+        [
+            PixelFormat::Rgba,
+            PixelFormat::Bgra,
+            PixelFormat::Rgb,
+            PixelFormat::Gray,
+        ]
+        .into_iter()
+        .find(|format| format.fourcc() == fourcc)
+    }
+}
+
+#[inline]
+pub(crate) fn read_pixel(src: &[u8], format: PixelFormat) -> [u8; 4] {
+    match format {
+        PixelFormat::Rgba => [src[0], src[1], src[2], src[3]],
+        PixelFormat::Bgra => [src[2], src[1], src[0], src[3]],
+        PixelFormat::Rgb => [src[0], src[1], src[2], 255],
+        PixelFormat::Gray => [src[0], src[0], src[0], 255],
+    }
+}
+
+#[inline]
+pub(crate) fn write_pixel(dst: &mut [u8], format: PixelFormat, [r, g, b, a]: [u8; 4]) {
+    match format {
+        PixelFormat::Rgba => dst.copy_from_slice(&[r, g, b, a]),
+        PixelFormat::Bgra => dst.copy_from_slice(&[b, g, r, a]),
+        PixelFormat::Rgb => dst.copy_from_slice(&[r, g, b]),
+        PixelFormat::Gray => {
+            dst[0] = ((77 * r as u32 + 150 * g as u32 + 29 * b as u32) >> 8) as u8;
+        }
+    }
+}
+
+/// Convert tightly packed pixels between formats, `dst` must hold as many pixels as `src`.
+pub(crate) fn convert_pixels_into(
+    src: &[u8],
+    src_format: PixelFormat,
+    dst: &mut [u8],
+    dst_format: PixelFormat,
+) {
+    let src_bpp = src_format.bytes_per_pixel();
+    let dst_bpp = dst_format.bytes_per_pixel();
+
+    match (src_format, dst_format) {
+        _ if src_format == dst_format => {
+            let len = src.len().min(dst.len());
+            dst[..len].copy_from_slice(&src[..len]);
+        }
+        (PixelFormat::Rgba, PixelFormat::Bgra) | (PixelFormat::Bgra, PixelFormat::Rgba) => {
+            for (src, dst) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+                dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+            }
+        }
+        _ => {
+            for (src, dst) in src.chunks_exact(src_bpp).zip(dst.chunks_exact_mut(dst_bpp)) {
+                write_pixel(dst, dst_format, read_pixel(src, src_format));
+            }
+        }
+    }
+}
+
+/// Convert tightly packed pixels between formats.
+pub(crate) fn convert_pixels(
+    src: &[u8],
+    src_format: PixelFormat,
+    dst_format: PixelFormat,
+) -> Vec<u8> {
+    if src_format == dst_format {
+        return src.to_vec();
+    }
+
+    let mut dst =
+        vec![0u8; src.len() / src_format.bytes_per_pixel() * dst_format.bytes_per_pixel()];
+    convert_pixels_into(src, src_format, &mut dst, dst_format);
+
+    dst
+}
+
+/// Set the alpha channel to 255, for sources whose fourth byte is padding (RGBx, BGRx).
+#[allow(dead_code)]
+pub(crate) fn set_opaque(pixels: &mut [u8], format: PixelFormat) {
+    if matches!(format, PixelFormat::Rgba | PixelFormat::Bgra) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+    }
+}
+
+/// Check that `len` bytes hold an image of `width` x `height` with rows `stride` bytes apart.
+pub(crate) fn check_buffer(
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    len: usize,
+    stride: usize,
+) -> XCapResult<()> {
+    let row_len = width as usize * format.bytes_per_pixel();
+    let required = match height {
+        0 => 0,
+        height => stride * (height as usize - 1) + row_len,
+    };
+
+    if stride < row_len || len < required {
+        return Err(XCapError::new(format!(
+            "Buffer of {len} bytes with stride {stride} is too small for a {width}x{height} {format:?} image, {required} bytes with a stride of at least {row_len} are required"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Copy tightly packed `src` pixels into `dst` rows that are `stride` bytes apart.
+pub(crate) fn copy_rows(
+    src: &[u8],
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    dst: &mut [u8],
+    stride: usize,
+) -> XCapResult<()> {
+    check_buffer(width, height, format, dst.len(), stride)?;
+
+    let row_len = width as usize * format.bytes_per_pixel();
+    if row_len == 0 {
+        return Ok(());
+    }
+
+    for (src_row, dst_row) in src.chunks_exact(row_len).zip(dst.chunks_mut(stride)) {
+        dst_row[..row_len].copy_from_slice(src_row);
+    }
+
+    Ok(())
+}
+
+/// Convert `src` rows that are `src_stride` bytes apart into `dst` rows that are
+/// `dst_stride` bytes apart, used to read native capture buffers with padded rows.
+#[allow(clippy::too_many_arguments)]
+#[allow(dead_code)]
+pub(crate) fn convert_rows_into(
+    src: &[u8],
+    src_stride: usize,
+    src_format: PixelFormat,
+    width: u32,
+    height: u32,
+    dst: &mut [u8],
+    dst_stride: usize,
+    dst_format: PixelFormat,
+) -> XCapResult<()> {
+    check_buffer(width, height, src_format, src.len(), src_stride)?;
+    check_buffer(width, height, dst_format, dst.len(), dst_stride)?;
+
+    let src_row_len = width as usize * src_format.bytes_per_pixel();
+    let dst_row_len = width as usize * dst_format.bytes_per_pixel();
+    if src_row_len == 0 {
+        return Ok(());
+    }
+
+    for (src_row, dst_row) in src
+        .chunks(src_stride)
+        .zip(dst.chunks_mut(dst_stride))
+        .take(height as usize)
+    {
+        convert_pixels_into(
+            &src_row[..src_row_len],
+            src_format,
+            &mut dst_row[..dst_row_len],
+            dst_format,
+        );
+    }
+
+    Ok(())
+}
+
+/// Copy an already captured image into a caller provided buffer.
+#[allow(dead_code)]
+pub(crate) fn copy_image_into(
+    image: &RgbaImage,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    let (width, height) = image.dimensions();
+    copy_rows(
+        image.as_raw(),
+        width,
+        height,
+        PixelFormat::Rgba,
+        buffer,
+        stride,
+    )?;
+
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_pixels() {
+        let rgba = [255, 128, 0, 200, 10, 20, 30, 255];
+
+        let bgra = convert_pixels(&rgba, PixelFormat::Rgba, PixelFormat::Bgra);
+        assert_eq!(bgra, vec![0, 128, 255, 200, 30, 20, 10, 255]);
+        assert_eq!(
+            convert_pixels(&bgra, PixelFormat::Bgra, PixelFormat::Rgb),
+            vec![255, 128, 0, 10, 20, 30]
+        );
+        assert_eq!(
+            convert_pixels(&rgba, PixelFormat::Rgba, PixelFormat::Gray),
+            vec![151, 18]
+        );
+        assert_eq!(PixelFormat::from_fourcc(*b"GREY"), Some(PixelFormat::Gray));
+
+        let mut rgbx = [1, 2, 3, 0, 4, 5, 6, 0];
+        set_opaque(&mut rgbx, PixelFormat::Rgba);
+        assert_eq!(rgbx, [1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn test_copy_rows() {
+        let src = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut dst = [0u8; 10];
+
+        copy_rows(&src, 1, 2, PixelFormat::Rgba, &mut dst, 6).unwrap();
+        assert_eq!(dst, [1, 2, 3, 4, 0, 0, 5, 6, 7, 8]);
+
+        assert!(copy_rows(&src, 1, 2, PixelFormat::Rgba, &mut dst, 3).is_err());
+        assert!(copy_rows(&src, 1, 2, PixelFormat::Rgba, &mut dst[..9], 6).is_err());
+
+        // 源数据每行有填充字节
+        let bgra = [3, 2, 1, 4, 9, 9, 7, 6, 5, 8];
+        let mut rgb = [0u8; 6];
+        convert_rows_into(
+            &bgra,
+            6,
+            PixelFormat::Bgra,
+            1,
+            2,
+            &mut rgb,
+            3,
+            PixelFormat::Rgb,
+        )
+        .unwrap();
+        assert_eq!(rgb, [1, 2, 3, 5, 6, 7]);
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    Monitor, Window,
+    error::XCapResult,
+    platform::{impl_monitor::ImplMonitor, impl_window::ImplWindow},
+    watcher::{MonitorEvent, MonitorField, WindowEvent},
+};
+
+/// Platforms without change notifications compare snapshots at this interval.
+#[allow(dead_code)]
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[allow(dead_code)]
+fn get_active_window() -> XCapResult<Option<ImplWindow>> {
+    let active_window = ImplWindow::all()?
+        .into_iter()
+        .find(|impl_window| impl_window.is_focused().unwrap_or(false));
+
+    Ok(active_window)
+}
+
+/// Active window watcher built on `ImplWindow::is_focused`.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub(crate) struct PollingActiveWindowWatcher {
+    active_window_id: Option<u32>,
+}
+
+#[allow(dead_code)]
+impl PollingActiveWindowWatcher {
+    pub fn new() -> XCapResult<PollingActiveWindowWatcher> {
+        let active_window_id = get_active_window()?
+            .map(|impl_window| impl_window.id())
+            .transpose()?;
+
+        Ok(PollingActiveWindowWatcher { active_window_id })
+    }
+
+    pub fn next_change(&mut self) -> XCapResult<Option<ImplWindow>> {
+        loop {
+            let active_window = get_active_window()?;
+            let active_window_id = active_window
+                .as_ref()
+                .map(|impl_window| impl_window.id())
+                .transpose()?;
+
+            if active_window_id != self.active_window_id {
+                self.active_window_id = active_window_id;
+                return Ok(active_window);
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+struct WindowSnapshot {
+    title: String,
+    position: (i32, i32),
+    size: (u32, u32),
+    state: (bool, bool),
+}
+
+#[allow(dead_code)]
+impl WindowSnapshot {
+    fn new(impl_window: &ImplWindow) -> XCapResult<WindowSnapshot> {
+        Ok(WindowSnapshot {
+            title: impl_window.title()?,
+            position: (impl_window.x()?, impl_window.y()?),
+            size: (impl_window.width()?, impl_window.height()?),
+            state: (impl_window.is_minimized()?, impl_window.is_maximized()?),
+        })
+    }
+}
+
+#[allow(dead_code)]
+fn take_snapshots() -> XCapResult<Vec<(u32, ImplWindow, WindowSnapshot)>> {
+    let mut snapshots = Vec::new();
+
+    for impl_window in ImplWindow::all()? {
+        // 枚举过程中关闭的窗口在下一次轮询时处理
+        let Ok(snapshot) = WindowSnapshot::new(&impl_window) else {
+            continue;
+        };
+        snapshots.push((impl_window.id()?, impl_window, snapshot));
+    }
+
+    Ok(snapshots)
+}
+
+/// Window watcher that diffs snapshots of `ImplWindow::all`.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub(crate) struct PollingWindowWatcher {
+    windows: HashMap<u32, (ImplWindow, WindowSnapshot)>,
+    events: VecDeque<WindowEvent>,
+}
+
+#[allow(dead_code)]
+impl PollingWindowWatcher {
+    pub fn new() -> XCapResult<PollingWindowWatcher> {
+        let windows = take_snapshots()?
+            .into_iter()
+            .map(|(id, impl_window, snapshot)| (id, (impl_window, snapshot)))
+            .collect();
+
+        Ok(PollingWindowWatcher {
+            windows,
+            events: VecDeque::new(),
+        })
+    }
+
+    fn update(&mut self) -> XCapResult<()> {
+        let snapshots = take_snapshots()?;
+        let window = |impl_window: &ImplWindow| Window::new(impl_window.clone());
+
+        for (id, impl_window, snapshot) in &snapshots {
+            let Some((_, previous)) = self.windows.get(id) else {
+                self.events
+                    .push_back(WindowEvent::Created(window(impl_window)));
+                continue;
+            };
+
+            if previous.position != snapshot.position {
+                self.events
+                    .push_back(WindowEvent::Moved(window(impl_window)));
+            }
+            if previous.size != snapshot.size {
+                self.events
+                    .push_back(WindowEvent::Resized(window(impl_window)));
+            }
+            if previous.title != snapshot.title {
+                self.events
+                    .push_back(WindowEvent::TitleChanged(window(impl_window)));
+            }
+            if previous.state != snapshot.state {
+                self.events
+                    .push_back(WindowEvent::StateChanged(window(impl_window)));
+            }
+        }
+
+        let windows: HashMap<u32, (ImplWindow, WindowSnapshot)> = snapshots
+            .into_iter()
+            .map(|(id, impl_window, snapshot)| (id, (impl_window, snapshot)))
+            .collect();
+
+        for (id, (impl_window, _)) in &self.windows {
+            if !windows.contains_key(id) {
+                self.events
+                    .push_back(WindowEvent::Destroyed(window(impl_window)));
+            }
+        }
+
+        self.windows = windows;
+
+        Ok(())
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<WindowEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+
+            thread::sleep(POLL_INTERVAL);
+            self.update()?;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MonitorSnapshot {
+    position: (i32, i32),
+    size: (u32, u32),
+    rotation: f32,
+    scale_factor: f32,
+    frequency: f32,
+    is_primary: bool,
+}
+
+impl MonitorSnapshot {
+    fn new(impl_monitor: &ImplMonitor) -> XCapResult<MonitorSnapshot> {
+        Ok(MonitorSnapshot {
+            position: (impl_monitor.x()?, impl_monitor.y()?),
+            size: (impl_monitor.width()?, impl_monitor.height()?),
+            rotation: impl_monitor.rotation()?,
+            scale_factor: impl_monitor.scale_factor()?,
+            frequency: impl_monitor.frequency()?,
+            is_primary: impl_monitor.is_primary()?,
+        })
+    }
+
+    fn changed_fields(&self, other: &MonitorSnapshot) -> Vec<MonitorField> {
+        [
+            (self.position != other.position, MonitorField::Position),
+            (self.size != other.size, MonitorField::Size),
+            (self.rotation != other.rotation, MonitorField::Rotation),
+            (
+                self.scale_factor != other.scale_factor,
+                MonitorField::ScaleFactor,
+            ),
+            (self.frequency != other.frequency, MonitorField::Frequency),
+            (self.is_primary != other.is_primary, MonitorField::Primary),
+        ]
+        .into_iter()
+        .filter_map(|(changed, field)| changed.then_some(field))
+        .collect()
+    }
+}
+
+/// The last known monitor configuration, diffed against the current one to produce events.
+/// Shared by the notification based and the polling monitor watchers.
+#[derive(Debug)]
+pub(crate) struct MonitorSnapshots {
+    monitors: Vec<(u32, ImplMonitor, MonitorSnapshot)>,
+}
+
+impl MonitorSnapshots {
+    fn take() -> XCapResult<Vec<(u32, ImplMonitor, MonitorSnapshot)>> {
+        let mut monitors = Vec::new();
+
+        for impl_monitor in ImplMonitor::all()? {
+            // 配置变化过程中显示器可能暂时不可用，等下一次变化再处理
+            let Ok(snapshot) = MonitorSnapshot::new(&impl_monitor) else {
+                continue;
+            };
+            monitors.push((impl_monitor.id()?, impl_monitor, snapshot));
+        }
+
+        Ok(monitors)
+    }
+
+    pub fn new() -> XCapResult<MonitorSnapshots> {
+        Ok(MonitorSnapshots {
+            monitors: MonitorSnapshots::take()?,
+        })
+    }
+
+    pub fn update(&mut self) -> XCapResult<Vec<MonitorEvent>> {
+        let monitors = MonitorSnapshots::take()?;
+        let mut events = Vec::new();
+
+        for (id, impl_monitor, _) in &self.monitors {
+            if !monitors.iter().any(|(current_id, _, _)| current_id == id) {
+                events.push(MonitorEvent::Removed(Monitor::new(impl_monitor.clone())));
+            }
+        }
+
+        for (id, impl_monitor, snapshot) in &monitors {
+            let monitor = Monitor::new(impl_monitor.clone());
+
+            match self
+                .monitors
+                .iter()
+                .find(|(previous_id, _, _)| previous_id == id)
+            {
+                Some((_, _, previous)) => {
+                    let fields = previous.changed_fields(snapshot);
+                    if !fields.is_empty() {
+                        events.push(MonitorEvent::Changed(monitor, fields));
+                    }
+                }
+                None => events.push(MonitorEvent::Added(monitor)),
+            }
+        }
+
+        self.monitors = monitors;
+
+        Ok(events)
+    }
+}
+
+/// Monitor watcher that diffs snapshots of `ImplMonitor::all`.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub(crate) struct PollingMonitorWatcher {
+    snapshots: MonitorSnapshots,
+    events: VecDeque<MonitorEvent>,
+}
+
+#[allow(dead_code)]
+impl PollingMonitorWatcher {
+    pub fn new() -> XCapResult<PollingMonitorWatcher> {
+        Ok(PollingMonitorWatcher {
+            snapshots: MonitorSnapshots::new()?,
+            events: VecDeque::new(),
+        })
+    }
+
+    pub fn next_event(&mut self) -> XCapResult<MonitorEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+
+            thread::sleep(POLL_INTERVAL);
+            self.events.extend(self.snapshots.update()?);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_changed_fields() {
+        let snapshot = MonitorSnapshot {
+            position: (0, 0),
+            size: (1920, 1080),
+            rotation: 0.0,
+            scale_factor: 1.0,
+            frequency: 60.0,
+            is_primary: true,
+        };
+        assert!(snapshot.changed_fields(&snapshot.clone()).is_empty());
+
+        let rotated = MonitorSnapshot {
+            size: (1080, 1920),
+            rotation: 90.0,
+            is_primary: false,
+            ..snapshot.clone()
+        };
+        assert_eq!(
+            snapshot.changed_fields(&rotated),
+            vec![
+                MonitorField::Size,
+                MonitorField::Rotation,
+                MonitorField::Primary
+            ]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use std::{path::PathBuf, time::SystemTime};
+
+/// Details of the process owning a window.
+///
+/// Fields the platform does not expose, or the current user is not allowed to read, are empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// Executable name, e.g. `firefox`.
+    pub name: String,
+    pub exe_path: Option<PathBuf>,
+    /// Command line arguments, the first one is usually the program itself.
+    pub cmdline: Vec<String>,
+    pub start_time: Option<SystemTime>,
+}
@@ -0,0 +1,308 @@
+use image::{Rgba, RgbaImage};
+
+use crate::{Window, error::XCapResult, image_diff::Region};
+
+/// Selects windows whose content is redacted from monitor captures.
+#[derive(Debug, Clone)]
+pub enum WindowFilter {
+    Id(u32),
+    Pid(u32),
+    AppName(String),
+    /// Window title matched against a glob pattern, `*` matches any run of characters and `?` a
+    /// single one. Not a regular expression.
+    TitleGlob(String),
+    /// Window title matched against a regular expression.
+    #[cfg(feature = "regex")]
+    TitleMatches(regex::Regex),
+}
+
+impl PartialEq for WindowFilter {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WindowFilter::Id(a), WindowFilter::Id(b)) => a == b,
+            (WindowFilter::Pid(a), WindowFilter::Pid(b)) => a == b,
+            (WindowFilter::AppName(a), WindowFilter::AppName(b)) => a == b,
+            (WindowFilter::TitleGlob(a), WindowFilter::TitleGlob(b)) => a == b,
+            // Regex 没有实现 PartialEq，比较原始的表达式
+            #[cfg(feature = "regex")]
+            (WindowFilter::TitleMatches(a), WindowFilter::TitleMatches(b)) => {
+                a.as_str() == b.as_str()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for WindowFilter {}
+
+impl WindowFilter {
+    /// Errors reading the window property are returned, so callers decide whether to fail open or closed.
+    pub fn matches(&self, window: &Window) -> XCapResult<bool> {
+        let matched = match self {
+            WindowFilter::Id(id) => window.id()? == *id,
+            WindowFilter::Pid(pid) => window.pid()? == *pid,
+            WindowFilter::AppName(app_name) => window.app_name()? == *app_name,
+            WindowFilter::TitleGlob(pattern) => wildcard_match(pattern, &window.title()?),
+            #[cfg(feature = "regex")]
+            WindowFilter::TitleMatches(regex) => regex.is_match(&window.title()?),
+        };
+
+        Ok(matched)
+    }
+}
+
+/// How the visible parts of redacted windows are hidden.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedactionStyle {
+    Fill(Rgba<u8>),
+    /// Replace square blocks of this size with their average color.
+    Pixelate(u32),
+    /// Gaussian blur with this sigma, small values may leave large text readable.
+    Blur(f32),
+}
+
+impl Default for RedactionStyle {
+    fn default() -> Self {
+        RedactionStyle::Fill(Rgba([0, 0, 0, 255]))
+    }
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters and `?` a single one.
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // 上一个 * 的位置，以及它当前匹配到的文本位置
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Build a mask of the visible parts of the redacted windows.
+///
+/// `windows` are rectangles in image coordinates, topmost first, paired with whether the window
+/// is redacted. Windows on top hide the parts of redacted windows below them.
+fn visible_mask(width: u32, height: u32, windows: &[(i32, i32, u32, u32, bool)]) -> Vec<bool> {
+    let mut mask = vec![false; (width * height) as usize];
+
+    // 从最底层开始绘制，上层窗口覆盖下层窗口
+    for &(x, y, w, h, redacted) in windows.iter().rev() {
+        let left = x.clamp(0, width as i32) as u32;
+        let top = y.clamp(0, height as i32) as u32;
+        let right = (x + w as i32).clamp(0, width as i32) as u32;
+        let bottom = (y + h as i32).clamp(0, height as i32) as u32;
+
+        for row in top..bottom {
+            let start = (row * width) as usize;
+            mask[start + left as usize..start + right as usize].fill(redacted);
+        }
+    }
+
+    mask
+}
+
+fn mask_bounds(width: u32, height: u32, mask: &[bool]) -> Option<Region> {
+    let mut bounds: Option<Region> = None;
+    for y in 0..height {
+        let row = &mask[(y * width) as usize..((y + 1) * width) as usize];
+        let (Some(left), Some(right)) = (
+            row.iter().position(|value| *value),
+            row.iter().rposition(|value| *value),
+        ) else {
+            continue;
+        };
+
+        let region = Region::new(left as u32, y, (right - left + 1) as u32, 1);
+        bounds = Some(bounds.map_or(region, |bounds| bounds.union(&region)));
+    }
+
+    bounds
+}
+
+/// Redact the visible parts of the matching windows in `image`.
+pub(crate) fn redact_windows(
+    image: &mut RgbaImage,
+    windows: &[(i32, i32, u32, u32, bool)],
+    style: RedactionStyle,
+) {
+    let (width, height) = image.dimensions();
+    let mask = visible_mask(width, height, windows);
+    let Some(bounds) = mask_bounds(width, height, &mask) else {
+        return;
+    };
+    let is_masked = |x: u32, y: u32| mask[(y * width + x) as usize];
+
+    match style {
+        RedactionStyle::Fill(color) => {
+            for (x, y, pixel) in image.enumerate_pixels_mut() {
+                if is_masked(x, y) {
+                    *pixel = color;
+                }
+            }
+        }
+        RedactionStyle::Pixelate(block_size) => {
+            let block_size = block_size.max(1);
+            for block_y in (bounds.y..bounds.y + bounds.height).step_by(block_size as usize) {
+                for block_x in (bounds.x..bounds.x + bounds.width).step_by(block_size as usize) {
+                    let block = Region::new(
+                        block_x,
+                        block_y,
+                        block_size.min(width - block_x),
+                        block_size.min(height - block_y),
+                    );
+                    let pixels = || {
+                        (block.y..block.y + block.height).flat_map(move |y| {
+                            (block.x..block.x + block.width).map(move |x| (x, y))
+                        })
+                    };
+
+                    // 只取被遮挡窗口内的像素求平均，避免混入其它窗口的颜色
+                    let mut sum = [0u64; 4];
+                    let mut count = 0;
+                    for (x, y) in pixels().filter(|(x, y)| is_masked(*x, *y)) {
+                        for (sum, value) in sum.iter_mut().zip(image.get_pixel(x, y).0) {
+                            *sum += value as u64;
+                        }
+                        count += 1;
+                    }
+                    if count == 0 {
+                        continue;
+                    }
+
+                    let average = Rgba(sum.map(|sum| (sum / count) as u8));
+                    for (x, y) in pixels().filter(|(x, y)| is_masked(*x, *y)) {
+                        image.put_pixel(x, y, average);
+                    }
+                }
+            }
+        }
+        RedactionStyle::Blur(sigma) => {
+            let cropped =
+                image::imageops::crop_imm(image, bounds.x, bounds.y, bounds.width, bounds.height)
+                    .to_image();
+            let blurred = image::imageops::blur(&cropped, sigma);
+
+            for (x, y, pixel) in blurred.enumerate_pixels() {
+                if is_masked(bounds.x + x, bounds.y + y) {
+                    image.put_pixel(bounds.x + x, bounds.y + y, *pixel);
+                }
+            }
+        }
+    }
+}
+
+/// Redact the windows selected by `filters` from a capture of a monitor.
+///
+/// The monitor rectangle is given in the coordinate space of window geometry.
+pub(crate) fn redact_monitor_image(
+    image: &mut RgbaImage,
+    (monitor_x, monitor_y, monitor_width, monitor_height): (i32, i32, u32, u32),
+    filters: &[WindowFilter],
+    style: RedactionStyle,
+) -> XCapResult<()> {
+    if filters.is_empty() || monitor_width == 0 || monitor_height == 0 {
+        return Ok(());
+    }
+
+    let scale_x = image.width() as f32 / monitor_width as f32;
+    let scale_y = image.height() as f32 / monitor_height as f32;
+
+    let mut windows = Vec::new();
+    for window in Window::all()? {
+        // 读取失败时按需要遮挡处理，宁可多遮挡也不能泄露内容
+        let redacted = filters
+            .iter()
+            .any(|filter| filter.matches(&window).unwrap_or(true));
+
+        // 最小化、未映射或位于其他工作区的窗口不可见，既不需要遮挡也不会遮住其他窗口。
+        // 无法确定时，需要遮挡的窗口视为可见，其余窗口视为不可见，避免误把遮挡区域当成被覆盖
+        let is_viewable = window.impl_window.is_viewable().unwrap_or(redacted);
+        if !is_viewable {
+            continue;
+        }
+
+        // 需要遮挡的窗口位置未知时无法遮挡，直接返回错误；其余窗口跳过只会让遮挡范围变大
+        let geometry = (|| -> XCapResult<(i32, i32, u32, u32)> {
+            Ok((window.x()?, window.y()?, window.width()?, window.height()?))
+        })();
+        let (x, y, width, height) = match geometry {
+            Ok(geometry) => geometry,
+            Err(err) if redacted => return Err(err),
+            Err(_) => continue,
+        };
+
+        let left = ((x - monitor_x) as f32 * scale_x).floor() as i32;
+        let top = ((y - monitor_y) as f32 * scale_y).floor() as i32;
+        let right = ((x + width as i32 - monitor_x) as f32 * scale_x).ceil() as i32;
+        let bottom = ((y + height as i32 - monitor_y) as f32 * scale_y).ceil() as i32;
+
+        windows.push((
+            left,
+            top,
+            (right - left).max(0) as u32,
+            (bottom - top).max(0) as u32,
+            redacted,
+        ));
+    }
+
+    redact_windows(image, &windows, style);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*Password*", "My Password Manager"));
+        assert!(wildcard_match("Chat - ?", "Chat - 3"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("Chat - ?", "Chat - 12"));
+        assert!(!wildcard_match("*Vault", "Vault - Locked"));
+    }
+
+    #[test]
+    fn test_redact_windows() {
+        let white = Rgba([255, 255, 255, 255]);
+        let red = Rgba([255, 0, 0, 255]);
+        let mut image = RgbaImage::from_pixel(8, 8, white);
+
+        // 被遮挡窗口的右半部分被上层窗口覆盖
+        let windows = [(4, 0, 4, 8, false), (0, 0, 8, 4, true)];
+        redact_windows(&mut image, &windows, RedactionStyle::Fill(red));
+
+        assert_eq!(*image.get_pixel(0, 0), red);
+        assert_eq!(*image.get_pixel(3, 3), red);
+        assert_eq!(*image.get_pixel(4, 0), white);
+        assert_eq!(*image.get_pixel(0, 4), white);
+
+        let mut image = RgbaImage::from_fn(4, 4, |x, _| Rgba([(x * 60) as u8, 0, 0, 255]));
+        redact_windows(
+            &mut image,
+            &[(-2, 0, 4, 4, true)],
+            RedactionStyle::Pixelate(2),
+        );
+        assert_eq!(*image.get_pixel(0, 0), Rgba([30, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 3), Rgba([30, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(2, 0), Rgba([120, 0, 0, 255]));
+    }
+}
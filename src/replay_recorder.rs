@@ -0,0 +1,424 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{BufWriter, Cursor, Write},
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use image::{RgbaImage, imageops::FilterType};
+
+use crate::{
+    Monitor, VideoRecorder,
+    error::{XCapError, XCapResult},
+    image_file::{Compression, ImageFormat, SaveOptions, encode_image, save_image},
+    perceptual_hash::{SimilarityFilter, SimilarityOptions},
+    pixel_format::PixelFormat,
+    thumbnail::downscale_pixels,
+    video_recorder::Frame,
+};
+
+/// Largest downscale factor used to fit a frame into the byte budget.
+const MAX_DOWNSCALE: u32 = 8;
+
+#[derive(Debug, Clone)]
+pub struct ReplayRecorderOptions {
+    /// How much recording is kept in the buffer.
+    pub duration: Duration,
+    /// Upper bound of the memory used by buffered frames.
+    pub max_bytes: usize,
+    /// Frames arriving faster than this are dropped.
+    pub frame_rate: f32,
+    /// Store frames PNG compressed instead of raw RGBA, trades CPU for memory.
+    pub compress: bool,
+    /// Drop frames that look like the previous buffered frame, `None` keeps every frame.
+    pub skip_similar: Option<SimilarityOptions>,
+}
+
+impl Default for ReplayRecorderOptions {
+    fn default() -> Self {
+        ReplayRecorderOptions {
+            duration: Duration::from_secs(30),
+            max_bytes: 512 * 1024 * 1024,
+            frame_rate: 10.0,
+            compress: false,
+            skip_similar: None,
+        }
+    }
+}
+
+/// Output of [`ReplayRecorder::dump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayFormat {
+    /// One image per frame in the target directory, named `frame-000000.<ext>`.
+    ImageSequence(ImageFormat),
+    /// A single YUV4MPEG2 (4:4:4) file, playable with ffmpeg/mpv.
+    Y4m,
+}
+
+#[derive(Debug)]
+enum FrameData {
+    Raw(Vec<u8>),
+    Png(Vec<u8>),
+}
+
+#[derive(Debug)]
+struct BufferedFrame {
+    timestamp: Instant,
+    width: u32,
+    height: u32,
+    data: FrameData,
+}
+
+impl BufferedFrame {
+    fn size(&self) -> usize {
+        match &self.data {
+            FrameData::Raw(data) | FrameData::Png(data) => data.len(),
+        }
+    }
+
+    fn to_image(&self) -> XCapResult<RgbaImage> {
+        match &self.data {
+            FrameData::Raw(data) => RgbaImage::from_raw(self.width, self.height, data.clone())
+                .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed")),
+            FrameData::Png(data) => {
+                let image = image::load_from_memory_with_format(data, image::ImageFormat::Png)?;
+                Ok(image.to_rgba8())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplayBuffer {
+    frames: VecDeque<Arc<BufferedFrame>>,
+    bytes: usize,
+}
+
+impl ReplayBuffer {
+    fn push(&mut self, frame: BufferedFrame, options: &ReplayRecorderOptions) {
+        self.bytes += frame.size();
+        let timestamp = frame.timestamp;
+        self.frames.push_back(Arc::new(frame));
+
+        while let Some(front) = self.frames.front() {
+            let is_expired = timestamp.duration_since(front.timestamp) > options.duration;
+            if !is_expired && self.bytes <= options.max_bytes {
+                break;
+            }
+
+            self.bytes -= front.size();
+            self.frames.pop_front();
+        }
+    }
+}
+
+fn downscale_factor(width: u32, height: u32, bytes_per_pixel: f64, budget: usize) -> u32 {
+    let mut factor = 1;
+    while factor < MAX_DOWNSCALE {
+        let pixels = (width / factor).max(1) as f64 * (height / factor).max(1) as f64;
+        if pixels * bytes_per_pixel <= budget as f64 {
+            break;
+        }
+        factor *= 2;
+    }
+
+    factor
+}
+
+// `compression_ratio` 是上一帧 PNG 压缩后每像素的字节数，用来预估缩放系数，
+// 这样每帧只需要编码一次
+fn buffer_frame(
+    frame: Frame,
+    budget: usize,
+    compress: bool,
+    compression_ratio: &mut f64,
+) -> XCapResult<BufferedFrame> {
+    let timestamp = Instant::now();
+    let frame = frame.convert(PixelFormat::Rgba);
+    let bytes_per_pixel = if compress { *compression_ratio } else { 4.0 };
+    let factor = downscale_factor(frame.width, frame.height, bytes_per_pixel, budget);
+
+    let (width, height, pixels) = if factor == 1 {
+        (frame.width, frame.height, frame.raw)
+    } else {
+        let dst_width = (frame.width / factor).max(1);
+        let dst_height = (frame.height / factor).max(1);
+        let image = downscale_pixels(
+            frame.width,
+            frame.height,
+            PixelFormat::Rgba,
+            &frame.raw,
+            dst_width,
+            dst_height,
+        );
+        (dst_width, dst_height, image.into_raw())
+    };
+
+    let data = if compress {
+        let image = RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))?;
+        let options = SaveOptions {
+            compression: Compression::Fast,
+            ..SaveOptions::default()
+        };
+
+        let mut buffer = Cursor::new(Vec::new());
+        encode_image(&image, &mut buffer, ImageFormat::Png, &options)?;
+        let data = buffer.into_inner();
+        *compression_ratio = data.len() as f64 / (width as f64 * height as f64);
+        FrameData::Png(data)
+    } else {
+        FrameData::Raw(pixels)
+    };
+
+    Ok(BufferedFrame {
+        timestamp,
+        width,
+        height,
+        data,
+    })
+}
+
+fn record_frames(
+    receiver: Receiver<Frame>,
+    buffer: Arc<Mutex<ReplayBuffer>>,
+    options: ReplayRecorderOptions,
+    running: Arc<AtomicBool>,
+) {
+    let interval = Duration::from_secs_f32(1.0 / options.frame_rate.max(0.1));
+    let frame_count = (options.duration.as_secs_f32() * options.frame_rate).ceil() as usize;
+    let budget = options.max_bytes / frame_count.max(1);
+    let mut last_frame: Option<Instant> = None;
+    let mut similarity_filter = options.skip_similar.map(SimilarityFilter::new);
+    let mut compression_ratio = 4.0;
+
+    while running.load(Ordering::Acquire) {
+        let frame = match receiver.recv_timeout(Duration::from_millis(200)) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        if let Some(last_frame) = last_frame
+            && last_frame.elapsed() < interval
+        {
+            continue;
+        }
+        last_frame = Some(Instant::now());
+
+        if let Some(similarity_filter) = &mut similarity_filter
+            && similarity_filter.is_similar_frame(&frame)
+        {
+            continue;
+        }
+
+        let result = buffer_frame(frame, budget, options.compress, &mut compression_ratio)
+            .and_then(|frame| {
+                buffer.lock()?.push(frame, &options);
+                Ok(())
+            });
+
+        if let Err(err) = result {
+            log::error!("Failed to buffer frame: {err:?}");
+        }
+    }
+}
+
+fn write_y4m<P: AsRef<Path>>(
+    path: P,
+    frames: &[Arc<BufferedFrame>],
+    frame_rate: f32,
+) -> XCapResult<()> {
+    let first = frames
+        .first()
+        .ok_or_else(|| XCapError::new("Replay buffer is empty"))?;
+    let (width, height) = (first.width, first.height);
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(
+        writer,
+        "YUV4MPEG2 W{width} H{height} F{}:1000 Ip A1:1 C444",
+        (frame_rate * 1000.0).round() as u32
+    )?;
+
+    let plane_size = (width * height) as usize;
+    let mut planes = vec![0u8; plane_size * 3];
+
+    for (index, frame) in frames.iter().enumerate() {
+        let mut image = frame.to_image()?;
+        // 缩放系数可能随帧变化，统一到第一帧的尺寸
+        if image.dimensions() != (width, height) {
+            image = image::imageops::resize(&image, width, height, FilterType::Triangle);
+        }
+
+        let (y_plane, uv_planes) = planes.split_at_mut(plane_size);
+        let (u_plane, v_plane) = uv_planes.split_at_mut(plane_size);
+
+        // BT.601 limited range
+        for (index, rgba) in image.as_raw().chunks_exact(4).enumerate() {
+            let (r, g, b) = (rgba[0] as i32, rgba[1] as i32, rgba[2] as i32);
+            y_plane[index] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+            u_plane[index] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+            v_plane[index] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+
+        // 节流和相似帧过滤会丢帧，按时间戳重复写入，保持播放时长与录制一致
+        let repeat = frames
+            .get(index + 1)
+            .map(|next| {
+                let elapsed = next.timestamp.duration_since(frame.timestamp);
+                (elapsed.as_secs_f32() * frame_rate).round() as usize
+            })
+            .unwrap_or(1)
+            .max(1);
+
+        for _ in 0..repeat {
+            writer.write_all(b"FRAME\n")?;
+            writer.write_all(&planes)?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Keeps the most recent frames of a monitor in memory, so that they can be
+/// written to disk after something interesting happened.
+#[derive(Debug)]
+pub struct ReplayRecorder {
+    video_recorder: VideoRecorder,
+    buffer: Arc<Mutex<ReplayBuffer>>,
+    options: ReplayRecorderOptions,
+    running: Arc<AtomicBool>,
+}
+
+impl ReplayRecorder {
+    pub fn new(monitor: &Monitor, options: ReplayRecorderOptions) -> XCapResult<ReplayRecorder> {
+        let (video_recorder, receiver) = monitor.video_recorder()?;
+        let buffer = Arc::new(Mutex::new(ReplayBuffer::default()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        {
+            let buffer = buffer.clone();
+            let options = options.clone();
+            let running = running.clone();
+            thread::spawn(move || record_frames(receiver, buffer, options, running));
+        }
+
+        Ok(ReplayRecorder {
+            video_recorder,
+            buffer,
+            options,
+            running,
+        })
+    }
+}
+
+impl ReplayRecorder {
+    pub fn start(&self) -> XCapResult<()> {
+        self.video_recorder.start()
+    }
+
+    pub fn stop(&self) -> XCapResult<()> {
+        self.video_recorder.stop()
+    }
+
+    /// Number of buffered frames.
+    pub fn len(&self) -> XCapResult<usize> {
+        Ok(self.buffer.lock()?.frames.len())
+    }
+
+    pub fn is_empty(&self) -> XCapResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Memory used by buffered frames in bytes.
+    pub fn buffered_bytes(&self) -> XCapResult<usize> {
+        Ok(self.buffer.lock()?.bytes)
+    }
+
+    pub fn clear(&self) -> XCapResult<()> {
+        let mut buffer = self.buffer.lock()?;
+        buffer.frames.clear();
+        buffer.bytes = 0;
+
+        Ok(())
+    }
+
+    /// Write the buffered frames to `path` and return the number of frames written.
+    ///
+    /// `path` is a directory for image sequences and a file for Y4M. Recording
+    /// keeps going while the frames are written.
+    pub fn dump<P: AsRef<Path>>(&self, path: P, format: ReplayFormat) -> XCapResult<usize> {
+        let frames: Vec<Arc<BufferedFrame>> = self.buffer.lock()?.frames.iter().cloned().collect();
+
+        match format {
+            ReplayFormat::ImageSequence(image_format) => {
+                let extension = match image_format {
+                    ImageFormat::Png => "png",
+                    ImageFormat::Jpeg => "jpg",
+                    ImageFormat::WebP => "webp",
+                    ImageFormat::Qoi => "qoi",
+                    ImageFormat::Bmp => "bmp",
+                    ImageFormat::Tiff => "tiff",
+                };
+                let options = SaveOptions {
+                    format: Some(image_format),
+                    ..SaveOptions::default()
+                };
+
+                fs::create_dir_all(&path)?;
+                for (index, frame) in frames.iter().enumerate() {
+                    let filename = path.as_ref().join(format!("frame-{index:06}.{extension}"));
+                    save_image(&frame.to_image()?, filename, &options)?;
+                }
+            }
+            ReplayFormat::Y4m => write_y4m(path, &frames, self.options.frame_rate)?,
+        }
+
+        Ok(frames.len())
+    }
+}
+
+impl Drop for ReplayRecorder {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Err(err) = self.video_recorder.stop() {
+            log::error!("Failed to stop replay recorder: {err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_buffer_limits() {
+        let options = ReplayRecorderOptions {
+            duration: Duration::from_secs(60),
+            max_bytes: 32,
+            ..ReplayRecorderOptions::default()
+        };
+
+        let mut buffer = ReplayBuffer::default();
+        for _ in 0..5 {
+            let frame = buffer_frame(Frame::new(2, 1, vec![0; 8]), 8, false, &mut 4.0).unwrap();
+            buffer.push(frame, &options);
+        }
+        assert_eq!(buffer.frames.len(), 4);
+        assert_eq!(buffer.bytes, 32);
+
+        // 超出预算的帧会被缩小
+        let frame = buffer_frame(Frame::new(4, 4, vec![0; 64]), 16, false, &mut 4.0).unwrap();
+        assert_eq!((frame.width, frame.height), (2, 2));
+    }
+}
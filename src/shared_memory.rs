@@ -0,0 +1,578 @@
+use std::{
+    ffi::CString,
+    io, ptr,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering, fence},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::{XCapError, XCapResult},
+    pixel_format::PixelFormat,
+    video_recorder::Frame,
+};
+
+const MAGIC: u32 = u32::from_le_bytes(*b"XCSH");
+const VERSION: u32 = 1;
+const PAGE_SIZE: usize = 4096;
+
+#[repr(C)]
+struct ShmHeader {
+    magic: AtomicU32,
+    version: AtomicU32,
+    slot_count: AtomicU32,
+    /// Futex word, incremented on every publish.
+    notify: AtomicU32,
+    slot_size: AtomicU64,
+    /// Sequence of the latest published frame, 0 when nothing was published.
+    sequence: AtomicU64,
+    closed: AtomicU32,
+}
+
+#[repr(C)]
+struct SlotHeader {
+    /// Sequence of the frame in the slot, 0 while it is being written.
+    sequence: AtomicU64,
+    timestamp: AtomicU64,
+    size: AtomicU64,
+    width: AtomicU32,
+    height: AtomicU32,
+    stride: AtomicU32,
+    format: AtomicU32,
+}
+
+fn align_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+/// Byte offsets inside the shared memory object.
+struct Layout {
+    slot_count: usize,
+    slot_size: usize,
+}
+
+impl Layout {
+    fn slot_header_offset(&self, index: usize) -> usize {
+        align_up(size_of::<ShmHeader>(), 64) + index * align_up(size_of::<SlotHeader>(), 64)
+    }
+
+    fn slot_data_offset(&self, index: usize) -> usize {
+        align_up(self.slot_header_offset(self.slot_count), PAGE_SIZE)
+            + index * align_up(self.slot_size, PAGE_SIZE)
+    }
+
+    fn len(&self) -> usize {
+        self.slot_data_offset(self.slot_count)
+    }
+}
+
+fn shm_name(name: &str) -> XCapResult<CString> {
+    let name = name.strip_prefix('/').unwrap_or(name);
+    if name.is_empty() || name.contains('/') {
+        return Err(XCapError::new(format!(
+            "Invalid shared memory name: {name}"
+        )));
+    }
+
+    CString::new(format!("/{name}")).map_err(|err| XCapError::new(err.to_string()))
+}
+
+/// A mapped shared memory object.
+struct SharedMemory {
+    ptr: *mut u8,
+    len: usize,
+    layout: Layout,
+}
+
+unsafe impl Send for SharedMemory {}
+
+impl SharedMemory {
+    fn map(fd: libc::c_int, len: usize) -> XCapResult<*mut u8> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(ptr.cast())
+    }
+
+    fn create(name: &CString, layout: Layout) -> XCapResult<SharedMemory> {
+        let len = layout.len();
+
+        unsafe {
+            // O_EXCL 保证对象由当前发布者创建，不会截断或在 Drop 时删除其他发布者的对象
+            let fd = libc::shm_open(
+                name.as_ptr(),
+                libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+                0o600,
+            );
+            if fd < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::AlreadyExists {
+                    return Err(XCapError::new(format!(
+                        "Shared memory object {name:?} already exists"
+                    )));
+                }
+                return Err(err.into());
+            }
+            let fd = scopeguard::guard(fd, |fd| {
+                libc::close(fd);
+            });
+
+            let ptr = if libc::ftruncate(*fd, len as libc::off_t) != 0 {
+                Err(io::Error::last_os_error().into())
+            } else {
+                SharedMemory::map(*fd, len)
+            };
+            let ptr = ptr.inspect_err(|_| {
+                libc::shm_unlink(name.as_ptr());
+            })?;
+
+            Ok(SharedMemory { ptr, len, layout })
+        }
+    }
+
+    fn open(name: &CString) -> XCapResult<SharedMemory> {
+        unsafe {
+            let fd = libc::shm_open(name.as_ptr(), libc::O_RDWR, 0);
+            if fd < 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            let fd = scopeguard::guard(fd, |fd| {
+                libc::close(fd);
+            });
+
+            let mut stat: libc::stat = std::mem::zeroed();
+            if libc::fstat(*fd, &mut stat) != 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+
+            let len = stat.st_size as usize;
+            if len < size_of::<ShmHeader>() {
+                return Err(XCapError::new("Shared memory object is too small"));
+            }
+
+            let ptr = SharedMemory::map(*fd, len)?;
+            let mut shared_memory = SharedMemory {
+                ptr,
+                len,
+                layout: Layout {
+                    slot_count: 0,
+                    slot_size: 0,
+                },
+            };
+
+            let header = shared_memory.header();
+            if header.magic.load(Ordering::Acquire) != MAGIC
+                || header.version.load(Ordering::Relaxed) != VERSION
+            {
+                return Err(XCapError::new(
+                    "Shared memory object is not an xcap frame buffer",
+                ));
+            }
+
+            shared_memory.layout = Layout {
+                slot_count: header.slot_count.load(Ordering::Relaxed) as usize,
+                slot_size: header.slot_size.load(Ordering::Relaxed) as usize,
+            };
+            if shared_memory.layout.slot_count == 0 || shared_memory.layout.len() > len {
+                return Err(XCapError::new("Shared memory object has an invalid layout"));
+            }
+
+            Ok(shared_memory)
+        }
+    }
+
+    fn header(&self) -> &ShmHeader {
+        unsafe { &*(self.ptr as *const ShmHeader) }
+    }
+
+    fn slot_header(&self, index: usize) -> &SlotHeader {
+        unsafe { &*(self.ptr.add(self.layout.slot_header_offset(index)) as *const SlotHeader) }
+    }
+
+    fn slot_data(&self, index: usize) -> *mut u8 {
+        unsafe { self.ptr.add(self.layout.slot_data_offset(index)) }
+    }
+
+    fn wake(&self) {
+        let notify = &self.header().notify;
+        notify.fetch_add(1, Ordering::Release);
+
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                notify.as_ptr(),
+                libc::FUTEX_WAKE,
+                i32::MAX,
+                ptr::null::<libc::timespec>(),
+                ptr::null::<u32>(),
+                0,
+            );
+        }
+    }
+
+    /// Block until `notify` no longer equals `expected` or `timeout` passed.
+    fn wait(&self, expected: u32, timeout: Option<Duration>) {
+        let timespec = timeout.map(|timeout| libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        });
+
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                self.header().notify.as_ptr(),
+                libc::FUTEX_WAIT,
+                expected,
+                timespec
+                    .as_ref()
+                    .map_or(ptr::null(), |timespec| timespec as *const libc::timespec),
+                ptr::null::<u32>(),
+                0,
+            );
+        }
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.cast(), self.len);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShmPublisherOptions {
+    /// Name subscribers attach to, a POSIX shared memory name without the leading `/`.
+    pub name: String,
+    /// Number of frames kept in the ring buffer.
+    pub slot_count: u32,
+    /// Largest frame in bytes, defaults to a 4K RGBA frame.
+    pub slot_size: usize,
+}
+
+impl Default for ShmPublisherOptions {
+    fn default() -> Self {
+        ShmPublisherOptions {
+            name: String::from("xcap"),
+            slot_count: 4,
+            slot_size: 3840 * 2160 * 4,
+        }
+    }
+}
+
+/// Publishes frames into a named shared memory ring buffer that other
+/// processes read with [`ShmSubscriber`] without copying through a pipe.
+pub struct ShmPublisher {
+    name: CString,
+    shared_memory: SharedMemory,
+}
+
+impl std::fmt::Debug for ShmPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShmPublisher")
+            .field("name", &self.name)
+            .field("slot_count", &self.shared_memory.layout.slot_count)
+            .field("slot_size", &self.shared_memory.layout.slot_size)
+            .finish()
+    }
+}
+
+impl ShmPublisher {
+    pub fn new(options: ShmPublisherOptions) -> XCapResult<ShmPublisher> {
+        if options.slot_count == 0 || options.slot_size == 0 {
+            return Err(XCapError::new("slot_count and slot_size must not be zero"));
+        }
+
+        let name = shm_name(&options.name)?;
+        let shared_memory = SharedMemory::create(
+            &name,
+            Layout {
+                slot_count: options.slot_count as usize,
+                slot_size: options.slot_size,
+            },
+        )?;
+
+        let header = shared_memory.header();
+        header
+            .slot_count
+            .store(options.slot_count, Ordering::Relaxed);
+        header
+            .slot_size
+            .store(options.slot_size as u64, Ordering::Relaxed);
+        header.version.store(VERSION, Ordering::Relaxed);
+        header.magic.store(MAGIC, Ordering::Release);
+
+        Ok(ShmPublisher {
+            name,
+            shared_memory,
+        })
+    }
+
+    /// Copy `frame` into the next slot and wake subscribers, returns the frame sequence.
+    pub fn publish(&self, frame: &Frame) -> XCapResult<u64> {
+        let layout = &self.shared_memory.layout;
+        if frame.raw.len() > layout.slot_size {
+            return Err(XCapError::new(format!(
+                "Frame of {} bytes does not fit into slots of {} bytes",
+                frame.raw.len(),
+                layout.slot_size
+            )));
+        }
+
+        let header = self.shared_memory.header();
+        let sequence = header.sequence.load(Ordering::Relaxed) + 1;
+        let index = (sequence % layout.slot_count as u64) as usize;
+        let slot = self.shared_memory.slot_header(index);
+
+        slot.sequence.store(0, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        unsafe {
+            ptr::copy_nonoverlapping(
+                frame.raw.as_ptr(),
+                self.shared_memory.slot_data(index),
+                frame.raw.len(),
+            );
+        }
+        slot.timestamp
+            .store(timestamp.as_nanos() as u64, Ordering::Relaxed);
+        slot.size.store(frame.raw.len() as u64, Ordering::Relaxed);
+        slot.width.store(frame.width, Ordering::Relaxed);
+        slot.height.store(frame.height, Ordering::Relaxed);
+        slot.stride.store(
+            frame.width * frame.format.bytes_per_pixel() as u32,
+            Ordering::Relaxed,
+        );
+        slot.format
+            .store(u32::from_le_bytes(frame.format.fourcc()), Ordering::Relaxed);
+
+        slot.sequence.store(sequence, Ordering::Release);
+        header.sequence.store(sequence, Ordering::Release);
+        self.shared_memory.wake();
+
+        Ok(sequence)
+    }
+}
+
+impl Drop for ShmPublisher {
+    fn drop(&mut self) {
+        self.shared_memory
+            .header()
+            .closed
+            .store(1, Ordering::Release);
+        self.shared_memory.wake();
+
+        unsafe {
+            libc::shm_unlink(self.name.as_ptr());
+        }
+    }
+}
+
+/// A frame copied out of the shared memory ring buffer.
+#[derive(Debug, Clone)]
+pub struct ShmFrame {
+    pub sequence: u64,
+    /// Time since the unix epoch when the frame was published.
+    pub timestamp: Duration,
+    pub width: u32,
+    pub height: u32,
+    pub stride: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+impl ShmFrame {
+    pub fn into_frame(self) -> Frame {
+        Frame::new_with_format(self.width, self.height, self.format, self.data)
+    }
+}
+
+/// Reads frames published by a [`ShmPublisher`], possibly in another process.
+pub struct ShmSubscriber {
+    shared_memory: SharedMemory,
+    next_sequence: u64,
+}
+
+impl std::fmt::Debug for ShmSubscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShmSubscriber")
+            .field("next_sequence", &self.next_sequence)
+            .finish()
+    }
+}
+
+impl ShmSubscriber {
+    /// Attach to the publisher with the given name, frames published from now on are received.
+    pub fn attach(name: &str) -> XCapResult<ShmSubscriber> {
+        let shared_memory = SharedMemory::open(&shm_name(name)?)?;
+        let next_sequence = shared_memory.header().sequence.load(Ordering::Acquire) + 1;
+
+        Ok(ShmSubscriber {
+            shared_memory,
+            next_sequence,
+        })
+    }
+
+    /// Read the next frame if it is available, skipping frames that were
+    /// overwritten before they could be read.
+    fn try_read(&mut self) -> Option<ShmFrame> {
+        let layout = &self.shared_memory.layout;
+        let slot_count = layout.slot_count as u64;
+
+        loop {
+            let published = self.shared_memory.header().sequence.load(Ordering::Acquire);
+            if published < self.next_sequence {
+                return None;
+            }
+            if published - self.next_sequence >= slot_count {
+                self.next_sequence = published + 1 - slot_count;
+            }
+
+            let sequence = self.next_sequence;
+            self.next_sequence += 1;
+
+            let index = (sequence % slot_count) as usize;
+            let slot = self.shared_memory.slot_header(index);
+            if slot.sequence.load(Ordering::Acquire) != sequence {
+                continue;
+            }
+
+            let size = (slot.size.load(Ordering::Relaxed) as usize).min(layout.slot_size);
+            let mut data = vec![0u8; size];
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.shared_memory.slot_data(index),
+                    data.as_mut_ptr(),
+                    size,
+                );
+            }
+            let frame = ShmFrame {
+                sequence,
+                timestamp: Duration::from_nanos(slot.timestamp.load(Ordering::Relaxed)),
+                width: slot.width.load(Ordering::Relaxed),
+                height: slot.height.load(Ordering::Relaxed),
+                stride: slot.stride.load(Ordering::Relaxed),
+                format: PixelFormat::from_fourcc(slot.format.load(Ordering::Relaxed).to_le_bytes())
+                    .unwrap_or_default(),
+                data,
+            };
+
+            // 复制期间被发布者覆盖则数据可能不完整，丢弃这一帧
+            fence(Ordering::Acquire);
+            if slot.sequence.load(Ordering::Relaxed) == sequence {
+                return Some(frame);
+            }
+        }
+    }
+
+    /// Wait for the next frame and copy it out together with its metadata.
+    ///
+    /// Returns `Ok(None)` on timeout. A frame overwritten by the publisher while
+    /// it was copied is dropped and a newer one is read instead.
+    pub fn recv_frame(&mut self, timeout: Option<Duration>) -> XCapResult<Option<ShmFrame>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            let notify = self.shared_memory.header().notify.load(Ordering::Acquire);
+
+            if let Some(frame) = self.try_read() {
+                return Ok(Some(frame));
+            }
+
+            if self.shared_memory.header().closed.load(Ordering::Acquire) != 0 {
+                return Err(XCapError::new("Shared memory publisher closed"));
+            }
+
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+
+            self.shared_memory.wait(notify, timeout);
+        }
+    }
+
+    /// Block until the next frame is published and copy it out.
+    pub fn recv(&mut self) -> XCapResult<Frame> {
+        self.recv_frame(None)?
+            .map(ShmFrame::into_frame)
+            .ok_or_else(|| XCapError::new("Shared memory receive failed"))
+    }
+
+    pub fn recv_timeout(&mut self, timeout: Duration) -> XCapResult<Option<Frame>> {
+        Ok(self.recv_frame(Some(timeout))?.map(ShmFrame::into_frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_and_receive() {
+        let name = format!("xcap-test-{}", std::process::id());
+        let publisher = ShmPublisher::new(ShmPublisherOptions {
+            name: name.clone(),
+            slot_count: 2,
+            slot_size: 16,
+        })
+        .unwrap();
+        let mut subscriber = ShmSubscriber::attach(&name).unwrap();
+
+        assert!(
+            subscriber
+                .recv_timeout(Duration::from_millis(10))
+                .unwrap()
+                .is_none()
+        );
+
+        for value in 1..=3 {
+            publisher
+                .publish(&Frame::new(2, 1, vec![value; 8]))
+                .unwrap();
+        }
+        assert!(publisher.publish(&Frame::new(4, 2, vec![0; 32])).is_err());
+
+        // 槽位只有两个，第一帧已被覆盖
+        let frame = subscriber.recv().unwrap();
+        assert_eq!((frame.width, frame.height), (2, 1));
+        assert_eq!(frame.raw, vec![2; 8]);
+
+        let frame = subscriber.recv_frame(None).unwrap().unwrap();
+        assert_eq!(frame.format, PixelFormat::Rgba);
+        assert_eq!(frame.sequence, 3);
+
+        // 同名对象已存在时不能再创建
+        assert!(
+            ShmPublisher::new(ShmPublisherOptions {
+                name: name.clone(),
+                ..ShmPublisherOptions::default()
+            })
+            .is_err()
+        );
+
+        drop(publisher);
+        assert!(subscriber.recv().is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use image::RgbaImage;
+
+use crate::pixel_format::{PixelFormat, read_pixel};
+
+/// The largest size with the aspect ratio of `width` x `height` that fits in
+/// `max_width` x `max_height`, images are never enlarged.
+pub(crate) fn thumbnail_size(
+    width: u32,
+    height: u32,
+    max_width: u32,
+    max_height: u32,
+) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+
+    let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64);
+
+    (
+        ((width as f64 * scale).round() as u32).clamp(1, max_width.max(1)),
+        ((height as f64 * scale).round() as u32).clamp(1, max_height.max(1)),
+    )
+}
+
+/// Box filter downscale, `read_pixel` returns the RGBA value of a source pixel.
+///
+/// Source rows are read once and in order, so the pixels can be converted from the native
+/// format while downscaling instead of building a full size image first.
+pub(crate) fn box_downscale<F>(
+    width: u32,
+    height: u32,
+    dst_width: u32,
+    dst_height: u32,
+    read_pixel: F,
+) -> RgbaImage
+where
+    F: Fn(u32, u32) -> [u8; 4],
+{
+    let mut dst = RgbaImage::new(dst_width, dst_height);
+    if width == 0 || height == 0 || dst_width == 0 || dst_height == 0 {
+        return dst;
+    }
+
+    // 每个源像素列对应的目标列，以及每个目标列覆盖的源像素数
+    let columns: Vec<usize> = (0..width)
+        .map(|x| (x as u64 * dst_width as u64 / width as u64) as usize)
+        .collect();
+    let mut column_counts = vec![0u64; dst_width as usize];
+    for column in &columns {
+        column_counts[*column] += 1;
+    }
+
+    // 一个目标像素可能覆盖上千万个源像素，u32 会溢出
+    let mut sums = vec![[0u64; 4]; dst_width as usize];
+    let mut src_y = 0;
+    for dst_y in 0..dst_height {
+        let src_y_end = ((dst_y as u64 + 1) * height as u64 / dst_height as u64) as u32;
+        let rows = src_y_end - src_y;
+
+        for y in src_y..src_y_end {
+            for (x, column) in columns.iter().enumerate() {
+                for (sum, value) in sums[*column].iter_mut().zip(read_pixel(x as u32, y)) {
+                    *sum += value as u64;
+                }
+            }
+        }
+
+        for (dst_x, sum) in sums.iter_mut().enumerate() {
+            let count = (column_counts[dst_x] * rows as u64).max(1);
+            dst.put_pixel(
+                dst_x as u32,
+                dst_y,
+                image::Rgba(sum.map(|value| (value / count) as u8)),
+            );
+            *sum = [0; 4];
+        }
+
+        src_y = src_y_end;
+    }
+
+    dst
+}
+
+/// Downscale tightly packed pixels of any format to `dst_width` x `dst_height` RGBA pixels.
+pub(crate) fn downscale_pixels(
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    pixels: &[u8],
+    dst_width: u32,
+    dst_height: u32,
+) -> RgbaImage {
+    let bytes_per_pixel = format.bytes_per_pixel();
+
+    box_downscale(width, height, dst_width, dst_height, |x, y| {
+        let index = (y as usize * width as usize + x as usize) * bytes_per_pixel;
+        read_pixel(&pixels[index..index + bytes_per_pixel], format)
+    })
+}
+
+/// Thumbnail of an already captured image, for backends that can't capture at a reduced size.
+#[allow(dead_code)]
+pub(crate) fn thumbnail_image(image: RgbaImage, max_width: u32, max_height: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let (dst_width, dst_height) = thumbnail_size(width, height, max_width, max_height);
+    if (dst_width, dst_height) == (width, height) {
+        return image;
+    }
+
+    downscale_pixels(
+        width,
+        height,
+        PixelFormat::Rgba,
+        image.as_raw(),
+        dst_width,
+        dst_height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_size() {
+        assert_eq!(thumbnail_size(1920, 1080, 320, 320), (320, 180));
+        assert_eq!(thumbnail_size(1080, 1920, 320, 320), (180, 320));
+        assert_eq!(thumbnail_size(100, 50, 320, 320), (100, 50));
+        assert_eq!(thumbnail_size(10000, 1, 100, 100), (100, 1));
+    }
+
+    #[test]
+    fn test_downscale_pixels() {
+        let pixels = [
+            0, 0, 0, 255, 100, 100, 100, 255, //
+            200, 200, 200, 255, 100, 100, 100, 255,
+        ];
+
+        let image = downscale_pixels(2, 2, PixelFormat::Rgba, &pixels, 1, 1);
+        assert_eq!(image.as_raw(), &vec![100, 100, 100, 255]);
+
+        // 3 列缩小到 2 列，每列至少覆盖一个源像素
+        let pixels = [30, 60, 90];
+        let image = downscale_pixels(3, 1, PixelFormat::Gray, &pixels, 2, 1);
+        assert_eq!(image.as_raw(), &vec![45, 45, 45, 255, 90, 90, 90, 255]);
+    }
+
+    #[test]
+    fn test_box_downscale_large_source() {
+        // 一个目标像素覆盖 1700 多万个源像素，255 倍后超过 u32::MAX
+        let image = box_downscale(4200, 4100, 1, 1, |_, _| [255, 128, 1, 255]);
+        assert_eq!(image.as_raw(), &vec![255, 128, 1, 255]);
+    }
+}
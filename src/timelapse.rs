@@ -0,0 +1,418 @@
+use std::{
+    collections::{VecDeque, hash_map::DefaultHasher},
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use image::RgbaImage;
+
+use crate::{
+    Monitor, Window,
+    error::{XCapError, XCapResult},
+    image_file::{SaveOptions, save_image},
+    perceptual_hash::{SimilarityFilter, SimilarityOptions},
+};
+
+/// Something captured by a [`Timelapse`].
+#[derive(Debug, Clone)]
+pub enum TimelapseTarget {
+    Monitor(Monitor),
+    Window(Window),
+}
+
+impl From<Monitor> for TimelapseTarget {
+    fn from(monitor: Monitor) -> Self {
+        TimelapseTarget::Monitor(monitor)
+    }
+}
+
+impl From<Window> for TimelapseTarget {
+    fn from(window: Window) -> Self {
+        TimelapseTarget::Window(window)
+    }
+}
+
+impl TimelapseTarget {
+    fn name(&self) -> XCapResult<String> {
+        match self {
+            TimelapseTarget::Monitor(monitor) => monitor.name(),
+            // 同一个应用可能有多个窗口，加上窗口 id 避免文件名相同
+            TimelapseTarget::Window(window) => {
+                Ok(format!("{}-{}", window.app_name()?, window.id()?))
+            }
+        }
+    }
+
+    fn capture_image(&self) -> XCapResult<RgbaImage> {
+        match self {
+            TimelapseTarget::Monitor(monitor) => monitor.capture_image(),
+            TimelapseTarget::Window(window) => window.capture_image(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelapseOptions {
+    /// Time between two captures.
+    pub interval: Duration,
+    /// Directory the images are written to, created when missing.
+    pub directory: PathBuf,
+    /// File name template, supports `{timestamp}` (unix seconds), `{timestamp_ms}`,
+    /// `{name}` (monitor name, or window app name and id), `{index}` (position of the target in
+    /// the list) and `{sequence}`. All targets of one capture share the sequence, so templates
+    /// should keep `{name}` or `{index}` to tell them apart.
+    /// The image format is taken from the extension.
+    pub template: String,
+    pub save_options: SaveOptions,
+    /// Don't write an image when it is identical to the previous one of the same target.
+    pub skip_identical: bool,
+    /// Don't write an image when it looks like the previous written one of the same target.
+    pub skip_similar: Option<SimilarityOptions>,
+    /// Delete the oldest images once more than this many were written.
+    pub max_files: Option<usize>,
+    /// Delete the oldest images once they use more than this many bytes.
+    pub max_bytes: Option<u64>,
+}
+
+impl Default for TimelapseOptions {
+    fn default() -> Self {
+        TimelapseOptions {
+            interval: Duration::from_secs(60),
+            directory: PathBuf::from("timelapse"),
+            template: String::from("{timestamp}-{name}-{index}-{sequence}.png"),
+            save_options: SaveOptions::default(),
+            skip_identical: true,
+            skip_similar: None,
+            max_files: None,
+            max_bytes: None,
+        }
+    }
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    // `.` 和 `..` 会被当成当前目录和上级目录
+    if name.chars().all(|char| char == '.') {
+        return "_".repeat(name.len().max(1));
+    }
+
+    name.chars()
+        .map(|char| {
+            if char.is_alphanumeric() || matches!(char, '-' | '_' | '.') {
+                char
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn format_file_name(
+    template: &str,
+    timestamp: Duration,
+    name: &str,
+    index: usize,
+    sequence: u64,
+) -> String {
+    template
+        .replace("{timestamp_ms}", &timestamp.as_millis().to_string())
+        .replace("{timestamp}", &timestamp.as_secs().to_string())
+        .replace("{name}", &sanitize_file_name(name))
+        .replace("{index}", &index.to_string())
+        .replace("{sequence}", &format!("{sequence:06}"))
+}
+
+fn hash_image(image: &RgbaImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    image.as_raw().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Files written by the timelapse, oldest first.
+#[derive(Debug, Default)]
+struct Retention {
+    files: VecDeque<(PathBuf, u64)>,
+    bytes: u64,
+}
+
+impl Retention {
+    fn push(&mut self, path: PathBuf, size: u64, options: &TimelapseOptions) {
+        // 模板生成了相同的路径时旧文件已被覆盖，只保留最新的记录，避免淘汰旧记录时删除新文件
+        if let Some(position) = self.files.iter().position(|(file, _)| *file == path)
+            && let Some((_, old_size)) = self.files.remove(position)
+        {
+            self.bytes -= old_size;
+        }
+
+        self.files.push_back((path, size));
+        self.bytes += size;
+
+        loop {
+            let over_count = options
+                .max_files
+                .is_some_and(|max_files| self.files.len() > max_files);
+            let over_bytes = options
+                .max_bytes
+                .is_some_and(|max_bytes| self.bytes > max_bytes);
+            if !over_count && !over_bytes {
+                break;
+            }
+
+            let Some((path, size)) = self.files.pop_front() else {
+                break;
+            };
+            self.bytes -= size;
+
+            if let Err(err) = fs::remove_file(&path) {
+                log::error!("Failed to remove {path:?}: {err:?}");
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TimelapseState {
+    stopped: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl TimelapseState {
+    /// Sleep until `deadline`, returns false when the timelapse was stopped.
+    fn wait_until(&self, deadline: Instant) -> XCapResult<bool> {
+        let mut stopped = self.stopped.lock()?;
+        loop {
+            if *stopped {
+                return Ok(false);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(true);
+            }
+
+            stopped = self.condvar.wait_timeout(stopped, deadline - now)?.0;
+        }
+    }
+}
+
+struct TimelapseWorker {
+    targets: Vec<TimelapseTarget>,
+    options: TimelapseOptions,
+    hashes: Vec<Option<u64>>,
+    similarity_filters: Vec<Option<SimilarityFilter>>,
+    retention: Retention,
+    sequence: u64,
+}
+
+impl TimelapseWorker {
+    fn capture(&mut self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut written = false;
+
+        for (index, target) in self.targets.iter().enumerate() {
+            let result = target.capture_image().and_then(|image| {
+                if self.options.skip_identical {
+                    let hash = hash_image(&image);
+                    if self.hashes[index] == Some(hash) {
+                        return Ok(());
+                    }
+                    self.hashes[index] = Some(hash);
+                }
+
+                if let Some(similarity_filter) = &mut self.similarity_filters[index]
+                    && similarity_filter.is_similar(&image)
+                {
+                    return Ok(());
+                }
+
+                let file_name = format_file_name(
+                    &self.options.template,
+                    timestamp,
+                    &target.name()?,
+                    index,
+                    self.sequence,
+                );
+                let path = self.options.directory.join(file_name);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                save_image(&image, &path, &self.options.save_options)?;
+
+                let size = fs::metadata(&path)?.len();
+                self.retention.push(path, size, &self.options);
+                written = true;
+
+                Ok(())
+            });
+
+            if let Err(err) = result {
+                log::error!("Timelapse capture failed: {err:?}");
+            }
+        }
+
+        if written {
+            self.sequence += 1;
+        }
+    }
+
+    fn run(mut self, state: Arc<TimelapseState>) {
+        let interval = self.options.interval;
+        let start = Instant::now();
+        let mut tick: u32 = 0;
+
+        loop {
+            self.capture();
+
+            // 基于起始时间计算下一次截图时间，避免误差累积；落后时跳过错过的时间点
+            let elapsed = start.elapsed();
+            tick = tick.max((elapsed.as_nanos() / interval.as_nanos()) as u32) + 1;
+
+            match state.wait_until(start + interval * tick) {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(err) => {
+                    log::error!("Timelapse stopped: {err:?}");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Captures monitors or windows to image files at a fixed interval on a
+/// background thread.
+#[derive(Debug)]
+pub struct Timelapse {
+    state: Arc<TimelapseState>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Timelapse {
+    pub fn new<T: Into<TimelapseTarget>>(
+        targets: Vec<T>,
+        options: TimelapseOptions,
+    ) -> XCapResult<Timelapse> {
+        if options.interval.is_zero() {
+            return Err(XCapError::new("Timelapse interval must not be zero"));
+        }
+
+        fs::create_dir_all(&options.directory)?;
+
+        let targets: Vec<TimelapseTarget> = targets.into_iter().map(Into::into).collect();
+        let worker = TimelapseWorker {
+            hashes: vec![None; targets.len()],
+            similarity_filters: vec![
+                options.skip_similar.map(SimilarityFilter::new);
+                targets.len()
+            ],
+            targets,
+            options,
+            retention: Retention::default(),
+            sequence: 0,
+        };
+
+        let state = Arc::new(TimelapseState {
+            stopped: Mutex::new(false),
+            condvar: Condvar::new(),
+        });
+
+        let worker_state = state.clone();
+        let handle = thread::spawn(move || worker.run(worker_state));
+
+        Ok(Timelapse {
+            state,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stop capturing and wait for the capture in progress to finish.
+    pub fn stop(&mut self) -> XCapResult<()> {
+        *self.state.stopped.lock()? = true;
+        self.state.condvar.notify_all();
+
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|_| XCapError::new("Timelapse thread panicked"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Timelapse {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log::error!("Failed to stop timelapse: {err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_file_name() {
+        let timestamp = Duration::from_millis(1_700_000_000_123);
+
+        assert_eq!(
+            format_file_name("{timestamp}-{name}-{sequence}.png", timestamp, "DP-1", 0, 7),
+            "1700000000-DP-1-000007.png"
+        );
+        assert_eq!(
+            format_file_name("{name}/{timestamp_ms}.jpg", timestamp, "a/b c", 0, 0),
+            "a_b_c/1700000000123.jpg"
+        );
+        assert_eq!(
+            format_file_name("{name}/{sequence}.png", timestamp, "..", 0, 1),
+            "__/000001.png"
+        );
+        assert_eq!(
+            format_file_name("{name}/{sequence}.png", timestamp, "", 0, 1),
+            "_/000001.png"
+        );
+    }
+
+    #[test]
+    fn test_targets_with_same_name() {
+        let timestamp = Duration::from_secs(1_700_000_000);
+        let template = TimelapseOptions::default().template;
+
+        // 两个同名的目标在同一次截图中使用不同的路径
+        assert_ne!(
+            format_file_name(&template, timestamp, "firefox", 0, 3),
+            format_file_name(&template, timestamp, "firefox", 1, 3)
+        );
+
+        // 相同的路径只记录一次，淘汰时不会删除仍然存在的文件
+        let directory = std::env::temp_dir().join(format!("xcap-timelapse-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("same.png");
+        let other = directory.join("other.png");
+        fs::write(&path, [0; 4]).unwrap();
+        fs::write(&other, [0; 2]).unwrap();
+
+        let options = TimelapseOptions {
+            max_files: Some(2),
+            ..TimelapseOptions::default()
+        };
+        let mut retention = Retention::default();
+        retention.push(path.clone(), 8, &options);
+        retention.push(path.clone(), 4, &options);
+        assert_eq!(retention.files.len(), 1);
+        assert_eq!(retention.bytes, 4);
+
+        retention.push(other.clone(), 2, &options);
+        assert!(path.exists());
+        assert_eq!(retention.bytes, 6);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+use image::{RgbaImage, imageops};
+
+/// How the panel-native image is turned into the image the user sees, the reflections are
+/// applied first, then the clockwise rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Transform {
+    /// Clockwise rotation in degrees, one of 0, 90, 180 or 270, the same as `Monitor::rotation()`.
+    pub rotation: u32,
+    /// Mirror along the x axis, i.e. left and right are swapped.
+    pub reflect_x: bool,
+    /// Mirror along the y axis, i.e. top and bottom are swapped.
+    pub reflect_y: bool,
+}
+
+impl Transform {
+    pub fn new(rotation: u32, reflect_x: bool, reflect_y: bool) -> Transform {
+        Transform {
+            rotation: rotation % 360 / 90 * 90,
+            reflect_x,
+            reflect_y,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::default()
+    }
+
+    /// Whether width and height are swapped between the panel-native and the visible image.
+    pub fn swaps_axes(&self) -> bool {
+        self.rotation == 90 || self.rotation == 270
+    }
+
+    /// Turn a panel-native image into the user-visible image.
+    pub fn apply(&self, image: RgbaImage) -> RgbaImage {
+        let image = reflect(image, self.reflect_x, self.reflect_y);
+        rotate_clockwise(image, self.rotation)
+    }
+
+    /// Turn a user-visible image back into the panel-native image.
+    pub fn revert(&self, image: RgbaImage) -> RgbaImage {
+        let image = rotate_clockwise(image, (360 - self.rotation) % 360);
+        reflect(image, self.reflect_x, self.reflect_y)
+    }
+}
+
+fn reflect(mut image: RgbaImage, reflect_x: bool, reflect_y: bool) -> RgbaImage {
+    if reflect_x {
+        imageops::flip_horizontal_in_place(&mut image);
+    }
+    if reflect_y {
+        imageops::flip_vertical_in_place(&mut image);
+    }
+
+    image
+}
+
+fn rotate_clockwise(image: RgbaImage, rotation: u32) -> RgbaImage {
+    match rotation {
+        90 => imageops::rotate90(&image),
+        180 => imageops::rotate180(&image),
+        270 => imageops::rotate270(&image),
+        _ => image,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn test_transform() {
+        // 2x1 的图像，左边红色右边绿色
+        let red = Rgba([255, 0, 0, 255]);
+        let green = Rgba([0, 255, 0, 255]);
+        let image = RgbaImage::from_fn(2, 1, |x, _| if x == 0 { red } else { green });
+
+        let transform = Transform::new(90, false, false);
+        let rotated = transform.apply(image.clone());
+        assert_eq!(rotated.dimensions(), (1, 2));
+        // 顺时针旋转后左边的像素在上面
+        assert_eq!(*rotated.get_pixel(0, 0), red);
+        assert_eq!(transform.revert(rotated), image);
+
+        let transform = Transform::new(270, true, false);
+        let transformed = transform.apply(image.clone());
+        assert_eq!(*transformed.get_pixel(0, 0), red);
+        assert_eq!(transform.revert(transformed), image);
+
+        assert!(Transform::new(360, false, false).is_identity());
+    }
+}
@@ -1,17 +1,110 @@
-use std::sync::{Condvar, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
 
-use crate::{XCapResult, platform::impl_video_recorder::ImplVideoRecorder};
+use image::RgbaImage;
+
+use crate::{
+    XCapResult,
+    pixel_format::{PixelFormat, convert_pixels},
+    platform::impl_video_recorder::ImplVideoRecorder,
+};
 
 #[derive(Debug, Clone)]
 pub struct Frame {
     pub width: u32,
     pub height: u32,
+    /// Layout of `raw`, rows are tightly packed.
+    pub format: PixelFormat,
     pub raw: Vec<u8>,
 }
 
 impl Frame {
     pub fn new(width: u32, height: u32, raw: Vec<u8>) -> Self {
-        Self { width, height, raw }
+        Self::new_with_format(width, height, PixelFormat::Rgba, raw)
+    }
+
+    pub fn new_with_format(width: u32, height: u32, format: PixelFormat, raw: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            raw,
+        }
+    }
+
+    /// Convert the pixels to `format`, a no-op when the frame already has that format.
+    pub fn convert(self, format: PixelFormat) -> Frame {
+        if self.format == format {
+            return self;
+        }
+
+        let raw = convert_pixels(&self.raw, self.format, format);
+        Frame::new_with_format(self.width, self.height, format, raw)
+    }
+}
+
+impl From<RgbaImage> for Frame {
+    fn from(image: RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+        Frame::new(width, height, image.into_raw())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VideoRecorderOptions {
+    /// Pixel format of the recorded frames.
+    pub format: PixelFormat,
+    /// Number of buffers handed back with [`VideoRecorder::recycle`] that are kept for new frames.
+    pub pool_size: usize,
+}
+
+impl Default for VideoRecorderOptions {
+    fn default() -> Self {
+        VideoRecorderOptions {
+            format: PixelFormat::Rgba,
+            pool_size: 4,
+        }
+    }
+}
+
+/// Frame buffers handed back by consumers, reused so that recording doesn't allocate.
+#[derive(Debug, Clone)]
+pub(crate) struct FramePool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    size: usize,
+}
+
+impl FramePool {
+    pub fn new(size: usize) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(Vec::with_capacity(size))),
+            size,
+        }
+    }
+
+    /// A buffer of `len` bytes, its content is unspecified.
+    #[allow(dead_code)]
+    pub fn take(&self, len: usize) -> Vec<u8> {
+        let buffer = self
+            .buffers
+            .lock()
+            .ok()
+            .and_then(|mut buffers| buffers.pop());
+
+        match buffer {
+            Some(mut buffer) => {
+                buffer.resize(len, 0);
+                buffer
+            }
+            None => vec![0; len],
+        }
+    }
+
+    pub fn put(&self, buffer: Vec<u8>) {
+        if let Ok(mut buffers) = self.buffers.lock()
+            && buffers.len() < self.size
+        {
+            buffers.push(buffer);
+        }
     }
 }
 
@@ -59,12 +152,14 @@ impl RecorderWaker {
 #[derive(Debug, Clone)]
 pub struct VideoRecorder {
     impl_video_recorder: ImplVideoRecorder,
+    pool: FramePool,
 }
 
 impl VideoRecorder {
-    pub(crate) fn new(impl_video_recorder: ImplVideoRecorder) -> VideoRecorder {
+    pub(crate) fn new(impl_video_recorder: ImplVideoRecorder, pool: FramePool) -> VideoRecorder {
         VideoRecorder {
             impl_video_recorder,
+            pool,
         }
     }
 }
@@ -76,4 +171,8 @@ impl VideoRecorder {
     pub fn stop(&self) -> XCapResult<()> {
         self.impl_video_recorder.stop()
     }
+    /// Hand a consumed frame back, its buffer is reused for a later frame.
+    pub fn recycle(&self, frame: Frame) {
+        self.pool.put(frame.raw);
+    }
 }
@@ -0,0 +1,1302 @@
+use std::{
+    collections::{
+        HashMap,
+        hash_map::{DefaultHasher, Entry},
+    },
+    hash::Hasher,
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    ops::Range,
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::Duration,
+};
+
+use des::{
+    Des,
+    cipher::{BlockEncrypt, KeyInit},
+};
+use flate2::{Compress, Compression, FlushCompress};
+
+use crate::{
+    Monitor, VideoRecorder,
+    error::{XCapError, XCapResult},
+    video_recorder::Frame,
+};
+
+const TILE_SIZE: u32 = 64;
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Clients have to finish the handshake and authentication within this time.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const ENCODING_RAW: i32 = 0;
+const ENCODING_COPY_RECT: i32 = 1;
+const ENCODING_ZRLE: i32 = 16;
+const ENCODING_DESKTOP_SIZE: i32 = -223;
+
+const SECURITY_NONE: u8 = 1;
+const SECURITY_VNC_AUTH: u8 = 2;
+
+/// Minimum number of rows a scrolled block must have to be sent as CopyRect.
+const MIN_COPY_ROWS: u32 = 16;
+
+#[derive(Debug, Clone)]
+pub struct VncServerOptions {
+    /// Address the RFB server listens on.
+    pub address: SocketAddr,
+    /// VNC password, only the first 8 bytes are used. No authentication when `None`.
+    pub password: Option<String>,
+    /// Desktop name sent to viewers, defaults to the monitor friendly name.
+    pub desktop_name: Option<String>,
+    /// Maximum number of simultaneous viewers.
+    pub max_clients: usize,
+}
+
+impl Default for VncServerOptions {
+    fn default() -> Self {
+        VncServerOptions {
+            address: SocketAddr::from(([127, 0, 0, 1], 5900)),
+            password: None,
+            desktop_name: None,
+            max_clients: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Rect {
+    fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn clip(&self, width: u32, height: u32) -> Option<Rect> {
+        let right = (self.x + self.width).min(width);
+        let bottom = (self.y + self.height).min(height);
+
+        if self.x >= right || self.y >= bottom {
+            return None;
+        }
+
+        Some(Rect::new(self.x, self.y, right - self.x, bottom - self.y))
+    }
+}
+
+/// RGBA framebuffer shared between all viewers.
+#[derive(Debug, Clone)]
+struct Framebuffer {
+    width: u32,
+    height: u32,
+    pixels: Arc<Vec<u8>>,
+}
+
+impl Framebuffer {
+    fn row(&self, x: u32, y: u32, width: u32) -> &[u8] {
+        let start = ((y * self.width + x) * 4) as usize;
+        &self.pixels[start..start + (width * 4) as usize]
+    }
+}
+
+// https://github.com/rfbproto/rfbproto/blob/master/rfbproto.rst#pixel-format-data-structure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PixelFormat {
+    bits_per_pixel: u8,
+    depth: u8,
+    big_endian: bool,
+    true_color: bool,
+    red_max: u16,
+    green_max: u16,
+    blue_max: u16,
+    red_shift: u8,
+    green_shift: u8,
+    blue_shift: u8,
+}
+
+impl Default for PixelFormat {
+    fn default() -> Self {
+        PixelFormat {
+            bits_per_pixel: 32,
+            depth: 24,
+            big_endian: false,
+            true_color: true,
+            red_max: 255,
+            green_max: 255,
+            blue_max: 255,
+            red_shift: 16,
+            green_shift: 8,
+            blue_shift: 0,
+        }
+    }
+}
+
+impl PixelFormat {
+    fn from_bytes(bytes: &[u8; 16]) -> PixelFormat {
+        PixelFormat {
+            bits_per_pixel: bytes[0],
+            depth: bytes[1],
+            big_endian: bytes[2] != 0,
+            true_color: bytes[3] != 0,
+            red_max: u16::from_be_bytes([bytes[4], bytes[5]]),
+            green_max: u16::from_be_bytes([bytes[6], bytes[7]]),
+            blue_max: u16::from_be_bytes([bytes[8], bytes[9]]),
+            red_shift: bytes[10],
+            green_shift: bytes[11],
+            blue_shift: bytes[12],
+        }
+    }
+
+    fn to_bytes(self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[0] = self.bits_per_pixel;
+        bytes[1] = self.depth;
+        bytes[2] = self.big_endian as u8;
+        bytes[3] = self.true_color as u8;
+        bytes[4..6].copy_from_slice(&self.red_max.to_be_bytes());
+        bytes[6..8].copy_from_slice(&self.green_max.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.blue_max.to_be_bytes());
+        bytes[10] = self.red_shift;
+        bytes[11] = self.green_shift;
+        bytes[12] = self.blue_shift;
+        bytes
+    }
+
+    fn is_supported(&self) -> bool {
+        self.true_color && matches!(self.bits_per_pixel, 8 | 16 | 32)
+    }
+
+    fn pixel_value(&self, rgba: &[u8]) -> u32 {
+        let channel = |value: u8, max: u16, shift: u8| {
+            let value = value as u32 * max as u32 / 255;
+            value.checked_shl(shift as u32).unwrap_or(0)
+        };
+
+        channel(rgba[0], self.red_max, self.red_shift)
+            | channel(rgba[1], self.green_max, self.green_shift)
+            | channel(rgba[2], self.blue_max, self.blue_shift)
+    }
+
+    fn pixel_bytes(&self, value: u32) -> ([u8; 4], usize) {
+        match self.bits_per_pixel {
+            8 => ([value as u8, 0, 0, 0], 1),
+            16 => {
+                let bytes = if self.big_endian {
+                    (value as u16).to_be_bytes()
+                } else {
+                    (value as u16).to_le_bytes()
+                };
+                ([bytes[0], bytes[1], 0, 0], 2)
+            }
+            _ => {
+                let bytes = if self.big_endian {
+                    value.to_be_bytes()
+                } else {
+                    value.to_le_bytes()
+                };
+                (bytes, 4)
+            }
+        }
+    }
+
+    fn write_pixel(&self, value: u32, out: &mut Vec<u8>) {
+        let (bytes, size) = self.pixel_bytes(value);
+        out.extend_from_slice(&bytes[..size]);
+    }
+
+    /// Bytes of a ZRLE CPIXEL inside the full pixel, `None` when CPIXEL is the full pixel.
+    fn compressed_pixel_range(&self) -> Option<Range<usize>> {
+        if !self.true_color || self.bits_per_pixel != 32 || self.depth > 24 {
+            return None;
+        }
+
+        let max = self.pixel_value(&[255, 255, 255, 255]);
+
+        if max < 1 << 24 {
+            Some(if self.big_endian { 1..4 } else { 0..3 })
+        } else if max & 0xFF == 0 {
+            Some(if self.big_endian { 0..3 } else { 1..4 })
+        } else {
+            None
+        }
+    }
+
+    fn write_compressed_pixel(&self, value: u32, range: &Option<Range<usize>>, out: &mut Vec<u8>) {
+        match range {
+            Some(range) => {
+                let (bytes, _) = self.pixel_bytes(value);
+                out.extend_from_slice(&bytes[range.clone()]);
+            }
+            None => self.write_pixel(value, out),
+        }
+    }
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn skip<R: Read>(reader: &mut R, len: u64) -> io::Result<()> {
+    io::copy(&mut reader.take(len), &mut io::sink())?;
+    Ok(())
+}
+
+// https://github.com/rfbproto/rfbproto/blob/master/rfbproto.rst#vnc-authentication
+fn vnc_auth_response(password: &str, challenge: &[u8; 16]) -> [u8; 16] {
+    // VNC 的 DES key 每个字节的比特位是反转的
+    let mut key = [0u8; 8];
+    for (dst, src) in key.iter_mut().zip(password.as_bytes()) {
+        *dst = src.reverse_bits();
+    }
+
+    let cipher = Des::new(&key.into());
+    let mut response = *challenge;
+    for block in response.chunks_exact_mut(8) {
+        cipher.encrypt_block(block.into());
+    }
+
+    response
+}
+
+fn row_hash(row: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(row);
+    hasher.finish()
+}
+
+fn tile_differs(old: &Framebuffer, new: &Framebuffer, rect: Rect) -> bool {
+    (rect.y..rect.y + rect.height)
+        .any(|y| old.row(rect.x, y, rect.width) != new.row(rect.x, y, rect.width))
+}
+
+/// Changed tiles of `new` compared to `old`, horizontally adjacent tiles are merged.
+fn damaged_rects(old: &Framebuffer, new: &Framebuffer) -> Vec<Rect> {
+    let mut rects = Vec::new();
+
+    for y in (0..new.height).step_by(TILE_SIZE as usize) {
+        let height = TILE_SIZE.min(new.height - y);
+        let mut run: Option<Rect> = None;
+
+        for x in (0..new.width).step_by(TILE_SIZE as usize) {
+            let tile = Rect::new(x, y, TILE_SIZE.min(new.width - x), height);
+
+            if tile_differs(old, new, tile) {
+                run = Some(match run {
+                    Some(run) => Rect::new(run.x, y, run.width + tile.width, height),
+                    None => tile,
+                });
+            } else if let Some(run) = run.take() {
+                rects.push(run);
+            }
+        }
+
+        rects.extend(run);
+    }
+
+    rects
+}
+
+/// Detect a vertically scrolled block inside `bounds`, returns the destination
+/// rectangle and the source y coordinate for a CopyRect.
+fn detect_scroll(old: &Framebuffer, new: &Framebuffer, bounds: Rect) -> Option<(Rect, u32)> {
+    let old_hashes: Vec<u64> = (bounds.y..bounds.y + bounds.height)
+        .map(|y| row_hash(old.row(bounds.x, y, bounds.width)))
+        .collect();
+    let new_hashes: Vec<u64> = (bounds.y..bounds.y + bounds.height)
+        .map(|y| row_hash(new.row(bounds.x, y, bounds.width)))
+        .collect();
+
+    let mut old_rows: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (index, &hash) in old_hashes.iter().enumerate() {
+        old_rows.entry(hash).or_default().push(index);
+    }
+
+    // 统计每种偏移量的匹配行数，忽略大量重复的行（例如纯色背景）
+    let mut votes: HashMap<i64, u32> = HashMap::new();
+    for (index, hash) in new_hashes.iter().enumerate() {
+        if let Some(rows) = old_rows.get(hash)
+            && rows.len() <= 4
+        {
+            for &row in rows {
+                if row != index {
+                    *votes.entry(row as i64 - index as i64).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let (&offset, &count) = votes.iter().max_by_key(|&(_, count)| *count)?;
+    if count < MIN_COPY_ROWS {
+        return None;
+    }
+
+    // 找到最长的连续匹配区域
+    let mut best: Option<Range<usize>> = None;
+    let mut start = None;
+    for index in 0..=new_hashes.len() {
+        let source = index as i64 + offset;
+        let matched = index < new_hashes.len()
+            && source >= 0
+            && (source as usize) < old_hashes.len()
+            && new_hashes[index] == old_hashes[source as usize];
+
+        match (matched, start) {
+            (true, None) => start = Some(index),
+            (false, Some(run_start)) => {
+                if best
+                    .as_ref()
+                    .is_none_or(|best| best.len() < index - run_start)
+                {
+                    best = Some(run_start..index);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let best = best?;
+    if (best.len() as u32) < MIN_COPY_ROWS {
+        return None;
+    }
+
+    let dst = Rect::new(
+        bounds.x,
+        bounds.y + best.start as u32,
+        bounds.width,
+        best.len() as u32,
+    );
+    let src_y = (dst.y as i64 + offset) as u32;
+
+    // 哈希可能碰撞，确认内容完全一致
+    let is_equal = (0..dst.height).all(|row| {
+        old.row(dst.x, src_y + row, dst.width) == new.row(dst.x, dst.y + row, dst.width)
+    });
+
+    is_equal.then_some((dst, src_y))
+}
+
+fn bounding_rect(rects: &[Rect]) -> Option<Rect> {
+    let first = rects.first()?;
+    let (mut left, mut top) = (first.x, first.y);
+    let (mut right, mut bottom) = (first.x + first.width, first.y + first.height);
+
+    for rect in rects {
+        left = left.min(rect.x);
+        top = top.min(rect.y);
+        right = right.max(rect.x + rect.width);
+        bottom = bottom.max(rect.y + rect.height);
+    }
+
+    Some(Rect::new(left, top, right - left, bottom - top))
+}
+
+/// Apply a CopyRect to `old`, so the remaining damage can be computed against it.
+fn apply_copy(old: &Framebuffer, dst: Rect, src_y: u32) -> Framebuffer {
+    let mut pixels = old.pixels.as_ref().clone();
+    let row_size = (dst.width * 4) as usize;
+
+    for row in 0..dst.height {
+        let src = (((src_y + row) * old.width + dst.x) * 4) as usize;
+        let dst = (((dst.y + row) * old.width + dst.x) * 4) as usize;
+        pixels.copy_within(src..src + row_size, dst);
+    }
+
+    Framebuffer {
+        width: old.width,
+        height: old.height,
+        pixels: Arc::new(pixels),
+    }
+}
+
+fn write_rect_header(out: &mut Vec<u8>, rect: Rect, encoding: i32) {
+    out.extend_from_slice(&(rect.x as u16).to_be_bytes());
+    out.extend_from_slice(&(rect.y as u16).to_be_bytes());
+    out.extend_from_slice(&(rect.width as u16).to_be_bytes());
+    out.extend_from_slice(&(rect.height as u16).to_be_bytes());
+    out.extend_from_slice(&encoding.to_be_bytes());
+}
+
+fn encode_raw(
+    framebuffer: &Framebuffer,
+    rect: Rect,
+    pixel_format: &PixelFormat,
+    out: &mut Vec<u8>,
+) {
+    for y in rect.y..rect.y + rect.height {
+        for rgba in framebuffer.row(rect.x, y, rect.width).chunks_exact(4) {
+            pixel_format.write_pixel(pixel_format.pixel_value(rgba), out);
+        }
+    }
+}
+
+fn write_run_length(mut length: usize, out: &mut Vec<u8>) {
+    length -= 1;
+    while length >= 255 {
+        out.push(255);
+        length -= 255;
+    }
+    out.push(length as u8);
+}
+
+// https://github.com/rfbproto/rfbproto/blob/master/rfbproto.rst#zrle-encoding
+fn encode_zrle_tile(
+    framebuffer: &Framebuffer,
+    tile: Rect,
+    pixel_format: &PixelFormat,
+    range: &Option<Range<usize>>,
+    out: &mut Vec<u8>,
+) {
+    let mut values = Vec::with_capacity((tile.width * tile.height) as usize);
+    for y in tile.y..tile.y + tile.height {
+        for rgba in framebuffer.row(tile.x, y, tile.width).chunks_exact(4) {
+            values.push(pixel_format.pixel_value(rgba));
+        }
+    }
+
+    let mut palette: Vec<u32> = Vec::new();
+    let mut indexes: HashMap<u32, u8> = HashMap::new();
+    for &value in &values {
+        if let Entry::Vacant(entry) = indexes.entry(value) {
+            if palette.len() == 127 {
+                palette.clear();
+                break;
+            }
+            entry.insert(palette.len() as u8);
+            palette.push(value);
+        }
+    }
+
+    match palette.len() {
+        // 超过 127 种颜色，使用 raw
+        0 => {
+            out.push(0);
+            for &value in &values {
+                pixel_format.write_compressed_pixel(value, range, out);
+            }
+        }
+        1 => {
+            out.push(1);
+            pixel_format.write_compressed_pixel(palette[0], range, out);
+        }
+        2..=16 => {
+            out.push(palette.len() as u8);
+            for &value in &palette {
+                pixel_format.write_compressed_pixel(value, range, out);
+            }
+
+            let bits = match palette.len() {
+                2 => 1,
+                3..=4 => 2,
+                _ => 4,
+            };
+
+            for row in values.chunks(tile.width as usize) {
+                let mut byte = 0u8;
+                let mut used = 0;
+                for value in row {
+                    byte = (byte << bits) | indexes[value];
+                    used += bits;
+                    if used == 8 {
+                        out.push(byte);
+                        byte = 0;
+                        used = 0;
+                    }
+                }
+                if used > 0 {
+                    out.push(byte << (8 - used));
+                }
+            }
+        }
+        _ => {
+            out.push(128 + palette.len() as u8);
+            for &value in &palette {
+                pixel_format.write_compressed_pixel(value, range, out);
+            }
+
+            let mut index = 0;
+            while index < values.len() {
+                let value = values[index];
+                let length = values[index..].iter().take_while(|&&v| v == value).count();
+                if length == 1 {
+                    out.push(indexes[&value]);
+                } else {
+                    out.push(indexes[&value] | 128);
+                    write_run_length(length, out);
+                }
+                index += length;
+            }
+        }
+    }
+}
+
+fn deflate(compress: &mut Compress, input: &[u8]) -> XCapResult<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / 2 + 1024);
+    let mut consumed = 0;
+
+    loop {
+        if output.len() == output.capacity() {
+            output.reserve(output.capacity().max(1024));
+        }
+
+        let total_in = compress.total_in();
+        compress
+            .compress_vec(&input[consumed..], &mut output, FlushCompress::Sync)
+            .map_err(XCapError::new)?;
+        consumed += (compress.total_in() - total_in) as usize;
+
+        if consumed == input.len() && output.len() < output.capacity() {
+            return Ok(output);
+        }
+    }
+}
+
+fn encode_zrle(
+    framebuffer: &Framebuffer,
+    rect: Rect,
+    pixel_format: &PixelFormat,
+    compress: &mut Compress,
+    out: &mut Vec<u8>,
+) -> XCapResult<()> {
+    let range = pixel_format.compressed_pixel_range();
+    let mut data = Vec::new();
+
+    for y in (rect.y..rect.y + rect.height).step_by(TILE_SIZE as usize) {
+        for x in (rect.x..rect.x + rect.width).step_by(TILE_SIZE as usize) {
+            let tile = Rect::new(
+                x,
+                y,
+                TILE_SIZE.min(rect.x + rect.width - x),
+                TILE_SIZE.min(rect.y + rect.height - y),
+            );
+            encode_zrle_tile(framebuffer, tile, pixel_format, &range, &mut data);
+        }
+    }
+
+    let compressed = deflate(compress, &data)?;
+    out.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+    out.extend_from_slice(&compressed);
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UpdateRequest {
+    incremental: bool,
+    rect: Rect,
+}
+
+#[derive(Debug)]
+struct ClientRequest {
+    pixel_format: PixelFormat,
+    encodings: Vec<i32>,
+    pending: Option<UpdateRequest>,
+    dirty: bool,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Client {
+    stream: TcpStream,
+    request: Mutex<ClientRequest>,
+    condvar: Condvar,
+}
+
+impl Client {
+    fn close(&self) {
+        if let Ok(mut request) = self.request.lock() {
+            request.closed = true;
+        }
+        self.condvar.notify_all();
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+
+    fn mark_dirty(&self) -> XCapResult<()> {
+        self.request.lock()?.dirty = true;
+        self.condvar.notify_all();
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ServerState {
+    video_recorder: VideoRecorder,
+    options: VncServerOptions,
+    desktop_name: String,
+    running: AtomicBool,
+    framebuffer: Mutex<Framebuffer>,
+    clients: Mutex<Vec<Arc<Client>>>,
+}
+
+impl ServerState {
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    fn framebuffer(&self) -> XCapResult<Framebuffer> {
+        Ok(self.framebuffer.lock()?.clone())
+    }
+
+    fn is_full(&self) -> XCapResult<bool> {
+        Ok(self.clients.lock()?.len() >= self.options.max_clients)
+    }
+
+    fn add_client(&self, client: Arc<Client>) -> XCapResult<bool> {
+        let mut clients = self.clients.lock()?;
+        if clients.len() >= self.options.max_clients {
+            return Ok(false);
+        }
+
+        clients.push(client);
+        if clients.len() == 1 {
+            self.video_recorder.start()?;
+        }
+
+        Ok(true)
+    }
+
+    fn remove_client(&self, client: &Arc<Client>) -> XCapResult<()> {
+        let mut clients = self.clients.lock()?;
+        let len = clients.len();
+        clients.retain(|item| !Arc::ptr_eq(item, client));
+
+        // 没有客户端时停止录制，避免空转
+        if clients.len() < len && clients.is_empty() {
+            self.video_recorder.stop()?;
+        }
+
+        Ok(())
+    }
+
+    fn publish(&self, frame: Frame) -> XCapResult<()> {
+        if frame.raw.len() != (frame.width * frame.height * 4) as usize {
+            return Err(XCapError::new(format!(
+                "Invalid frame size {}x{}",
+                frame.width, frame.height
+            )));
+        }
+
+        *self.framebuffer.lock()? = Framebuffer {
+            width: frame.width,
+            height: frame.height,
+            pixels: Arc::new(frame.raw),
+        };
+
+        for client in self.clients.lock()?.iter() {
+            client.mark_dirty()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn receive_frames(state: Arc<ServerState>, receiver: Receiver<Frame>) {
+    while state.is_running() {
+        let mut frame = match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        while let Ok(next_frame) = receiver.try_recv() {
+            frame = next_frame;
+        }
+
+        if let Err(err) = state.publish(frame) {
+            log::error!("Failed to publish frame: {err:?}");
+        }
+    }
+}
+
+fn write_failure(stream: &mut TcpStream, minor_version: u32, reason: &str) -> XCapResult<()> {
+    if minor_version >= 7 {
+        stream.write_all(&[0])?;
+    } else {
+        stream.write_all(&0u32.to_be_bytes())?;
+    }
+    stream.write_all(&(reason.len() as u32).to_be_bytes())?;
+    stream.write_all(reason.as_bytes())?;
+
+    Ok(())
+}
+
+fn write_security_result(
+    stream: &mut TcpStream,
+    minor_version: u32,
+    reason: Option<&str>,
+) -> XCapResult<()> {
+    match reason {
+        None => stream.write_all(&0u32.to_be_bytes())?,
+        Some(reason) => {
+            stream.write_all(&1u32.to_be_bytes())?;
+            if minor_version >= 8 {
+                stream.write_all(&(reason.len() as u32).to_be_bytes())?;
+                stream.write_all(reason.as_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// https://github.com/rfbproto/rfbproto/blob/master/rfbproto.rst#handshake-messages
+fn handshake(
+    state: &ServerState,
+    stream: &mut TcpStream,
+    client: &Arc<Client>,
+) -> XCapResult<bool> {
+    stream.write_all(b"RFB 003.008\n")?;
+
+    let mut version = [0u8; 12];
+    stream.read_exact(&mut version)?;
+    let version = String::from_utf8_lossy(&version);
+    let minor_version = match version.strip_prefix("RFB 003.") {
+        Some(minor) => minor.trim_end().parse::<u32>().map_err(XCapError::new)?,
+        None => {
+            return Err(XCapError::new(format!(
+                "Unsupported protocol version {version:?}"
+            )));
+        }
+    };
+
+    // 已满时直接拒绝，不必等待认证
+    if state.is_full()? {
+        write_failure(stream, minor_version, "Too many clients")?;
+        return Ok(false);
+    }
+
+    let security_type = if state.options.password.is_some() {
+        SECURITY_VNC_AUTH
+    } else {
+        SECURITY_NONE
+    };
+
+    if minor_version >= 7 {
+        stream.write_all(&[1, security_type])?;
+        if read_u8(stream)? != security_type {
+            write_security_result(stream, minor_version, Some("Unsupported security type"))?;
+            return Ok(false);
+        }
+    } else {
+        stream.write_all(&(security_type as u32).to_be_bytes())?;
+    }
+
+    if let Some(password) = &state.options.password {
+        let challenge = rand::random::<[u8; 16]>();
+        stream.write_all(&challenge)?;
+
+        let mut response = [0u8; 16];
+        stream.read_exact(&mut response)?;
+
+        if response != vnc_auth_response(password, &challenge) {
+            write_security_result(stream, minor_version, Some("Authentication failed"))?;
+            return Ok(false);
+        }
+    }
+
+    // 认证成功后才占用名额并启动录制，未认证的连接不会占满客户端数量
+    let has_security_result = state.options.password.is_some() || minor_version >= 8;
+    if !state.add_client(client.clone())? {
+        if has_security_result {
+            write_security_result(stream, minor_version, Some("Too many clients"))?;
+        }
+        return Ok(false);
+    }
+    if has_security_result {
+        write_security_result(stream, minor_version, None)?;
+    }
+
+    // ClientInit，忽略 shared-flag，总是允许多个客户端共享
+    read_u8(stream)?;
+
+    let framebuffer = state.framebuffer()?;
+    let mut server_init = Vec::new();
+    server_init.extend_from_slice(&(framebuffer.width as u16).to_be_bytes());
+    server_init.extend_from_slice(&(framebuffer.height as u16).to_be_bytes());
+    server_init.extend_from_slice(&PixelFormat::default().to_bytes());
+    server_init.extend_from_slice(&(state.desktop_name.len() as u32).to_be_bytes());
+    server_init.extend_from_slice(state.desktop_name.as_bytes());
+    stream.write_all(&server_init)?;
+
+    Ok(true)
+}
+
+// https://github.com/rfbproto/rfbproto/blob/master/rfbproto.rst#client-to-server-messages
+fn read_messages(client: &Client, stream: &mut TcpStream) -> XCapResult<()> {
+    loop {
+        let message_type = read_u8(stream)?;
+
+        match message_type {
+            // SetPixelFormat
+            0 => {
+                skip(stream, 3)?;
+                let mut bytes = [0u8; 16];
+                stream.read_exact(&mut bytes)?;
+                let pixel_format = PixelFormat::from_bytes(&bytes);
+                if !pixel_format.is_supported() {
+                    return Err(XCapError::new(format!(
+                        "Unsupported pixel format {pixel_format:?}"
+                    )));
+                }
+                client.request.lock()?.pixel_format = pixel_format;
+            }
+            // SetEncodings
+            2 => {
+                skip(stream, 1)?;
+                let count = read_u16(stream)?;
+                let encodings = (0..count)
+                    .map(|_| read_u32(stream).map(|encoding| encoding as i32))
+                    .collect::<io::Result<Vec<i32>>>()?;
+                client.request.lock()?.encodings = encodings;
+            }
+            // FramebufferUpdateRequest
+            3 => {
+                let incremental = read_u8(stream)? != 0;
+                let rect = Rect::new(
+                    read_u16(stream)? as u32,
+                    read_u16(stream)? as u32,
+                    read_u16(stream)? as u32,
+                    read_u16(stream)? as u32,
+                );
+                client.request.lock()?.pending = Some(UpdateRequest { incremental, rect });
+                client.condvar.notify_all();
+            }
+            // KeyEvent，只读，忽略输入
+            4 => skip(stream, 7)?,
+            // PointerEvent
+            5 => skip(stream, 5)?,
+            // ClientCutText
+            6 => {
+                skip(stream, 3)?;
+                let length = read_u32(stream)?;
+                skip(stream, length as u64)?;
+            }
+            _ => {
+                return Err(XCapError::new(format!(
+                    "Unsupported message type {message_type}"
+                )));
+            }
+        }
+    }
+}
+
+struct UpdateEncoder {
+    compress: Compress,
+    /// The framebuffer the viewer currently shows, `None` before the first full update.
+    framebuffer: Option<Framebuffer>,
+}
+
+impl UpdateEncoder {
+    fn encode(
+        &mut self,
+        framebuffer: &Framebuffer,
+        request: UpdateRequest,
+        pixel_format: &PixelFormat,
+        encodings: &[i32],
+    ) -> XCapResult<Option<Vec<u8>>> {
+        let size_changed = self.framebuffer.as_ref().is_some_and(|current| {
+            current.width != framebuffer.width || current.height != framebuffer.height
+        });
+        let supports_desktop_size = encodings.contains(&ENCODING_DESKTOP_SIZE);
+
+        // 客户端无法处理尺寸变化，断开连接让它重新连接
+        if size_changed && !supports_desktop_size {
+            return Err(XCapError::new(
+                "Framebuffer size changed and the client does not support DesktopSize",
+            ));
+        }
+
+        let full_rect = Rect::new(0, 0, framebuffer.width, framebuffer.height);
+        let Some(rect) = request.rect.clip(framebuffer.width, framebuffer.height) else {
+            return Ok(None);
+        };
+
+        let mut copies = Vec::new();
+        let mut rects = Vec::new();
+
+        match &self.framebuffer {
+            Some(current) if !size_changed && request.incremental && rect == full_rect => {
+                let damaged = damaged_rects(current, framebuffer);
+                let mut base = current.clone();
+
+                if encodings.contains(&ENCODING_COPY_RECT)
+                    && let Some(bounds) = bounding_rect(&damaged)
+                    && let Some((dst, src_y)) = detect_scroll(current, framebuffer, bounds)
+                {
+                    base = apply_copy(current, dst, src_y);
+                    copies.push((dst, src_y));
+                }
+
+                if copies.is_empty() {
+                    rects = damaged;
+                } else {
+                    rects = damaged_rects(&base, framebuffer);
+                }
+            }
+            _ => rects.push(if size_changed { full_rect } else { rect }),
+        }
+
+        if copies.is_empty() && rects.is_empty() && !size_changed {
+            return Ok(None);
+        }
+
+        let encoding = encodings
+            .iter()
+            .copied()
+            .find(|&encoding| encoding == ENCODING_ZRLE || encoding == ENCODING_RAW)
+            .unwrap_or(ENCODING_RAW);
+
+        let count = copies.len() + rects.len() + size_changed as usize;
+        let mut out = vec![0, 0];
+        out.extend_from_slice(&(count as u16).to_be_bytes());
+
+        if size_changed {
+            write_rect_header(&mut out, full_rect, ENCODING_DESKTOP_SIZE);
+        }
+
+        // CopyRect 必须在其它矩形之前，保证源区域还未被修改
+        for (dst, src_y) in copies {
+            write_rect_header(&mut out, dst, ENCODING_COPY_RECT);
+            out.extend_from_slice(&(dst.x as u16).to_be_bytes());
+            out.extend_from_slice(&(src_y as u16).to_be_bytes());
+        }
+
+        for rect in rects {
+            write_rect_header(&mut out, rect, encoding);
+            if encoding == ENCODING_ZRLE {
+                encode_zrle(
+                    framebuffer,
+                    rect,
+                    pixel_format,
+                    &mut self.compress,
+                    &mut out,
+                )?;
+            } else {
+                encode_raw(framebuffer, rect, pixel_format, &mut out);
+            }
+        }
+
+        if rect == full_rect || size_changed {
+            self.framebuffer = Some(framebuffer.clone());
+        }
+
+        Ok(Some(out))
+    }
+}
+
+fn send_updates(state: &ServerState, client: &Client, stream: &mut TcpStream) -> XCapResult<()> {
+    let mut encoder = UpdateEncoder {
+        compress: Compress::new(Compression::default(), true),
+        framebuffer: None,
+    };
+
+    loop {
+        let (request, pixel_format, encodings) = {
+            let mut client_request = client.request.lock()?;
+
+            loop {
+                if client_request.closed || !state.is_running() {
+                    return Ok(());
+                }
+
+                if let Some(pending) = client_request.pending
+                    && (!pending.incremental || client_request.dirty)
+                {
+                    break;
+                }
+
+                client_request = client
+                    .condvar
+                    .wait_timeout(client_request, POLL_INTERVAL)?
+                    .0;
+            }
+
+            client_request.dirty = false;
+            (
+                client_request.pending.take(),
+                client_request.pixel_format,
+                client_request.encodings.clone(),
+            )
+        };
+
+        let Some(request) = request else {
+            continue;
+        };
+
+        let framebuffer = state.framebuffer()?;
+
+        match encoder.encode(&framebuffer, request, &pixel_format, &encodings)? {
+            Some(update) => stream.write_all(&update)?,
+            // 没有变化，等待下一帧
+            None => {
+                client.request.lock()?.pending.get_or_insert(UpdateRequest {
+                    incremental: true,
+                    ..request
+                });
+            }
+        }
+    }
+}
+
+fn handle_connection(state: &ServerState, mut stream: TcpStream) -> XCapResult<()> {
+    stream.set_nodelay(true)?;
+
+    let client = Arc::new(Client {
+        stream: stream.try_clone()?,
+        request: Mutex::new(ClientRequest {
+            pixel_format: PixelFormat::default(),
+            encodings: vec![ENCODING_RAW],
+            pending: None,
+            dirty: false,
+            closed: false,
+        }),
+        condvar: Condvar::new(),
+    });
+
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let result = handshake(state, &mut stream, &client).and_then(|is_ready| {
+        if !is_ready {
+            return Ok(());
+        }
+
+        // 握手完成后客户端可以长时间不发送消息
+        stream.set_read_timeout(None)?;
+
+        let reader_client = client.clone();
+        let mut reader_stream = stream.try_clone()?;
+        thread::spawn(move || {
+            if let Err(err) = read_messages(&reader_client, &mut reader_stream) {
+                log::debug!("VNC client disconnected: {err:?}");
+            }
+            reader_client.close();
+        });
+
+        send_updates(state, &client, &mut stream)
+    });
+
+    client.close();
+    state.remove_client(&client)?;
+
+    result
+}
+
+/// A view-only VNC (RFB 3.3 - 3.8) server for a monitor.
+///
+/// Supports the Raw, CopyRect and ZRLE encodings, the DesktopSize
+/// pseudo-encoding and VNC password authentication.
+#[derive(Debug)]
+pub struct VncServer {
+    state: Arc<ServerState>,
+    local_addr: SocketAddr,
+}
+
+impl VncServer {
+    pub fn new(monitor: &Monitor, options: VncServerOptions) -> XCapResult<VncServer> {
+        let image = monitor.capture_image()?;
+        let desktop_name = match &options.desktop_name {
+            Some(desktop_name) => desktop_name.clone(),
+            None => monitor.friendly_name()?,
+        };
+
+        let listener = TcpListener::bind(options.address)?;
+        let local_addr = listener.local_addr()?;
+        let (video_recorder, receiver) = monitor.video_recorder()?;
+
+        let state = Arc::new(ServerState {
+            video_recorder,
+            options,
+            desktop_name,
+            running: AtomicBool::new(true),
+            framebuffer: Mutex::new(Framebuffer {
+                width: image.width(),
+                height: image.height(),
+                pixels: Arc::new(image.into_raw()),
+            }),
+            clients: Mutex::new(Vec::new()),
+        });
+
+        let receiver_state = state.clone();
+        thread::spawn(move || receive_frames(receiver_state, receiver));
+
+        let listener_state = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if !listener_state.is_running() {
+                    break;
+                }
+
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        log::error!("Failed to accept connection: {err:?}");
+                        continue;
+                    }
+                };
+
+                let state = listener_state.clone();
+                thread::spawn(move || {
+                    if let Err(err) = handle_connection(&state, stream) {
+                        log::debug!("VNC connection closed: {err:?}");
+                    }
+                });
+            }
+        });
+
+        Ok(VncServer { state, local_addr })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections and disconnect all viewers.
+    pub fn stop(&self) -> XCapResult<()> {
+        if !self.state.running.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        for client in self.state.clients.lock()?.iter() {
+            client.close();
+        }
+        self.state.video_recorder.stop()?;
+
+        // 唤醒阻塞在 accept 上的监听线程
+        if let Ok(stream) = TcpStream::connect(self.local_addr) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for VncServer {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log::error!("Failed to stop VNC server: {err:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framebuffer(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> Framebuffer {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend_from_slice(&pixel(x, y));
+            }
+        }
+
+        Framebuffer {
+            width,
+            height,
+            pixels: Arc::new(pixels),
+        }
+    }
+
+    #[test]
+    fn test_vnc_auth_response() {
+        let challenge: [u8; 16] = std::array::from_fn(|index| index as u8);
+
+        // 与 `openssl enc -des-ecb` 使用按位反转的 key 计算的结果一致
+        assert_eq!(
+            vnc_auth_response("password", &challenge),
+            [
+                0xb8, 0x66, 0x92, 0x41, 0x25, 0xc8, 0xee, 0xbb, 0x9d, 0xeb, 0xc1, 0xdb, 0x61, 0xc5,
+                0x38, 0xe2
+            ]
+        );
+        // 只使用前 8 个字节
+        assert_eq!(
+            vnc_auth_response("password123", &challenge),
+            vnc_auth_response("password", &challenge)
+        );
+    }
+
+    #[test]
+    fn test_pixel_format() {
+        let pixel_format = PixelFormat::default();
+        assert_eq!(
+            PixelFormat::from_bytes(&pixel_format.to_bytes()),
+            pixel_format
+        );
+        assert_eq!(
+            pixel_format.pixel_value(&[0x11, 0x22, 0x33, 0xFF]),
+            0x112233
+        );
+        assert_eq!(pixel_format.compressed_pixel_range(), Some(0..3));
+
+        let rgb565 = PixelFormat {
+            bits_per_pixel: 16,
+            depth: 16,
+            red_max: 31,
+            green_max: 63,
+            blue_max: 31,
+            red_shift: 11,
+            green_shift: 5,
+            blue_shift: 0,
+            ..PixelFormat::default()
+        };
+        assert_eq!(rgb565.pixel_value(&[255, 255, 255, 255]), 0xFFFF);
+        assert_eq!(rgb565.compressed_pixel_range(), None);
+    }
+
+    #[test]
+    fn test_damaged_rects() {
+        let old = framebuffer(200, 100, |_, _| [0, 0, 0, 255]);
+        let new = framebuffer(200, 100, |x, y| {
+            if (70..150).contains(&x) && y == 10 {
+                [255, 255, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            }
+        });
+
+        assert_eq!(damaged_rects(&old, &new), vec![Rect::new(64, 0, 128, 64)]);
+        assert!(damaged_rects(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_detect_scroll() {
+        let row_pixel = |y: u32| [(y * 7) as u8, (y * 13) as u8, y as u8, 255];
+        let old = framebuffer(64, 128, |_, y| row_pixel(y));
+        let new = framebuffer(64, 128, |_, y| row_pixel(y + 20));
+
+        let bounds = Rect::new(0, 0, 64, 128);
+        assert_eq!(
+            detect_scroll(&old, &new, bounds),
+            Some((Rect::new(0, 0, 64, 108), 20))
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::{
+    Monitor, Window,
+    error::XCapResult,
+    platform::impl_watcher::{ImplActiveWindowWatcher, ImplMonitorWatcher, ImplWindowWatcher},
+};
+
+/// Notifies about changes of the active (focused) window.
+///
+/// On X11 this listens for `_NET_ACTIVE_WINDOW` changes on the root window, platforms without
+/// change notifications compare snapshots periodically.
+#[derive(Debug)]
+pub struct ActiveWindowWatcher {
+    impl_watcher: ImplActiveWindowWatcher,
+    failed: bool,
+}
+
+impl ActiveWindowWatcher {
+    pub fn new() -> XCapResult<ActiveWindowWatcher> {
+        Ok(ActiveWindowWatcher {
+            impl_watcher: ImplActiveWindowWatcher::new()?,
+            failed: false,
+        })
+    }
+
+    /// Block until the active window changes, `None` means that no window is active.
+    pub fn next_change(&mut self) -> XCapResult<Option<Window>> {
+        Ok(self.impl_watcher.next_change()?.map(Window::new))
+    }
+
+    /// Watch on a background thread. The thread stops after an error was sent, or once the
+    /// receiver is dropped and the next change arrives.
+    pub fn into_receiver(self) -> Receiver<XCapResult<Option<Window>>> {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for result in self {
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+/// Yields every change of the active window, blocking in between. The iterator ends after the
+/// first error.
+impl Iterator for ActiveWindowWatcher {
+    type Item = XCapResult<Option<Window>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = self.next_change();
+        self.failed = result.is_err();
+
+        Some(result)
+    }
+}
+
+/// A change of a top-level window reported by [`WindowWatcher`].
+#[derive(Debug, Clone)]
+pub enum WindowEvent {
+    Created(Window),
+    /// The window is gone, its getters fail but `id()` still identifies it.
+    Destroyed(Window),
+    Moved(Window),
+    Resized(Window),
+    TitleChanged(Window),
+    /// Minimized, maximized or another window manager state changed.
+    StateChanged(Window),
+}
+
+impl WindowEvent {
+    pub fn window(&self) -> &Window {
+        match self {
+            WindowEvent::Created(window)
+            | WindowEvent::Destroyed(window)
+            | WindowEvent::Moved(window)
+            | WindowEvent::Resized(window)
+            | WindowEvent::TitleChanged(window)
+            | WindowEvent::StateChanged(window) => window,
+        }
+    }
+}
+
+/// Notifies about created, destroyed, moved, resized and retitled windows.
+///
+/// On X11 this combines `SubstructureNotify` on the root window, `_NET_CLIENT_LIST` changes and
+/// `PropertyNotify` of every client window, platforms without change notifications compare
+/// snapshots periodically.
+#[derive(Debug)]
+pub struct WindowWatcher {
+    impl_watcher: ImplWindowWatcher,
+    failed: bool,
+}
+
+impl WindowWatcher {
+    pub fn new() -> XCapResult<WindowWatcher> {
+        Ok(WindowWatcher {
+            impl_watcher: ImplWindowWatcher::new()?,
+            failed: false,
+        })
+    }
+
+    /// Block until the next window event.
+    pub fn next_event(&mut self) -> XCapResult<WindowEvent> {
+        self.impl_watcher.next_event()
+    }
+
+    /// Watch on a background thread. The thread stops after an error was sent, or once the
+    /// receiver is dropped and the next event arrives.
+    pub fn into_receiver(self) -> Receiver<XCapResult<WindowEvent>> {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for result in self {
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+/// Yields every window event, blocking in between. The iterator ends after the first error.
+impl Iterator for WindowWatcher {
+    type Item = XCapResult<WindowEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = self.next_event();
+        self.failed = result.is_err();
+
+        Some(result)
+    }
+}
+
+/// A monitor property reported as changed by [`MonitorEvent::Changed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonitorField {
+    Position,
+    Size,
+    Rotation,
+    ScaleFactor,
+    Frequency,
+    Primary,
+}
+
+/// A monitor configuration change reported by [`MonitorWatcher`].
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    Added(Monitor),
+    /// The monitor is gone, captures and recorders using it should be rebuilt.
+    Removed(Monitor),
+    Changed(Monitor, Vec<MonitorField>),
+}
+
+impl MonitorEvent {
+    pub fn monitor(&self) -> &Monitor {
+        match self {
+            MonitorEvent::Added(monitor)
+            | MonitorEvent::Removed(monitor)
+            | MonitorEvent::Changed(monitor, _) => monitor,
+        }
+    }
+}
+
+/// Notifies about added, removed and reconfigured monitors.
+///
+/// On Linux this listens for RandR screen, CRTC and output changes, and for `wl_output` changes
+/// on Wayland. Platforms without change notifications compare snapshots periodically.
+/// The Linux listener threads close their connections shortly after the watcher is dropped.
+#[derive(Debug)]
+pub struct MonitorWatcher {
+    impl_watcher: ImplMonitorWatcher,
+    failed: bool,
+}
+
+impl MonitorWatcher {
+    pub fn new() -> XCapResult<MonitorWatcher> {
+        Ok(MonitorWatcher {
+            impl_watcher: ImplMonitorWatcher::new()?,
+            failed: false,
+        })
+    }
+
+    /// Block until the next monitor event.
+    pub fn next_event(&mut self) -> XCapResult<MonitorEvent> {
+        self.impl_watcher.next_event()
+    }
+
+    /// Watch on a background thread. The thread stops after an error was sent, or once the
+    /// receiver is dropped and the next event arrives.
+    pub fn into_receiver(self) -> Receiver<XCapResult<MonitorEvent>> {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for result in self {
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+/// Yields every monitor event, blocking in between. The iterator ends after the first error.
+impl Iterator for MonitorWatcher {
+    type Item = XCapResult<MonitorEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = self.next_event();
+        self.failed = result.is_err();
+
+        Some(result)
+    }
+}
@@ -1,6 +1,20 @@
+use std::path::Path;
+
 use image::RgbaImage;
 
-use crate::{Monitor, error::XCapResult, platform::impl_window::ImplWindow};
+use crate::{
+    Monitor,
+    capture_options::{CaptureOptions, CapturedImage, apply_color, apply_options},
+    error::XCapResult,
+    image_file::{SaveOptions, save_image},
+    pixel_format::PixelFormat,
+    platform::impl_window::ImplWindow,
+    process_info::ProcessInfo,
+    video_recorder::Frame,
+    window_query::WindowQuery,
+    window_type::WindowType,
+    workspace::Workspace,
+};
 
 #[derive(Debug, Clone)]
 pub struct Window {
@@ -23,6 +37,17 @@ impl Window {
 
         Ok(windows)
     }
+
+    /// The topmost window under the point, minimized and hidden windows are skipped.
+    /// The point uses the same coordinates as [`Monitor::from_point`].
+    pub fn from_point(x: i32, y: i32) -> XCapResult<Window> {
+        Ok(Window::new(ImplWindow::from_point(x, y)?))
+    }
+
+    /// Start a query for windows, e.g. `Window::find().app_name("firefox").visible_only().all()`.
+    pub fn find() -> WindowQuery {
+        WindowQuery::new()
+    }
 }
 
 impl Window {
@@ -34,6 +59,11 @@ impl Window {
     pub fn pid(&self) -> XCapResult<u32> {
         self.impl_window.pid()
     }
+    /// Executable, command line and start time of the window process. Fails for windows of
+    /// remote X clients, whose process id belongs to another machine.
+    pub fn process(&self) -> XCapResult<ProcessInfo> {
+        self.impl_window.process()
+    }
     /// The window app name
     pub fn app_name(&self) -> XCapResult<String> {
         self.impl_window.app_name()
@@ -78,10 +108,111 @@ impl Window {
     pub fn is_focused(&self) -> XCapResult<bool> {
         self.impl_window.is_focused()
     }
+    /// The window type, e.g. normal, dialog or dock.
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        self.impl_window.window_type()
+    }
+    /// The window is fullscreen.
+    pub fn is_fullscreen(&self) -> XCapResult<bool> {
+        self.impl_window.is_fullscreen()
+    }
+    /// The window is kept above other windows.
+    pub fn is_always_on_top(&self) -> XCapResult<bool> {
+        self.impl_window.is_always_on_top()
+    }
+    /// The window is shown on all workspaces.
+    pub fn is_sticky(&self) -> XCapResult<bool> {
+        self.impl_window.is_sticky()
+    }
+    /// The window is not shown in the taskbar.
+    pub fn is_skip_taskbar(&self) -> XCapResult<bool> {
+        self.impl_window.is_skip_taskbar()
+    }
+    /// The window opacity, from 0.0 (transparent) to 1.0 (opaque).
+    pub fn opacity(&self) -> XCapResult<f32> {
+        self.impl_window.opacity()
+    }
+    /// The window this dialog or popup belongs to.
+    pub fn transient_for(&self) -> XCapResult<Option<Window>> {
+        Ok(self.impl_window.transient_for()?.map(Window::new))
+    }
+    /// The index of the workspace the window is on, `None` when it is on all of them or unknown.
+    pub fn desktop(&self) -> XCapResult<Option<u32>> {
+        self.impl_window.desktop()
+    }
+    /// The workspace the window is on, `None` when it is on all of them or unknown.
+    pub fn workspace(&self) -> XCapResult<Option<Workspace>> {
+        let Some(index) = self.desktop()? else {
+            return Ok(None);
+        };
+
+        let workspace = Workspace::all()?
+            .into_iter()
+            .find(|workspace| workspace.index == index);
+
+        Ok(workspace)
+    }
+    /// The window icon closest to `preferred_size` pixels, `None` when the window has no icon.
+    pub fn icon(&self, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        self.impl_window.icon(preferred_size)
+    }
 }
 
 impl Window {
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         self.impl_window.capture_image()
     }
+
+    /// Capture image of the window as RGBA into `buffer`, rows are `stride` bytes apart.
+    /// Returns the size of the image. On Windows the window is captured into a temporary
+    /// image first, because it has to be rescaled and cropped.
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        self.impl_window.capture_into(buffer, stride)
+    }
+
+    /// Capture image of the window in `format`, converting at most once from the native pixels.
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        self.impl_window.capture_image_as(format)
+    }
+
+    /// Capture a thumbnail of the window that fits in `max_width` x `max_height`, keeping the
+    /// aspect ratio.
+    ///
+    /// X11 downscales while capturing and macOS captures at nominal resolution, Windows captures
+    /// at full size and downscales afterwards.
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        self.impl_window.capture_thumbnail(max_width, max_height)
+    }
+
+    /// Capture image of the window in the resolution requested by `options`.
+    pub fn capture_image_with_options(
+        &self,
+        options: &CaptureOptions,
+    ) -> XCapResult<CapturedImage> {
+        let mut image = self.impl_window.capture_image()?;
+        let monitor = self.current_monitor()?;
+        let icc_profile = apply_color(&mut image, options, || monitor.icc_profile())?;
+
+        Ok(apply_options(
+            image,
+            options,
+            self.impl_window.physical_size()?,
+            self.impl_window.logical_size()?,
+            monitor.scale_factor()?,
+            monitor.transform()?,
+            icc_profile,
+        ))
+    }
+
+    /// Capture image of the window and save it to `path`, the format is taken from
+    /// `options.format` or guessed from the file extension.
+    pub fn capture_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &SaveOptions,
+    ) -> XCapResult<()> {
+        let image = self.capture_image()?;
+
+        save_image(&image, path, options)
+    }
 }
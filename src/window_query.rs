@@ -0,0 +1,218 @@
+use crate::{
+    Monitor, Window, error::XCapResult, platform::impl_window::ImplWindow, redaction::WindowFilter,
+    workspace::Workspace,
+};
+
+/// Builder for searching windows, created by [`Window::find`].
+///
+/// All filters are evaluated against a single snapshot of the window list, cheap filters first,
+/// so that getters are only called for windows that are still candidates.
+#[derive(Debug, Clone, Default)]
+pub struct WindowQuery {
+    filters: Vec<WindowFilter>,
+    monitor: Option<Monitor>,
+    visible_only: bool,
+    current_workspace: bool,
+}
+
+impl WindowQuery {
+    pub fn new() -> WindowQuery {
+        WindowQuery::default()
+    }
+
+    pub fn id(self, id: u32) -> WindowQuery {
+        self.filter(WindowFilter::Id(id))
+    }
+
+    pub fn pid(self, pid: u32) -> WindowQuery {
+        self.filter(WindowFilter::Pid(pid))
+    }
+
+    pub fn app_name<S: Into<String>>(self, app_name: S) -> WindowQuery {
+        self.filter(WindowFilter::AppName(app_name.into()))
+    }
+
+    /// Window title matched against a glob pattern, `*` matches any run of characters and `?` a
+    /// single one. Not a regular expression.
+    pub fn title_glob<S: Into<String>>(self, pattern: S) -> WindowQuery {
+        self.filter(WindowFilter::TitleGlob(pattern.into()))
+    }
+
+    /// Window title matched against a regular expression.
+    #[cfg(feature = "regex")]
+    pub fn title_matches(self, regex: regex::Regex) -> WindowQuery {
+        self.filter(WindowFilter::TitleMatches(regex))
+    }
+
+    pub fn filter(mut self, filter: WindowFilter) -> WindowQuery {
+        self.filters.push(filter);
+        // 按开销从小到大判断，id 不需要额外请求
+        self.filters.sort_by_key(|filter| match filter {
+            WindowFilter::Id(_) => 0,
+            WindowFilter::Pid(_) => 1,
+            WindowFilter::AppName(_) => 2,
+            WindowFilter::TitleGlob(_) => 3,
+            #[cfg(feature = "regex")]
+            WindowFilter::TitleMatches(_) => 4,
+        });
+        self
+    }
+
+    /// Only windows whose current monitor is `monitor`.
+    pub fn on_monitor(mut self, monitor: &Monitor) -> WindowQuery {
+        self.monitor = Some(monitor.clone());
+        self
+    }
+
+    /// Skip windows that are not viewable, i.e. minimized, unmapped or on another workspace, and
+    /// windows without an area. Uses the same check as [`Window::from_point`].
+    pub fn visible_only(mut self) -> WindowQuery {
+        self.visible_only = true;
+        self
+    }
+
+    /// Skip windows on other workspaces, windows shown on all workspaces are kept. Has no effect
+    /// on platforms without workspaces.
+    pub fn current_workspace(mut self) -> WindowQuery {
+        self.current_workspace = true;
+        self
+    }
+
+    fn is_visible(window: &Window) -> bool {
+        window.impl_window.is_viewable().unwrap_or(false)
+            && window.width().is_ok_and(|width| width > 0)
+            && window.height().is_ok_and(|height| height > 0)
+    }
+
+    /// Monitor id and workspace index the windows have to be on.
+    fn targets(&self) -> XCapResult<(Option<u32>, Option<u32>)> {
+        let monitor_id = self.monitor.as_ref().map(Monitor::id).transpose()?;
+
+        let workspace_index = if self.current_workspace {
+            Workspace::current()?.map(|workspace| workspace.index)
+        } else {
+            None
+        };
+
+        Ok((monitor_id, workspace_index))
+    }
+
+    fn matches(
+        &self,
+        window: &Window,
+        monitor_id: Option<u32>,
+        workspace_index: Option<u32>,
+    ) -> bool {
+        if !self
+            .filters
+            .iter()
+            .all(|filter| filter.matches(window).unwrap_or(false))
+        {
+            return false;
+        }
+
+        if self.visible_only && !WindowQuery::is_visible(window) {
+            return false;
+        }
+
+        if let Some(workspace_index) = workspace_index {
+            let is_on_workspace = window
+                .desktop()
+                .is_ok_and(|desktop| desktop.is_none_or(|desktop| desktop == workspace_index));
+
+            if !is_on_workspace {
+                return false;
+            }
+        }
+
+        match monitor_id {
+            Some(monitor_id) => window
+                .current_monitor()
+                .and_then(|monitor| monitor.id())
+                .is_ok_and(|id| id == monitor_id),
+            None => true,
+        }
+    }
+
+    /// All matching windows, sorted by z coordinate like [`Window::all`].
+    pub fn all(&self) -> XCapResult<Vec<Window>> {
+        let (monitor_id, workspace_index) = self.targets()?;
+
+        let windows = ImplWindow::all()?
+            .into_iter()
+            .map(Window::new)
+            .filter(|window| self.matches(window, monitor_id, workspace_index))
+            .collect();
+
+        Ok(windows)
+    }
+
+    /// The topmost matching window.
+    pub fn first(&self) -> XCapResult<Option<Window>> {
+        let (monitor_id, workspace_index) = self.targets()?;
+
+        let window = ImplWindow::all()?
+            .into_iter()
+            .map(Window::new)
+            .find(|window| self.matches(window, monitor_id, workspace_index));
+
+        Ok(window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_order() {
+        // 无论添加顺序如何，开销小的过滤条件先判断
+        let query = WindowQuery::new()
+            .title_glob("*Firefox*")
+            .app_name("firefox")
+            .pid(42)
+            .id(7);
+
+        assert_eq!(
+            query.filters,
+            vec![
+                WindowFilter::Id(7),
+                WindowFilter::Pid(42),
+                WindowFilter::AppName(String::from("firefox")),
+                WindowFilter::TitleGlob(String::from("*Firefox*")),
+            ]
+        );
+
+        let query = WindowQuery::new()
+            .pid(1)
+            .title_glob("a")
+            .id(2)
+            .app_name("b")
+            .pid(3);
+
+        assert_eq!(
+            query.filters,
+            vec![
+                WindowFilter::Id(2),
+                WindowFilter::Pid(1),
+                WindowFilter::Pid(3),
+                WindowFilter::AppName(String::from("b")),
+                WindowFilter::TitleGlob(String::from("a")),
+            ]
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_title_matches() {
+        let query = WindowQuery::new()
+            .title_matches(regex::Regex::new("^Mozilla .*").unwrap())
+            .title_glob("*");
+
+        assert!(matches!(query.filters[0], WindowFilter::TitleGlob(_)));
+        assert_eq!(
+            query.filters[1],
+            WindowFilter::TitleMatches(regex::Regex::new("^Mozilla .*").unwrap())
+        );
+    }
+}
@@ -0,0 +1,25 @@
+/// The functional type of a window, following `_NET_WM_WINDOW_TYPE`.
+///
+/// Platforms without window types report the closest match, most windows are `Normal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WindowType {
+    #[default]
+    Normal,
+    Dialog,
+    /// Desktop background windows.
+    Desktop,
+    /// Panels, docks and menu bars.
+    Dock,
+    Toolbar,
+    /// Torn-off menus.
+    Menu,
+    Utility,
+    Splash,
+    DropdownMenu,
+    PopupMenu,
+    Tooltip,
+    Notification,
+    Combo,
+    /// Dragged items of a drag and drop operation.
+    Dnd,
+}
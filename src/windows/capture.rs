@@ -1,6 +1,8 @@
 use crate::{
     XCapError, XCapResult,
+    pixel_format::PixelFormat,
     platform::{impl_monitor::ImplMonitor, impl_window::ImplWindow},
+    video_recorder::Frame,
 };
 
 use image::RgbaImage;
@@ -35,14 +37,68 @@ pub(super) fn capture_monitor(
     height: Option<u32>,
 ) -> XCapResult<RgbaImage> {
     use super::wgc;
-    if let (Some(x), Some(y), Some(width), Some(height)) = (x, y, width, height) {
+    let frame = if let (Some(x), Some(y), Some(width), Some(height)) = (x, y, width, height) {
         let monitor_width = monitor.width()?;
         let monitor_height = monitor.height()?;
         check_capture_region(x, y, width, height, monitor_width, monitor_height)?;
-        wgc::capture_monitor(monitor.h_monitor, x, y, width, height)
+        wgc::capture_monitor(monitor.h_monitor, x, y, width, height, PixelFormat::Rgba)?
     } else {
-        wgc::capture_monitor(monitor.h_monitor, 0, 0, monitor.width()?, monitor.height()?)
-    }
+        capture_monitor_as(monitor, PixelFormat::Rgba)?
+    };
+
+    RgbaImage::from_raw(frame.width, frame.height, frame.raw)
+        .ok_or(XCapError::new("RgbaImage::from_raw failed"))
+}
+
+#[cfg(feature = "wgc")]
+pub(super) fn capture_monitor_as(monitor: &ImplMonitor, format: PixelFormat) -> XCapResult<Frame> {
+    use super::wgc;
+
+    wgc::capture_monitor(
+        monitor.h_monitor,
+        0,
+        0,
+        monitor.width()?,
+        monitor.height()?,
+        format,
+    )
+}
+
+#[cfg(feature = "wgc")]
+pub(super) fn capture_monitor_into(
+    monitor: &ImplMonitor,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    use crate::pixel_format::copy_rows;
+
+    // WGC 在回调线程中读取纹理，无法直接写入调用方的缓冲区，只能多拷贝一次
+    let frame = capture_monitor_as(monitor, format)?;
+    copy_rows(
+        &frame.raw,
+        frame.width,
+        frame.height,
+        format,
+        buffer,
+        stride,
+    )?;
+
+    Ok((frame.width, frame.height))
+}
+
+#[cfg(feature = "wgc")]
+pub(super) fn capture_monitor_thumbnail(
+    monitor: &ImplMonitor,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    use crate::thumbnail::thumbnail_image;
+
+    // WGC 只能得到完整尺寸的纹理，截图后再缩小
+    let image = capture_monitor(monitor, None, None, None, None)?;
+
+    Ok(thumbnail_image(image, max_width, max_height))
 }
 
 #[cfg(not(feature = "wgc"))]
@@ -78,6 +134,66 @@ pub(super) fn capture_monitor(
     }
 }
 
+#[cfg(not(feature = "wgc"))]
+pub(super) fn capture_monitor_as(monitor: &ImplMonitor, format: PixelFormat) -> XCapResult<Frame> {
+    use super::gdi;
+
+    gdi::capture_monitor_as(
+        monitor.x()?,
+        monitor.y()?,
+        monitor.width()? as i32,
+        monitor.height()? as i32,
+        format,
+    )
+}
+
+#[cfg(not(feature = "wgc"))]
+pub(super) fn capture_monitor_into(
+    monitor: &ImplMonitor,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<(u32, u32)> {
+    use super::gdi;
+
+    let width = monitor.width()?;
+    let height = monitor.height()?;
+    gdi::capture_monitor_into(
+        monitor.x()?,
+        monitor.y()?,
+        width as i32,
+        height as i32,
+        format,
+        buffer,
+        stride,
+    )?;
+
+    Ok((width, height))
+}
+
+#[cfg(not(feature = "wgc"))]
+pub(super) fn capture_monitor_thumbnail(
+    monitor: &ImplMonitor,
+    max_width: u32,
+    max_height: u32,
+) -> XCapResult<RgbaImage> {
+    use super::gdi;
+    use crate::thumbnail::thumbnail_size;
+
+    let width = monitor.width()?;
+    let height = monitor.height()?;
+    let (dst_width, dst_height) = thumbnail_size(width, height, max_width, max_height);
+
+    gdi::capture_monitor_thumbnail(
+        monitor.x()?,
+        monitor.y()?,
+        width as i32,
+        height as i32,
+        dst_width as i32,
+        dst_height as i32,
+    )
+}
+
 #[cfg(feature = "wgc")]
 pub(super) fn capture_window(window: &ImplWindow) -> XCapResult<RgbaImage> {
     use windows::Win32::System::Threading::{GetCurrentProcess, PROCESS_QUERY_LIMITED_INFORMATION};
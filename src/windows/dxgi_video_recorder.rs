@@ -23,7 +23,8 @@ use windows::{
 
 use crate::{
     XCapError, XCapResult,
-    video_recorder::{Frame, RecorderWaker},
+    pixel_format::PixelFormat,
+    video_recorder::{Frame, FramePool, RecorderWaker},
 };
 
 use super::utils::{create_d3d_device, texture_to_frame};
@@ -34,11 +35,17 @@ pub(crate) struct ImplVideoRecorder {
     d3d_context: ID3D11DeviceContext,
     duplication: IDXGIOutputDuplication,
     recorder_waker: Arc<RecorderWaker>,
+    format: PixelFormat,
+    pool: FramePool,
     tx: SyncSender<Frame>,
 }
 
 impl ImplVideoRecorder {
-    pub fn new(h_monitor: HMONITOR) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        h_monitor: HMONITOR,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         unsafe {
             let d3d_device = create_d3d_device(
                 D3D11_CREATE_DEVICE_BGRA_SUPPORT | D3D11_CREATE_DEVICE_SINGLETHREADED,
@@ -64,6 +71,8 @@ impl ImplVideoRecorder {
                         d3d_context,
                         duplication,
                         recorder_waker: Arc::new(RecorderWaker::new()),
+                        format,
+                        pool,
                         tx,
                     };
                     s.on_frame()?;
@@ -78,6 +87,8 @@ impl ImplVideoRecorder {
         let d3d_device = self.d3d_device.clone();
         let d3d_context = self.d3d_context.clone();
         let recorder_waker = self.recorder_waker.clone();
+        let format = self.format;
+        let pool = self.pool.clone();
         let tx = self.tx.clone();
 
         thread::spawn(move || {
@@ -114,6 +125,8 @@ impl ImplVideoRecorder {
                                     0,
                                     source_texture_desc.Width,
                                     source_texture_desc.Height,
+                                    format,
+                                    &pool,
                                 )?;
                                 let _ = tx.send(frame);
                             }
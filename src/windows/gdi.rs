@@ -10,8 +10,8 @@ use windows::Win32::{
         Gdi::{
             BITMAP, BITMAPINFO, BITMAPINFOHEADER, BitBlt, CreateCompatibleBitmap,
             CreateCompatibleDC, DIB_RGB_COLORS, DeleteDC, DeleteObject, GetCurrentObject,
-            GetDIBits, GetObjectW, GetWindowDC, HBITMAP, HDC, OBJ_BITMAP, ReleaseDC, SRCCOPY,
-            SelectObject,
+            GetDIBits, GetObjectW, GetWindowDC, HALFTONE, HBITMAP, HDC, OBJ_BITMAP, ReleaseDC,
+            SRCCOPY, SelectObject, SetBrushOrgEx, SetStretchBltMode, StretchBlt,
         },
     },
     Storage::Xps::{PRINT_WINDOW_FLAGS, PrintWindow},
@@ -22,17 +22,21 @@ use windows::Win32::{
 
 use crate::{
     error::{XCapError, XCapResult},
+    pixel_format::{PixelFormat, check_buffer},
     platform::utils::get_window_bounds,
+    video_recorder::Frame,
 };
 
-use super::utils::{bgra_to_rgba, get_os_major_version};
+use super::utils::{bgra_rows_into, bgra_to_rgba, fix_alpha, get_os_major_version};
 
-fn to_rgba_image(
+/// 读取位图的 BGRA 像素，`buffer` 的长度必须为 width * height * 4
+fn get_dib_bits(
     hdc_mem: HDC,
     h_bitmap: HBITMAP,
     width: i32,
     height: i32,
-) -> XCapResult<RgbaImage> {
+    buffer: &mut [u8],
+) -> XCapResult<()> {
     let buffer_size = width * height * 4;
     let mut bitmap_info = BITMAPINFO {
         bmiHeader: BITMAPINFOHEADER {
@@ -48,8 +52,6 @@ fn to_rgba_image(
         ..Default::default()
     };
 
-    let mut buffer = vec![0u8; buffer_size as usize];
-
     unsafe {
         let is_failed = GetDIBits(
             hdc_mem,
@@ -66,6 +68,18 @@ fn to_rgba_image(
         }
     };
 
+    Ok(())
+}
+
+fn to_rgba_image(
+    hdc_mem: HDC,
+    h_bitmap: HBITMAP,
+    width: i32,
+    height: i32,
+) -> XCapResult<RgbaImage> {
+    let mut buffer = vec![0u8; (width * height * 4) as usize];
+    get_dib_bits(hdc_mem, h_bitmap, width, height, &mut buffer)?;
+
     RgbaImage::from_raw(width as u32, height as u32, bgra_to_rgba(buffer))
         .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
 }
@@ -80,7 +94,27 @@ fn delete_bitmap_object(val: HBITMAP) {
     }
 }
 
-pub(super) fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapResult<RgbaImage> {
+/// 将桌面的指定区域复制到内存位图后交给 `read` 读取像素
+fn blt_monitor<T>(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    read: impl FnOnce(HDC, HBITMAP) -> XCapResult<T>,
+) -> XCapResult<T> {
+    stretch_blt_monitor(x, y, width, height, width, height, read)
+}
+
+/// 与 blt_monitor 相同，内存位图的尺寸为 `dst_width` x `dst_height`，尺寸不同时由 GDI 缩放
+fn stretch_blt_monitor<T>(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    dst_width: i32,
+    dst_height: i32,
+    read: impl FnOnce(HDC, HBITMAP) -> XCapResult<T>,
+) -> XCapResult<T> {
     unsafe {
         let hwnd = GetDesktopWindow();
         let scope_guard_hdc_desktop_window = guard(GetWindowDC(Some(hwnd)), |val| {
@@ -99,28 +133,148 @@ pub(super) fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapRe
         );
 
         let scope_guard_h_bitmap = guard(
-            CreateCompatibleBitmap(*scope_guard_hdc_desktop_window, width, height),
+            CreateCompatibleBitmap(*scope_guard_hdc_desktop_window, dst_width, dst_height),
             delete_bitmap_object,
         );
 
         SelectObject(*scope_guard_mem, (*scope_guard_h_bitmap).into());
 
-        BitBlt(
-            *scope_guard_mem,
-            0,
-            0,
-            width,
-            height,
-            Some(*scope_guard_hdc_desktop_window),
-            x,
-            y,
-            SRCCOPY,
-        )?;
+        if (dst_width, dst_height) == (width, height) {
+            BitBlt(
+                *scope_guard_mem,
+                0,
+                0,
+                width,
+                height,
+                Some(*scope_guard_hdc_desktop_window),
+                x,
+                y,
+                SRCCOPY,
+            )?;
+        } else {
+            // HALFTONE 会对源像素取平均，缩小时效果接近 box filter，设置该模式后需要重置画刷原点
+            SetStretchBltMode(*scope_guard_mem, HALFTONE);
+            let _ = SetBrushOrgEx(*scope_guard_mem, 0, 0, None);
+
+            let is_success = StretchBlt(
+                *scope_guard_mem,
+                0,
+                0,
+                dst_width,
+                dst_height,
+                Some(*scope_guard_hdc_desktop_window),
+                x,
+                y,
+                width,
+                height,
+                SRCCOPY,
+            )
+            .as_bool();
 
-        to_rgba_image(*scope_guard_mem, *scope_guard_h_bitmap, width, height)
+            if !is_success {
+                return Err(XCapError::new("StretchBlt failed"));
+            }
+        }
+
+        read(*scope_guard_mem, *scope_guard_h_bitmap)
     }
 }
 
+pub(super) fn capture_monitor(x: i32, y: i32, width: i32, height: i32) -> XCapResult<RgbaImage> {
+    blt_monitor(x, y, width, height, |hdc_mem, h_bitmap| {
+        to_rgba_image(hdc_mem, h_bitmap, width, height)
+    })
+}
+
+/// 由 GDI 直接缩小到 `dst_width` x `dst_height`，不会读取完整尺寸的像素
+pub(super) fn capture_monitor_thumbnail(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    dst_width: i32,
+    dst_height: i32,
+) -> XCapResult<RgbaImage> {
+    stretch_blt_monitor(
+        x,
+        y,
+        width,
+        height,
+        dst_width,
+        dst_height,
+        |hdc_mem, h_bitmap| {
+            let mut image = to_rgba_image(hdc_mem, h_bitmap, dst_width, dst_height)?;
+            // HALFTONE 缩放不保证 alpha 通道，屏幕内容都是不透明的
+            for pixel in image.pixels_mut() {
+                pixel[3] = 255;
+            }
+
+            Ok(image)
+        },
+    )
+}
+
+pub(super) fn capture_monitor_into(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    format: PixelFormat,
+    buffer: &mut [u8],
+    stride: usize,
+) -> XCapResult<()> {
+    check_buffer(width as u32, height as u32, format, buffer.len(), stride)?;
+
+    blt_monitor(x, y, width, height, |hdc_mem, h_bitmap| {
+        let row_len = width as usize * 4;
+
+        // 32 位 DIB 的行紧密排列，4 字节格式且行距相同时直接写入调用方的缓冲区
+        if format.bytes_per_pixel() == 4 && stride == row_len {
+            let buffer = &mut buffer[..row_len * height as usize];
+            get_dib_bits(hdc_mem, h_bitmap, width, height, buffer)?;
+            if format == PixelFormat::Rgba {
+                for pixel in buffer.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+            }
+            fix_alpha(buffer, width as u32, height as u32, stride, format);
+
+            return Ok(());
+        }
+
+        let mut bgra = vec![0u8; row_len * height as usize];
+        get_dib_bits(hdc_mem, h_bitmap, width, height, &mut bgra)?;
+        bgra_rows_into(
+            &bgra,
+            row_len,
+            width as u32,
+            height as u32,
+            buffer,
+            stride,
+            format,
+        )
+    })
+}
+
+pub(super) fn capture_monitor_as(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    format: PixelFormat,
+) -> XCapResult<Frame> {
+    let stride = width as usize * format.bytes_per_pixel();
+    let mut raw = vec![0u8; stride * height as usize];
+    capture_monitor_into(x, y, width, height, format, &mut raw, stride)?;
+
+    Ok(Frame::new_with_format(
+        width as u32,
+        height as u32,
+        format,
+        raw,
+    ))
+}
+
 pub(super) fn capture_window(hwnd: HWND) -> XCapResult<RgbaImage> {
     let window_bounds = get_window_bounds(hwnd)?;
 
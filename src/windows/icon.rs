@@ -0,0 +1,130 @@
+use std::{ffi::c_void, mem};
+
+use image::RgbaImage;
+use scopeguard::guard;
+use windows::Win32::{
+    Foundation::GetLastError,
+    Graphics::Gdi::{
+        BITMAP, BITMAPINFO, BITMAPINFOHEADER, CreateCompatibleDC, DIB_RGB_COLORS, DeleteDC,
+        DeleteObject, GetDIBits, GetObjectW, HBITMAP, HDC,
+    },
+    UI::WindowsAndMessaging::{GetIconInfo, HICON, ICONINFO},
+};
+
+use crate::error::{XCapError, XCapResult};
+
+/// Pixels of `h_bitmap` as top-down 32 bit BGRA.
+fn get_bitmap_bgra(
+    hdc_mem: HDC,
+    h_bitmap: HBITMAP,
+    width: i32,
+    height: i32,
+) -> XCapResult<Vec<u8>> {
+    let buffer_size = width * height * 4;
+    let mut bitmap_info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            biHeight: -height,
+            biPlanes: 1,
+            biBitCount: 32,
+            biSizeImage: buffer_size as u32,
+            biCompression: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut buffer = vec![0u8; buffer_size as usize];
+
+    unsafe {
+        let is_failed = GetDIBits(
+            hdc_mem,
+            h_bitmap,
+            0,
+            height as u32,
+            Some(buffer.as_mut_ptr().cast()),
+            &mut bitmap_info,
+            DIB_RGB_COLORS,
+        ) == 0;
+
+        if is_failed {
+            return Err(XCapError::new("Get RGBA data failed"));
+        }
+    };
+
+    Ok(buffer)
+}
+
+fn delete_icon_bitmap(val: HBITMAP) {
+    if val.is_invalid() {
+        return;
+    }
+
+    unsafe {
+        if !DeleteObject(val.into()).as_bool() {
+            log::error!("DeleteObject({:?}) failed: {:?}", val, GetLastError());
+        }
+    }
+}
+
+/// Draw `hicon` at its native size, the AND mask is used for icons without alpha channel.
+pub(super) fn capture_icon(hicon: HICON) -> XCapResult<Option<RgbaImage>> {
+    unsafe {
+        let mut icon_info = ICONINFO::default();
+        GetIconInfo(hicon, &mut icon_info)?;
+
+        // GetIconInfo 创建的位图需要调用方释放
+        let scope_guard_mask = guard(icon_info.hbmMask, delete_icon_bitmap);
+        let scope_guard_color = guard(icon_info.hbmColor, delete_icon_bitmap);
+
+        // 单色图标没有彩色位图
+        if scope_guard_color.is_invalid() {
+            return Ok(None);
+        }
+
+        let mut bitmap = BITMAP::default();
+        if GetObjectW(
+            (*scope_guard_color).into(),
+            mem::size_of::<BITMAP>() as i32,
+            Some(&mut bitmap as *mut BITMAP as *mut c_void),
+        ) == 0
+        {
+            return Err(XCapError::new("GetObjectW failed"));
+        }
+
+        let scope_guard_mem = guard(CreateCompatibleDC(None), |val| {
+            if !DeleteDC(val).as_bool() {
+                log::error!("DeleteDC({:?}) failed: {:?}", val, GetLastError());
+            }
+        });
+
+        let mut buffer = get_bitmap_bgra(
+            *scope_guard_mem,
+            *scope_guard_color,
+            bitmap.bmWidth,
+            bitmap.bmHeight,
+        )?;
+
+        // 没有 alpha 通道的图标由 AND 掩码决定透明区域，掩码为 1 的像素透明
+        if buffer.chunks_exact(4).all(|pixel| pixel[3] == 0) {
+            let mask = get_bitmap_bgra(
+                *scope_guard_mem,
+                *scope_guard_mask,
+                bitmap.bmWidth,
+                bitmap.bmHeight,
+            )?;
+            for (pixel, mask) in buffer.chunks_exact_mut(4).zip(mask.chunks_exact(4)) {
+                pixel[3] = 255 - mask[0];
+            }
+        }
+
+        for pixel in buffer.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+
+        RgbaImage::from_raw(bitmap.bmWidth as u32, bitmap.bmHeight as u32, buffer)
+            .map(Some)
+            .ok_or_else(|| XCapError::new("RgbaImage::from_raw failed"))
+    }
+}
@@ -20,12 +20,18 @@ use windows::{
 };
 
 use crate::{
+    capture_options::scale_size,
+    edid::Edid,
     error::{XCapError, XCapResult},
-    video_recorder::Frame,
+    pixel_format::PixelFormat,
+    transform::Transform,
+    video_recorder::{Frame, FramePool, VideoRecorderOptions},
 };
 
 use super::{
-    capture::capture_monitor,
+    capture::{
+        capture_monitor, capture_monitor_as, capture_monitor_into, capture_monitor_thumbnail,
+    },
     impl_video_recorder::ImplVideoRecorder,
     utils::{get_monitor_config, get_process_is_dpi_awareness, load_library},
 };
@@ -38,6 +44,10 @@ pub(crate) struct ImplMonitor {
     pub h_monitor: HMONITOR,
 }
 
+// HMONITOR 只是系统范围内的显示器标识，不绑定创建它的线程，可以在任意线程使用
+unsafe impl Send for ImplMonitor {}
+unsafe impl Sync for ImplMonitor {}
+
 extern "system" fn monitor_enum_proc(
     h_monitor: HMONITOR,
     _: HDC,
@@ -219,6 +229,10 @@ impl ImplMonitor {
         Ok(name)
     }
 
+    pub fn edid(&self) -> XCapResult<Edid> {
+        Err(XCapError::NotSupported)
+    }
+
     pub fn x(&self) -> XCapResult<i32> {
         let dev_mode_w = get_dev_mode_w(self.h_monitor)?;
         let dm_position = unsafe { dev_mode_w.Anonymous1.Anonymous2.dmPosition };
@@ -243,6 +257,21 @@ impl ImplMonitor {
         Ok(dev_mode_w.dmPelsHeight)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        let scale_factor = self.scale_factor()?;
+
+        Ok(scale_size(self.physical_size()?, 1.0 / scale_factor))
+    }
+
+    /// Bounds of the monitor in the coordinate space of window geometry.
+    pub fn window_space_bounds(&self) -> XCapResult<(i32, i32, u32, u32)> {
+        Ok((self.x()?, self.y()?, self.width()?, self.height()?))
+    }
+
     pub fn rotation(&self) -> XCapResult<f32> {
         let dev_mode_w = get_dev_mode_w(self.h_monitor)?;
         let dm_display_orientation =
@@ -257,6 +286,15 @@ impl ImplMonitor {
         Ok(rotation)
     }
 
+    pub fn transform(&self) -> XCapResult<Transform> {
+        Ok(Transform::new(self.rotation()? as u32, false, false))
+    }
+
+    pub fn icc_profile(&self) -> XCapResult<Option<Vec<u8>>> {
+        // 尚未实现读取显示器的 ICC profile
+        Ok(None)
+    }
+
     pub fn scale_factor(&self) -> XCapResult<f32> {
         get_scale_factor(self.h_monitor)
     }
@@ -282,13 +320,29 @@ impl ImplMonitor {
         capture_monitor(self, None, None, None, None)
     }
 
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        capture_monitor_as(self, format)
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        capture_monitor_into(self, PixelFormat::Rgba, buffer, stride)
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        capture_monitor_thumbnail(self, max_width, max_height)
+    }
+
     pub fn capture_region(&self, x: u32, y: u32, width: u32, height: u32) -> XCapResult<RgbaImage> {
         let image = capture_monitor(self, Some(x), Some(y), Some(width), Some(height))?;
         Ok(image)
     }
 
-    pub fn video_recorder(&self) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
-        ImplVideoRecorder::new(self.h_monitor)
+    pub fn video_recorder(
+        &self,
+        options: &VideoRecorderOptions,
+        pool: FramePool,
+    ) -> XCapResult<(ImplVideoRecorder, Receiver<Frame>)> {
+        ImplVideoRecorder::new(self.h_monitor, options.format, pool)
     }
 }
 
@@ -0,0 +1,8 @@
+use crate::polling_watcher::{
+    PollingActiveWindowWatcher, PollingMonitorWatcher, PollingWindowWatcher,
+};
+
+// 没有可用的窗口和显示器变化通知，通过轮询实现
+pub(crate) type ImplActiveWindowWatcher = PollingActiveWindowWatcher;
+pub(crate) type ImplWindowWatcher = PollingWindowWatcher;
+pub(crate) type ImplMonitorWatcher = PollingMonitorWatcher;
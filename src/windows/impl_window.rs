@@ -1,11 +1,17 @@
 use core::slice;
-use std::{ffi::c_void, mem, ptr};
+use std::{
+    ffi::c_void,
+    mem,
+    path::PathBuf,
+    ptr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use image::RgbaImage;
 use widestring::U16CString;
 use windows::{
     Win32::{
-        Foundation::{GetLastError, HANDLE, HWND, LPARAM, MAX_PATH, TRUE},
+        Foundation::{FILETIME, GetLastError, HANDLE, HWND, LPARAM, MAX_PATH, TRUE, WPARAM},
         Graphics::{
             Dwm::{DWMWA_CLOAKED, DwmGetWindowAttribute},
             Gdi::{IsRectEmpty, MONITOR_DEFAULTTONEAREST, MonitorFromWindow},
@@ -13,21 +19,37 @@ use windows::{
         Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW},
         System::{
             ProcessStatus::{GetModuleBaseNameW, GetModuleFileNameExW},
-            Threading::{GetCurrentProcessId, PROCESS_QUERY_LIMITED_INFORMATION},
+            Threading::{
+                GetCurrentProcessId, GetProcessTimes, PROCESS_NAME_WIN32,
+                PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW,
+            },
         },
         UI::WindowsAndMessaging::{
-            EnumWindows, GWL_EXSTYLE, GetClassNameW, GetForegroundWindow, GetWindowLongPtrW,
-            GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow,
-            IsWindowVisible, IsZoomed, WINDOW_EX_STYLE, WS_EX_TOOLWINDOW,
+            EnumWindows, GCLP_HICON, GCLP_HICONSM, GW_OWNER, GWL_EXSTYLE, GetClassLongPtrW,
+            GetClassNameW, GetForegroundWindow, GetLayeredWindowAttributes, GetWindow,
+            GetWindowLongPtrW, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+            HICON, ICON_BIG, ICON_SMALL2, IsIconic, IsWindow, IsWindowVisible, IsZoomed,
+            LAYERED_WINDOW_ATTRIBUTES_FLAGS, LWA_ALPHA, SMTO_ABORTIFHUNG, SendMessageTimeoutW,
+            WINDOW_EX_STYLE, WM_GETICON, WS_EX_APPWINDOW, WS_EX_LAYERED, WS_EX_TOOLWINDOW,
+            WS_EX_TOPMOST,
         },
     },
-    core::{BOOL, HSTRING, PCWSTR},
+    core::{BOOL, HSTRING, PCWSTR, PWSTR},
 };
 
-use crate::error::XCapResult;
+use crate::{
+    capture_options::scale_size,
+    error::{XCapError, XCapResult},
+    pixel_format::{PixelFormat, copy_image_into},
+    process_info::ProcessInfo,
+    thumbnail::thumbnail_image,
+    video_recorder::Frame,
+    window_type::WindowType,
+};
 
 use super::{
     capture::capture_window,
+    icon::capture_icon,
     impl_monitor::ImplMonitor,
     utils::{get_window_bounds, open_process},
 };
@@ -61,6 +83,24 @@ fn is_window_cloaked(hwnd: HWND) -> bool {
 }
 
 // https://webrtc.googlesource.com/src.git/+/refs/heads/main/modules/desktop_capture/win/window_capture_utils.cc#52
+fn get_window_ex_style(hwnd: HWND) -> WINDOW_EX_STYLE {
+    unsafe { WINDOW_EX_STYLE(GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32) }
+}
+
+fn get_window_owner(hwnd: HWND) -> Option<HWND> {
+    unsafe { GetWindow(hwnd, GW_OWNER).ok() }
+}
+
+fn get_class_name(hwnd: HWND) -> XCapResult<String> {
+    let mut lp_class_name = [0u16; MAX_PATH as usize];
+    let lp_class_name_length = unsafe { GetClassNameW(hwnd, &mut lp_class_name) } as usize;
+
+    let class_name =
+        U16CString::from_vec_truncate(&lp_class_name[0..lp_class_name_length]).to_string()?;
+
+    Ok(class_name)
+}
+
 fn is_valid_window(hwnd: HWND) -> bool {
     unsafe {
         // ignore invisible windows
@@ -306,6 +346,100 @@ fn get_app_name(pid: u32) -> XCapResult<String> {
     }
 }
 
+/// FILETIME counts 100 nanosecond intervals since 1601-01-01.
+fn filetime_to_system_time(filetime: FILETIME) -> Option<SystemTime> {
+    let intervals = ((filetime.dwHighDateTime as u64) << 32) | filetime.dwLowDateTime as u64;
+    // 1601-01-01 到 1970-01-01 之间的秒数
+    let unix_intervals = intervals.checked_sub(11_644_473_600 * 10_000_000)?;
+
+    Some(UNIX_EPOCH + Duration::from_nanos(unix_intervals * 100))
+}
+
+fn get_process_info(pid: u32) -> XCapResult<ProcessInfo> {
+    unsafe {
+        let scope_guard_handle = open_process(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?;
+
+        // 长路径最多 32767 个字符
+        let mut exe_name = vec![0u16; 32768];
+        let mut exe_name_len = exe_name.len() as u32;
+        let exe_path = QueryFullProcessImageNameW(
+            *scope_guard_handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(exe_name.as_mut_ptr()),
+            &mut exe_name_len,
+        )
+        .ok()
+        .map(|_| PathBuf::from(String::from_utf16_lossy(&exe_name[..exe_name_len as usize])));
+
+        let name = exe_path
+            .as_ref()
+            .and_then(|exe_path| exe_path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut creation_time = FILETIME::default();
+        let mut exit_time = FILETIME::default();
+        let mut kernel_time = FILETIME::default();
+        let mut user_time = FILETIME::default();
+        let start_time = GetProcessTimes(
+            *scope_guard_handle,
+            &mut creation_time,
+            &mut exit_time,
+            &mut kernel_time,
+            &mut user_time,
+        )
+        .ok()
+        .and_then(|_| filetime_to_system_time(creation_time));
+
+        Ok(ProcessInfo {
+            pid,
+            name,
+            exe_path,
+            // 读取其他进程的命令行需要访问它的 PEB，没有公开的 API
+            cmdline: Vec::new(),
+            start_time,
+        })
+    }
+}
+
+/// The icon the window shows in its title bar or the taskbar, falling back to the class icon.
+fn get_window_icon(hwnd: HWND, preferred_size: u32) -> Option<HICON> {
+    // 小图标一般为 16x16，大图标为 32x32
+    let icon_types = if preferred_size <= 16 {
+        [(ICON_SMALL2, GCLP_HICONSM), (ICON_BIG, GCLP_HICON)]
+    } else {
+        [(ICON_BIG, GCLP_HICON), (ICON_SMALL2, GCLP_HICONSM)]
+    };
+
+    unsafe {
+        for (icon_type, _) in icon_types {
+            let mut result = 0usize;
+            // 窗口无响应时不等待
+            SendMessageTimeoutW(
+                hwnd,
+                WM_GETICON,
+                WPARAM(icon_type as usize),
+                LPARAM(0),
+                SMTO_ABORTIFHUNG,
+                100,
+                Some(&mut result as *mut usize),
+            );
+            if result != 0 {
+                return Some(HICON(result as *mut c_void));
+            }
+        }
+
+        for (_, class_index) in icon_types {
+            let result = GetClassLongPtrW(hwnd, class_index);
+            if result != 0 {
+                return Some(HICON(result as *mut c_void));
+            }
+        }
+    }
+
+    None
+}
+
 impl ImplWindow {
     fn new(hwnd: HWND) -> ImplWindow {
         ImplWindow { hwnd }
@@ -327,6 +461,24 @@ impl ImplWindow {
 
         Ok(impl_windows)
     }
+
+    pub fn from_point(x: i32, y: i32) -> XCapResult<ImplWindow> {
+        // all 返回的窗口从顶层到底层排列，第一个包含该点的可见窗口即为结果
+        for impl_window in ImplWindow::all()? {
+            let contains_point = (|| -> XCapResult<bool> {
+                let (left, top) = (impl_window.x()?, impl_window.y()?);
+                let (width, height) = (impl_window.width()?, impl_window.height()?);
+
+                Ok(x >= left && x < left + width as i32 && y >= top && y < top + height as i32)
+            })();
+
+            if contains_point.unwrap_or(false) && impl_window.is_viewable().unwrap_or(false) {
+                return Ok(impl_window);
+            }
+        }
+
+        Err(XCapError::new("Not found window"))
+    }
 }
 
 impl ImplWindow {
@@ -339,6 +491,10 @@ impl ImplWindow {
         Ok(pid)
     }
 
+    pub fn process(&self) -> XCapResult<ProcessInfo> {
+        get_process_info(self.pid()?)
+    }
+
     pub fn app_name(&self) -> XCapResult<String> {
         get_app_name(self.pid()?)
     }
@@ -393,10 +549,27 @@ impl ImplWindow {
         Ok((rect.bottom - rect.top) as u32)
     }
 
+    pub fn physical_size(&self) -> XCapResult<(u32, u32)> {
+        Ok((self.width()?, self.height()?))
+    }
+
+    pub fn logical_size(&self) -> XCapResult<(u32, u32)> {
+        let scale_factor = self.current_monitor()?.scale_factor()?;
+
+        Ok(scale_size(self.physical_size()?, 1.0 / scale_factor))
+    }
+
     pub fn is_minimized(&self) -> XCapResult<bool> {
         unsafe { Ok(IsIconic(self.hwnd).as_bool()) }
     }
 
+    /// 未最小化且可见，被隐藏（cloaked）的窗口视为不可见
+    pub fn is_viewable(&self) -> XCapResult<bool> {
+        let is_visible = unsafe { IsWindowVisible(self.hwnd).as_bool() };
+
+        Ok(is_visible && !self.is_minimized()? && !is_window_cloaked(self.hwnd))
+    }
+
     pub fn is_maximized(&self) -> XCapResult<bool> {
         unsafe { Ok(IsZoomed(self.hwnd).as_bool()) }
     }
@@ -405,9 +578,111 @@ impl ImplWindow {
         unsafe { Ok(GetForegroundWindow() == self.hwnd) }
     }
 
+    pub fn window_type(&self) -> XCapResult<WindowType> {
+        let class_name = get_class_name(self.hwnd)?;
+
+        // Shell_TrayWnd 是任务栏，#32770 是系统对话框的窗口类
+        let window_type = if class_name == "Shell_TrayWnd" {
+            WindowType::Dock
+        } else if get_window_ex_style(self.hwnd).contains(WS_EX_TOOLWINDOW) {
+            WindowType::Utility
+        } else if class_name == "#32770" || get_window_owner(self.hwnd).is_some() {
+            WindowType::Dialog
+        } else {
+            WindowType::Normal
+        };
+
+        Ok(window_type)
+    }
+
+    pub fn is_fullscreen(&self) -> XCapResult<bool> {
+        let rect = get_window_bounds(self.hwnd)?;
+        let impl_monitor = self.current_monitor()?;
+
+        Ok(rect.left <= impl_monitor.x()?
+            && rect.top <= impl_monitor.y()?
+            && rect.right - rect.left >= impl_monitor.width()? as i32
+            && rect.bottom - rect.top >= impl_monitor.height()? as i32)
+    }
+
+    pub fn is_always_on_top(&self) -> XCapResult<bool> {
+        Ok(get_window_ex_style(self.hwnd).contains(WS_EX_TOPMOST))
+    }
+
+    pub fn is_sticky(&self) -> XCapResult<bool> {
+        // 固定到所有虚拟桌面需要未公开的 COM 接口才能读取
+        Ok(false)
+    }
+
+    pub fn is_skip_taskbar(&self) -> XCapResult<bool> {
+        let ex_style = get_window_ex_style(self.hwnd);
+
+        // 有所有者的窗口只有设置了 WS_EX_APPWINDOW 才会显示在任务栏
+        Ok(ex_style.contains(WS_EX_TOOLWINDOW)
+            || (get_window_owner(self.hwnd).is_some() && !ex_style.contains(WS_EX_APPWINDOW)))
+    }
+
+    pub fn opacity(&self) -> XCapResult<f32> {
+        if !get_window_ex_style(self.hwnd).contains(WS_EX_LAYERED) {
+            return Ok(1.0);
+        }
+
+        let mut alpha = 255u8;
+        let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS::default();
+        unsafe {
+            GetLayeredWindowAttributes(
+                self.hwnd,
+                None,
+                Some(&mut alpha as *mut u8),
+                Some(&mut flags as *mut LAYERED_WINDOW_ATTRIBUTES_FLAGS),
+            )?;
+        }
+
+        if flags.contains(LWA_ALPHA) {
+            Ok(alpha as f32 / 255.0)
+        } else {
+            Ok(1.0)
+        }
+    }
+
+    pub fn transient_for(&self) -> XCapResult<Option<ImplWindow>> {
+        Ok(get_window_owner(self.hwnd).map(ImplWindow::new))
+    }
+
+    pub fn desktop(&self) -> XCapResult<Option<u32>> {
+        // 虚拟桌面的序号没有公开的 API 可以读取
+        Ok(None)
+    }
+
+    pub fn icon(&self, preferred_size: u32) -> XCapResult<Option<RgbaImage>> {
+        match get_window_icon(self.hwnd, preferred_size) {
+            Some(hicon) => capture_icon(hicon),
+            None => Ok(None),
+        }
+    }
+
     pub fn capture_image(&self) -> XCapResult<RgbaImage> {
         capture_window(self)
     }
+
+    pub fn capture_image_as(&self, format: PixelFormat) -> XCapResult<Frame> {
+        // 窗口截图需要缩放并裁剪掉 DWM 边框，只能先得到 RGBA 图像再转换
+        Ok(Frame::from(self.capture_image()?).convert(format))
+    }
+
+    pub fn capture_into(&self, buffer: &mut [u8], stride: usize) -> XCapResult<(u32, u32)> {
+        // 同 capture_image_as，缩放和裁剪后的图像只能再拷贝一次
+        copy_image_into(&self.capture_image()?, buffer, stride)
+    }
+
+    pub fn capture_thumbnail(&self, max_width: u32, max_height: u32) -> XCapResult<RgbaImage> {
+        // PrintWindow 与 WGC 都只能按窗口的实际尺寸绘制，截图后再缩小
+        Ok(thumbnail_image(
+            self.capture_image()?,
+            max_width,
+            max_height,
+        ))
+    }
 }
 
 #[cfg(feature = "wgc")]
@@ -0,0 +1,6 @@
+use crate::{error::XCapResult, workspace::Workspace};
+
+pub fn get_workspaces() -> XCapResult<Vec<Workspace>> {
+    // 虚拟桌面没有公开的 API 可以枚举
+    Ok(Vec::new())
+}
@@ -3,6 +3,7 @@ mod capture;
 mod dxgi_video_recorder;
 #[cfg(not(feature = "wgc"))]
 mod gdi;
+mod icon;
 mod utils;
 #[cfg(feature = "wgc")]
 mod wgc;
@@ -11,4 +12,6 @@ mod wgc_video_recorder;
 
 pub mod impl_monitor;
 pub mod impl_video_recorder;
+pub mod impl_watcher;
 pub mod impl_window;
+pub mod impl_workspace;
@@ -32,7 +32,12 @@ use windows::{
     core::{HRESULT, Interface, PCWSTR, s, w},
 };
 
-use crate::{Frame, XCapError, error::XCapResult};
+use crate::{
+    Frame, XCapError,
+    error::XCapResult,
+    pixel_format::{PixelFormat, convert_rows_into},
+    video_recorder::FramePool,
+};
 
 pub(super) fn get_build_number() -> u32 {
     unsafe {
@@ -90,6 +95,53 @@ pub(super) fn bgra_to_rgba(mut buffer: Vec<u8>) -> Vec<u8> {
     buffer
 }
 
+/// 将系统返回的 BGRA 像素按行转换为 `format` 写入 `dst`
+pub(super) fn bgra_rows_into(
+    src: &[u8],
+    src_stride: usize,
+    width: u32,
+    height: u32,
+    dst: &mut [u8],
+    dst_stride: usize,
+    format: PixelFormat,
+) -> XCapResult<()> {
+    convert_rows_into(
+        src,
+        src_stride,
+        PixelFormat::Bgra,
+        width,
+        height,
+        dst,
+        dst_stride,
+        format,
+    )?;
+    fix_alpha(dst, width, height, dst_stride, format);
+
+    Ok(())
+}
+
+/// 与 bgra_to_rgba 相同，旧版本系统返回的 alpha 可能为 0
+pub(super) fn fix_alpha(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: PixelFormat,
+) {
+    if !matches!(format, PixelFormat::Rgba | PixelFormat::Bgra) || get_os_major_version() >= 8 {
+        return;
+    }
+
+    let row_len = width as usize * 4;
+    for row in buffer.chunks_mut(stride).take(height as usize) {
+        for pixel in row[..row_len].chunks_exact_mut(4) {
+            if pixel[3] == 0 {
+                pixel[3] = 255;
+            }
+        }
+    }
+}
+
 // 定义 GetProcessDpiAwareness 函数的类型
 type GetProcessDpiAwareness =
     unsafe extern "system" fn(hprocess: HANDLE, value: *mut u32) -> HRESULT;
@@ -282,6 +334,8 @@ pub(super) fn texture_to_frame(
     y: u32,
     width: u32,
     height: u32,
+    format: PixelFormat,
+    pool: &FramePool,
 ) -> XCapResult<Frame> {
     unsafe {
         let mut src_desc = D3D11_TEXTURE2D_DESC::default();
@@ -337,21 +391,26 @@ pub(super) fn texture_to_frame(
             Some(&mut mapped),
         )?;
 
-        let mut bgra = vec![0u8; (width * height * 4) as usize];
-        let src_ptr = mapped.pData as *const u8;
-
-        for row in 0..height {
-            let src_offset = (row * mapped.RowPitch) as usize;
-            let dst_offset = (row * width * 4) as usize;
-
-            let src_slice =
-                std::slice::from_raw_parts(src_ptr.add(src_offset), (width * 4) as usize);
-
-            bgra[dst_offset..dst_offset + (width * 4) as usize].copy_from_slice(src_slice);
-        }
+        // 直接从映射的纹理转换为请求的格式，缓冲区从池中复用
+        let row_len = width as usize * format.bytes_per_pixel();
+        let mut raw = pool.take(row_len * height as usize);
+        let src = std::slice::from_raw_parts(
+            mapped.pData as *const u8,
+            mapped.RowPitch as usize * height.saturating_sub(1) as usize + width as usize * 4,
+        );
+        let result = bgra_rows_into(
+            src,
+            mapped.RowPitch as usize,
+            width,
+            height,
+            &mut raw,
+            row_len,
+            format,
+        );
 
         d3d_context.Unmap(Some(&resource), 0);
+        result?;
 
-        Ok(Frame::new(width, height, bgra_to_rgba(bgra.to_owned())))
+        Ok(Frame::new_with_format(width, height, format, raw))
     }
 }
@@ -34,6 +34,8 @@ use windows::{
 use crate::{
     Frame,
     error::{XCapError, XCapResult},
+    pixel_format::PixelFormat,
+    video_recorder::FramePool,
 };
 
 use super::utils::{create_d3d_device, texture_to_frame};
@@ -67,6 +69,8 @@ pub(super) fn get_next_frame(
     y: u32,
     width: u32,
     height: u32,
+    format: PixelFormat,
+    pool: &FramePool,
 ) -> XCapResult<Frame> {
     let frame_pool = frame_pool
         .as_ref()
@@ -94,29 +98,19 @@ pub(super) fn get_next_frame(
         y,
         width,
         height,
+        format,
+        pool,
     )
 }
 
-pub(super) fn process_frame_arrival(
-    frame_pool: Ref<'_, Direct3D11CaptureFramePool>,
-    x: u32,
-    y: u32,
-    width: u32,
-    height: u32,
-) -> XCapResult<RgbaImage> {
-    let frame = get_next_frame(frame_pool, x, y, width, height)?;
-
-    RgbaImage::from_raw(frame.width, frame.height, frame.raw)
-        .ok_or(XCapError::new("RgbaImage::from_raw failed"))
-}
-
 pub(super) fn wgc_capture(
     item: &GraphicsCaptureItem,
     x: u32,
     y: u32,
     width: u32,
     height: u32,
-) -> XCapResult<RgbaImage> {
+    format: PixelFormat,
+) -> XCapResult<Frame> {
     let item_size = item.Size()?;
     let device = {
         let inspectable = unsafe { CreateDirect3D11DeviceFromDXGIDevice(&*IDXGIDEVICE)? };
@@ -141,8 +135,9 @@ pub(super) fn wgc_capture(
     frame_pool.FrameArrived(
         &TypedEventHandler::<Direct3D11CaptureFramePool, IInspectable>::new({
             move |frame_pool, _| {
-                let frame = process_frame_arrival(frame_pool, x, y, width, height)
-                    .map_err(|_| WindowsError::empty())?;
+                let frame =
+                    get_next_frame(frame_pool, x, y, width, height, format, &FramePool::new(0))
+                        .map_err(|_| WindowsError::empty())?;
 
                 let _ = sender.send(frame);
                 Ok(())
@@ -181,16 +176,17 @@ pub(super) fn capture_monitor(
     y: u32,
     width: u32,
     height: u32,
-) -> XCapResult<RgbaImage> {
+    format: PixelFormat,
+) -> XCapResult<Frame> {
     let interop = factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()?;
     let mut monitor_items = MONITOR_GRAPHICS_CAPTURE_ITEM.lock()?;
     let key = h_monitor.0 as usize;
     if let Some(item) = monitor_items.get(&key) {
-        wgc_capture(item, x, y, width, height)
+        wgc_capture(item, x, y, width, height, format)
     } else {
         let item = unsafe { interop.CreateForMonitor::<GraphicsCaptureItem>(h_monitor)? };
         monitor_items.insert(key, item.clone());
-        wgc_capture(&item, x, y, width, height)
+        wgc_capture(&item, x, y, width, height, format)
     }
 }
 
@@ -204,11 +200,14 @@ pub(super) fn capture_window(
     let interop = factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()?;
     let mut window_items = WINDOW_GRAPHICS_CAPTURE_ITEM.lock()?;
     let key = hwnd.0 as usize;
-    if let Some(item) = window_items.get(&key) {
-        wgc_capture(item, x, y, width, height)
+    let frame = if let Some(item) = window_items.get(&key) {
+        wgc_capture(item, x, y, width, height, PixelFormat::Rgba)?
     } else {
         let item: GraphicsCaptureItem = unsafe { interop.CreateForWindow(hwnd)? };
         window_items.insert(key, item.clone());
-        wgc_capture(&item, x, y, width, height)
-    }
+        wgc_capture(&item, x, y, width, height, PixelFormat::Rgba)?
+    };
+
+    RgbaImage::from_raw(frame.width, frame.height, frame.raw)
+        .ok_or(XCapError::new("RgbaImage::from_raw failed"))
 }
@@ -19,7 +19,11 @@ use windows::{
     core::{Error as WindowsError, IInspectable, Interface, factory},
 };
 
-use crate::{XCapResult, video_recorder::Frame};
+use crate::{
+    XCapResult,
+    pixel_format::PixelFormat,
+    video_recorder::{Frame, FramePool},
+};
 
 use super::wgc::{IDXGIDEVICE, get_next_frame};
 
@@ -55,6 +59,8 @@ impl Drop for WgcRuntime {
 pub(crate) struct ImplVideoRecorder {
     item: GraphicsCaptureItem,
     runtime: Arc<Mutex<Option<WgcRuntime>>>,
+    format: PixelFormat,
+    pool: FramePool,
     tx: SyncSender<Frame>,
 }
 
@@ -74,6 +80,8 @@ impl ImplVideoRecorder {
             item_size,
         )?;
 
+        let format = self.format;
+        let pool = self.pool.clone();
         let tx = self.tx.clone();
 
         frame_pool.FrameArrived(
@@ -85,6 +93,8 @@ impl ImplVideoRecorder {
                         0,
                         item_size.Width as u32,
                         item_size.Height as u32,
+                        format,
+                        &pool,
                     )
                     .map_err(|error| {
                         log::error!("wgc get_next_frame failed: {error}");
@@ -117,7 +127,11 @@ impl ImplVideoRecorder {
         })
     }
 
-    pub fn new(h_monitor: HMONITOR) -> XCapResult<(Self, Receiver<Frame>)> {
+    pub fn new(
+        h_monitor: HMONITOR,
+        format: PixelFormat,
+        pool: FramePool,
+    ) -> XCapResult<(Self, Receiver<Frame>)> {
         let interop = factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()?;
         let item = unsafe { interop.CreateForMonitor::<GraphicsCaptureItem>(h_monitor)? };
 
@@ -126,6 +140,8 @@ impl ImplVideoRecorder {
         let recorder = Self {
             item,
             runtime: Arc::new(Mutex::new(None)),
+            format,
+            pool,
             tx,
         };
 
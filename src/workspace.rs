@@ -0,0 +1,30 @@
+use crate::{error::XCapResult, platform::impl_workspace::get_workspaces};
+
+/// A virtual desktop, read from the EWMH `_NET_*DESKTOP*` root window properties on X11.
+///
+/// Platforms without a public workspace API report no workspaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    /// Index of the workspace, as reported by [`Window::desktop`](crate::Window::desktop).
+    pub index: u32,
+    pub name: String,
+    /// Top left corner of the workspace viewport, non-zero on large desktops like Compiz.
+    pub viewport: (i32, i32),
+    pub is_current: bool,
+}
+
+impl Workspace {
+    /// All workspaces, ordered by index.
+    pub fn all() -> XCapResult<Vec<Workspace>> {
+        get_workspaces()
+    }
+
+    /// The workspace shown right now.
+    pub fn current() -> XCapResult<Option<Workspace>> {
+        let workspace = Workspace::all()?
+            .into_iter()
+            .find(|workspace| workspace.is_current);
+
+        Ok(workspace)
+    }
+}